@@ -0,0 +1,462 @@
+//! Hierarchical document tree
+//!
+//! `build_structured_text`/`format_block_group` produce a flat string where
+//! `"## "`/`"### "` headings carry no real parent/child relationship, so
+//! `merge_pages` has nothing to walk when it needs to emit a table of
+//! contents or reason about section nesting. `DocumentTree` builds that
+//! structure after the fact, from the same `ContentElement` stream
+//! [`MarkdownGenerator::build_page_content`] already assembles, without
+//! touching that pipeline.
+//!
+//! Nodes live in a flat arena (a node `Vec` plus parent/child index lists)
+//! rather than a pointer-based tree, which sidesteps the borrow-checker pain
+//! of mutating a tree through owned child references. Headings are pushed
+//! onto a stack as they're seen; a heading first pops every open heading of
+//! the same or a deeper level off the stack before it's pushed, and body blocks
+//! (text/figures) attach as children of whatever heading is currently on
+//! top — exactly the nesting `##`/`###` implies in the flat string today.
+//!
+//! [`MarkdownGenerator::build_page_content`]: crate::markdown_gen::MarkdownGenerator::build_page_content
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::markdown_gen::{ContentElement, PageContent};
+use crate::yomitoku::TextDirection;
+
+/// Leaf content carried by a `DocumentNode::Block`
+#[derive(Debug, Clone)]
+pub enum BlockContent {
+    Text {
+        text: String,
+        direction: TextDirection,
+    },
+    Figure {
+        image_path: PathBuf,
+        caption: Option<String>,
+    },
+    FullPageImage {
+        image_path: PathBuf,
+    },
+}
+
+/// A node in the document tree's flat arena
+#[derive(Debug, Clone)]
+pub enum DocumentNode {
+    /// A `"## "`/`"### "` heading; `level` is 2 or 3, matching
+    /// [`MarkdownGenerator::heading_level`](crate::markdown_gen::MarkdownGenerator)'s scale
+    Section { level: u8, title: String },
+    /// A body block nested under whatever heading was on top of the stack
+    /// when it was encountered (or a root block, if none was)
+    Block { content: BlockContent },
+}
+
+/// A flat arena of [`DocumentNode`]s assembled across every page of a book,
+/// with parent/child indices standing in for tree pointers
+#[derive(Debug, Clone, Default)]
+pub struct DocumentTree {
+    nodes: Vec<DocumentNode>,
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl DocumentTree {
+    /// Create an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append one page's elements to the tree. `stack` is the caller-owned
+    /// heading stack (indices of currently-open sections, shallowest first);
+    /// pass the same `Vec` across pages so a heading on an earlier page can
+    /// still be the parent of body text on a later one.
+    pub fn push_page(&mut self, page: &PageContent, stack: &mut Vec<usize>) {
+        for element in &page.elements {
+            match element {
+                ContentElement::Text { content, direction } => {
+                    self.push_text(content, *direction, stack);
+                }
+                ContentElement::Figure {
+                    image_path,
+                    caption,
+                } => {
+                    self.push_block(
+                        BlockContent::Figure {
+                            image_path: image_path.clone(),
+                            caption: caption.clone(),
+                        },
+                        stack,
+                    );
+                }
+                ContentElement::FullPageImage { image_path } => {
+                    self.push_block(
+                        BlockContent::FullPageImage {
+                            image_path: image_path.clone(),
+                        },
+                        stack,
+                    );
+                }
+                ContentElement::PageBreak => {}
+            }
+        }
+    }
+
+    /// Split a `Text` element the same way [`render_page_content`](crate::markdown_gen::render_page_content)
+    /// does: the first line of each `"\n\n"`-separated paragraph is a
+    /// heading if it carries `"## "`/`"### "` markup, everything else is a
+    /// body block
+    fn push_text(&mut self, content: &str, direction: TextDirection, stack: &mut Vec<usize>) {
+        for paragraph in content.split("\n\n") {
+            let trimmed = paragraph.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let mut lines = trimmed.splitn(2, '\n');
+            let first = lines.next().unwrap_or("");
+            let rest = lines.next();
+
+            if let Some(title) = first.strip_prefix("### ") {
+                self.push_heading(3, title.trim(), stack);
+            } else if let Some(title) = first.strip_prefix("## ") {
+                self.push_heading(2, title.trim(), stack);
+            } else {
+                self.push_block(
+                    BlockContent::Text {
+                        text: trimmed.to_string(),
+                        direction,
+                    },
+                    stack,
+                );
+                continue;
+            }
+
+            if let Some(rest) = rest {
+                let rest = rest.trim();
+                if !rest.is_empty() {
+                    self.push_block(
+                        BlockContent::Text {
+                            text: rest.to_string(),
+                            direction,
+                        },
+                        stack,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Pop any open section of equal or greater depth (a `##` closes a prior
+    /// `##` or `###`, but a `###` only closes a prior `###`), then push the
+    /// new section under whatever remains on top
+    fn push_heading(&mut self, level: u8, title: &str, stack: &mut Vec<usize>) {
+        while let Some(&top) = stack.last() {
+            let should_pop = matches!(&self.nodes[top], DocumentNode::Section { level: top_level, .. } if *top_level >= level);
+            if should_pop {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        let parent = stack.last().copied();
+        let idx = self.push_node(
+            DocumentNode::Section {
+                level,
+                title: title.to_string(),
+            },
+            parent,
+        );
+        stack.push(idx);
+    }
+
+    /// Attach a body block as a child of whatever heading is on top of the
+    /// stack, or as a root node if the stack is empty
+    fn push_block(&mut self, content: BlockContent, stack: &[usize]) {
+        let parent = stack.last().copied();
+        self.push_node(DocumentNode::Block { content }, parent);
+    }
+
+    fn push_node(&mut self, node: DocumentNode, parent: Option<usize>) -> usize {
+        let idx = self.nodes.len();
+        self.nodes.push(node);
+        self.parent.push(parent);
+        self.children.push(Vec::new());
+        if let Some(parent_idx) = parent {
+            self.children[parent_idx].push(idx);
+        }
+        idx
+    }
+
+    /// Node indices with no parent, in document order
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&idx| self.parent[idx].is_none())
+            .collect()
+    }
+
+    /// Child node indices, in document order
+    pub fn children_of(&self, idx: usize) -> &[usize] {
+        &self.children[idx]
+    }
+
+    /// The node at `idx`
+    pub fn node(&self, idx: usize) -> &DocumentNode {
+        &self.nodes[idx]
+    }
+
+    /// Total number of nodes in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the tree has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// All sections in document order as `(node index, level, display title)`,
+    /// with duplicate titles (e.g. a repeated "まえがき" front-matter header)
+    /// renumbered as "title (2)", "title (3)", ... so they're distinguishable
+    /// in a table of contents
+    pub fn sections(&self) -> Vec<(usize, u8, String)> {
+        let mut seen: HashMap<String, u32> = HashMap::new();
+        let mut result = Vec::new();
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if let DocumentNode::Section { level, title } = node {
+                let count = seen.entry(title.clone()).or_insert(0);
+                *count += 1;
+                let display = if *count > 1 {
+                    format!("{} ({})", title, count)
+                } else {
+                    title.clone()
+                };
+                result.push((idx, *level, display));
+            }
+        }
+
+        result
+    }
+
+    /// Render an anchored `# Contents` list, one entry per section, indented
+    /// by heading level and linked to a GitHub-style slug of its (renumbered)
+    /// title
+    pub fn render_toc(&self) -> String {
+        let sections = self.sections();
+        if sections.is_empty() {
+            return String::new();
+        }
+
+        let mut toc = String::from("# Contents\n\n");
+        let mut slug_counts: HashMap<String, u32> = HashMap::new();
+
+        for (_, level, title) in &sections {
+            let indent = "  ".repeat((*level as usize).saturating_sub(2));
+            let slug = unique_slug(title, &mut slug_counts);
+            toc.push_str(&format!("{}- [{}](#{})\n", indent, title, slug));
+        }
+
+        toc.push('\n');
+        toc
+    }
+}
+
+/// Slugify `title` the way GitHub's Markdown renderer anchors headings
+/// (lowercase ASCII, spaces to hyphens, punctuation dropped), then
+/// disambiguate repeats with a `-1`, `-2`, ... suffix the same way GitHub
+/// does when two headings slugify to the same anchor
+fn unique_slug(title: &str, slug_counts: &mut HashMap<String, u32>) -> String {
+    let mut slug = String::new();
+    let mut last_was_dash = false;
+    for c in title.chars() {
+        if c.is_whitespace() {
+            if !last_was_dash && !slug.is_empty() {
+                slug.push('-');
+                last_was_dash = true;
+            }
+        } else if c.is_ascii_punctuation() {
+            continue;
+        } else {
+            slug.push(c.to_ascii_lowercase());
+            last_was_dash = false;
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+
+    let count = slug_counts.entry(slug.clone()).or_insert(0);
+    let unique = if *count == 0 {
+        slug
+    } else {
+        format!("{}-{}", slug, count)
+    };
+    *count += 1;
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_page(page_index: usize, content: &str) -> PageContent {
+        PageContent {
+            page_index,
+            elements: vec![ContentElement::Text {
+                content: content.to_string(),
+                direction: TextDirection::Horizontal,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_push_page_builds_section_hierarchy() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(
+            &text_page(0, "## 第一章\n\n本文1\n\n### 第一節\n\n本文2"),
+            &mut stack,
+        );
+
+        assert_eq!(tree.roots().len(), 1);
+        let chapter = tree.roots()[0];
+        assert!(matches!(
+            tree.node(chapter),
+            DocumentNode::Section { level: 2, title } if title == "第一章"
+        ));
+
+        // 本文1 and 第一節 are both children of 第一章
+        let children = tree.children_of(chapter);
+        assert_eq!(children.len(), 2);
+        assert!(matches!(tree.node(children[0]), DocumentNode::Block { .. }));
+        assert!(matches!(
+            tree.node(children[1]),
+            DocumentNode::Section { level: 3, .. }
+        ));
+
+        // 本文2 nests under 第一節, not 第一章
+        let section_children = tree.children_of(children[1]);
+        assert_eq!(section_children.len(), 1);
+        assert!(matches!(
+            tree.node(section_children[0]),
+            DocumentNode::Block { .. }
+        ));
+    }
+
+    #[test]
+    fn test_push_page_equal_level_pops_stack() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## 第一章\n\n## 第二章"), &mut stack);
+
+        // Two sibling sections at the root, not nested
+        let roots = tree.roots();
+        assert_eq!(roots.len(), 2);
+        assert!(tree.children_of(roots[0]).is_empty());
+    }
+
+    #[test]
+    fn test_push_page_higher_level_pops_deeper_section() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## 章\n\n### 節\n\n## 次の章"), &mut stack);
+
+        // The second "## " heading closes the "### " section, becoming a
+        // sibling of "## 章" at the root, not its child
+        assert_eq!(tree.roots().len(), 2);
+    }
+
+    #[test]
+    fn test_push_page_body_without_heading_is_root_block() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "見出しのない本文"), &mut stack);
+
+        let roots = tree.roots();
+        assert_eq!(roots.len(), 1);
+        assert!(matches!(tree.node(roots[0]), DocumentNode::Block { .. }));
+    }
+
+    #[test]
+    fn test_push_page_figure_attaches_to_open_section() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## 図版の章"), &mut stack);
+        tree.push_page(
+            &PageContent {
+                page_index: 1,
+                elements: vec![ContentElement::Figure {
+                    image_path: PathBuf::from("images/fig.png"),
+                    caption: Some("図".into()),
+                }],
+            },
+            &mut stack,
+        );
+
+        let chapter = tree.roots()[0];
+        let children = tree.children_of(chapter);
+        assert_eq!(children.len(), 1);
+        assert!(matches!(tree.node(children[0]), DocumentNode::Block { .. }));
+    }
+
+    #[test]
+    fn test_heading_stays_open_across_pages() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## 章"), &mut stack);
+        tree.push_page(&text_page(1, "続きの本文"), &mut stack);
+
+        let chapter = tree.roots()[0];
+        assert_eq!(tree.children_of(chapter).len(), 1);
+        assert_eq!(tree.roots().len(), 1);
+    }
+
+    #[test]
+    fn test_sections_renumbers_duplicate_titles() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## まえがき\n\n## 本編\n\n## まえがき"), &mut stack);
+
+        let sections = tree.sections();
+        let titles: Vec<&str> = sections.iter().map(|(_, _, t)| t.as_str()).collect();
+        assert_eq!(titles, vec!["まえがき", "本編", "まえがき (2)"]);
+    }
+
+    #[test]
+    fn test_render_toc_contains_anchored_links() {
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "## Intro\n\n### Background"), &mut stack);
+
+        let toc = tree.render_toc();
+        assert!(toc.starts_with("# Contents\n"));
+        assert!(toc.contains("[Intro](#intro)"));
+        assert!(toc.contains("[Background](#background)"));
+        // Sub-heading is indented relative to its parent
+        let background_line = toc.lines().find(|l| l.contains("Background")).unwrap();
+        assert!(background_line.starts_with("  -"));
+    }
+
+    #[test]
+    fn test_render_toc_empty_tree() {
+        let tree = DocumentTree::new();
+        assert!(tree.render_toc().is_empty());
+    }
+
+    #[test]
+    fn test_unique_slug_disambiguates_repeats() {
+        let mut counts = HashMap::new();
+        assert_eq!(unique_slug("Intro", &mut counts), "intro");
+        assert_eq!(unique_slug("Intro", &mut counts), "intro-1");
+        assert_eq!(unique_slug("Intro", &mut counts), "intro-2");
+    }
+
+    #[test]
+    fn test_tree_len_and_is_empty() {
+        let mut tree = DocumentTree::new();
+        assert!(tree.is_empty());
+        let mut stack = Vec::new();
+        tree.push_page(&text_page(0, "本文のみ"), &mut stack);
+        assert!(!tree.is_empty());
+        assert_eq!(tree.len(), 1);
+    }
+}
@@ -0,0 +1,740 @@
+//! EPUB3 generation module
+//!
+//! `MarkdownGenerator` only emits a single flat Markdown file, which isn't a
+//! reflowable e-book. `EpubGenerator` packages the same `PageContent` stream
+//! into a valid EPUB3 container instead: pages are split into chapters at
+//! `"## "` (`heading_level(2)`) boundaries rather than one-chapter-per-page,
+//! a `nav.xhtml` table of contents is generated from those same headings,
+//! and figures are copied from the images directory into the archive with
+//! matching manifest entries. Reading direction is preserved by setting
+//! `page-progression-direction="rtl"` and `writing-mode: vertical-rl;
+//! -epub-writing-mode: vertical-rl;` when a chapter's source text was
+//! `TextDirection::Vertical`. Creator, language, and a cover image are
+//! opt-in via the same `with_x(mut self, ...) -> Self` builder pattern
+//! [`MarkdownGenerator`](crate::markdown_gen::MarkdownGenerator) uses.
+
+use std::io::Write as IoWrite;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::markdown_gen::{ContentElement, PageContent};
+use crate::yomitoku::TextDirection;
+
+/// Error type for EPUB generation
+#[derive(Debug, Error)]
+pub enum EpubGenError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Zip error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("Generation error: {0}")]
+    GenerationError(String),
+}
+
+/// One EPUB chapter: everything between a `"## "` heading and the next
+#[derive(Debug, Clone)]
+struct Chapter {
+    title: String,
+    body_xhtml: String,
+    vertical: bool,
+}
+
+/// An image copied into `OEBPS/images/` and declared in the manifest
+#[derive(Debug, Clone)]
+struct ManifestImage {
+    id: String,
+    file_name: String,
+    media_type: &'static str,
+    is_cover: bool,
+}
+
+/// EPUB3 generator
+pub struct EpubGenerator {
+    images_dir: PathBuf,
+    creator: Option<String>,
+    language: String,
+    cover_image: Option<PathBuf>,
+}
+
+impl EpubGenerator {
+    /// Create a generator that will copy images from `images_dir` into the
+    /// archive (normally [`MarkdownGenerator::images_dir`](crate::markdown_gen::MarkdownGenerator::images_dir)).
+    /// Language defaults to `"ja"`, matching the OCR pipeline this crate
+    /// targets; creator and cover image are unset unless opted into below.
+    pub fn new(images_dir: &Path) -> Self {
+        Self {
+            images_dir: images_dir.to_path_buf(),
+            creator: None,
+            language: "ja".to_string(),
+            cover_image: None,
+        }
+    }
+
+    /// Set the `<dc:creator>` metadata entry. Omitted from `content.opf` if
+    /// never called.
+    pub fn with_creator(mut self, creator: &str) -> Self {
+        self.creator = Some(creator.to_string());
+        self
+    }
+
+    /// Override the `<dc:language>` metadata entry (default `"ja"`)
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    /// Mark `cover_path` (a file already present under `images_dir`) as the
+    /// EPUB cover: its manifest entry gets `properties="cover-image"`, and a
+    /// legacy `<meta name="cover">` is added alongside it for readers that
+    /// still look for the EPUB2 convention.
+    pub fn with_cover_image(mut self, cover_path: &Path) -> Self {
+        self.cover_image = Some(cover_path.to_path_buf());
+        self
+    }
+
+    /// Write a complete EPUB3 archive for `pages` to `output_path`
+    pub fn generate(
+        &self,
+        output_path: &Path,
+        title: &str,
+        pages: &[PageContent],
+    ) -> Result<(), EpubGenError> {
+        let chapters = Self::split_into_chapters(pages);
+        let rtl = chapters.iter().any(|c| c.vertical);
+        let images = self.collect_manifest_images()?;
+
+        let file = std::fs::File::create(output_path)?;
+        let mut zip = ZipWriter::new(file);
+
+        // The mimetype entry must be first and stored uncompressed, per spec.
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/epub+zip")?;
+
+        let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("META-INF/container.xml", deflated)?;
+        zip.write_all(Self::container_xml().as_bytes())?;
+
+        zip.start_file("OEBPS/content.opf", deflated)?;
+        zip.write_all(
+            self.content_opf(title, &chapters, rtl, &images)
+                .as_bytes(),
+        )?;
+
+        zip.start_file("OEBPS/nav.xhtml", deflated)?;
+        zip.write_all(Self::nav_xhtml(title, &chapters).as_bytes())?;
+
+        for (i, chapter) in chapters.iter().enumerate() {
+            zip.start_file(format!("OEBPS/chapter_{:03}.xhtml", i + 1), deflated)?;
+            zip.write_all(Self::chapter_xhtml(chapter).as_bytes())?;
+        }
+
+        for image in &images {
+            zip.start_file(format!("OEBPS/images/{}", image.file_name), deflated)?;
+            let bytes = std::fs::read(self.images_dir.join(&image.file_name))?;
+            zip.write_all(&bytes)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// List the files in `images_dir` as manifest entries, flagging whichever one
+    /// matches `self.cover_image` (by file name) as the cover
+    fn collect_manifest_images(&self) -> Result<Vec<ManifestImage>, EpubGenError> {
+        let mut images = Vec::new();
+        if !self.images_dir.is_dir() {
+            return Ok(images);
+        }
+
+        let cover_name = self
+            .cover_image
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string());
+
+        for entry in std::fs::read_dir(&self.images_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let is_cover = cover_name.as_deref() == Some(file_name.as_str());
+            images.push(ManifestImage {
+                id: format!("img_{}", Self::sanitize_id(&file_name)),
+                media_type: Self::media_type(&file_name),
+                file_name,
+                is_cover,
+            });
+        }
+        Ok(images)
+    }
+
+    /// Split every page's `ContentElement`s into chapters, cutting a new one
+    /// at each `"## "` heading rather than at page boundaries
+    fn split_into_chapters(pages: &[PageContent]) -> Vec<Chapter> {
+        let mut chapters = Vec::new();
+        let mut title = String::from("Untitled");
+        let mut body = String::new();
+        let mut vertical = false;
+        let mut started = false;
+
+        for page in pages {
+            for element in &page.elements {
+                match element {
+                    ContentElement::Text { content, direction } => {
+                        Self::append_text(
+                            content, *direction, &mut chapters, &mut title, &mut body,
+                            &mut vertical, &mut started,
+                        );
+                    }
+                    ContentElement::Figure {
+                        image_path,
+                        caption,
+                    } => {
+                        started = true;
+                        Self::append_figure(image_path, caption.as_deref(), &mut body);
+                    }
+                    ContentElement::FullPageImage { image_path } => {
+                        started = true;
+                        Self::append_image(image_path, &mut body);
+                    }
+                    ContentElement::PageBreak => {}
+                }
+            }
+        }
+
+        if started {
+            chapters.push(Chapter {
+                title,
+                body_xhtml: body,
+                vertical,
+            });
+        }
+
+        chapters
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn append_text(
+        content: &str,
+        direction: TextDirection,
+        chapters: &mut Vec<Chapter>,
+        title: &mut String,
+        body: &mut String,
+        vertical: &mut bool,
+        started: &mut bool,
+    ) {
+        if direction == TextDirection::Vertical {
+            *vertical = true;
+        }
+
+        for paragraph in content.split("\n\n") {
+            let trimmed = paragraph.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let mut lines = trimmed.splitn(2, '\n');
+            let first = lines.next().unwrap_or("");
+            let rest = lines.next();
+
+            if let Some(heading) = first.strip_prefix("## ") {
+                if *started {
+                    chapters.push(Chapter {
+                        title: title.clone(),
+                        body_xhtml: std::mem::take(body),
+                        vertical: *vertical,
+                    });
+                }
+                *title = heading.trim().to_string();
+                *vertical = direction == TextDirection::Vertical;
+                *started = true;
+                Self::append_paragraph(rest, body);
+                continue;
+            }
+
+            *started = true;
+
+            if let Some(sub_heading) = first.strip_prefix("### ") {
+                body.push_str(&format!("<h2>{}</h2>\n", Self::escape_xml(sub_heading.trim())));
+                Self::append_paragraph(rest, body);
+            } else {
+                body.push_str(&format!(
+                    "<p>{}</p>\n",
+                    Self::escape_xml_preserving_ruby(trimmed)
+                ));
+            }
+        }
+    }
+
+    fn append_paragraph(rest: Option<&str>, body: &mut String) {
+        let Some(rest) = rest else { return };
+        let rest = rest.trim();
+        if !rest.is_empty() {
+            body.push_str(&format!(
+                "<p>{}</p>\n",
+                Self::escape_xml_preserving_ruby(rest)
+            ));
+        }
+    }
+
+    fn append_figure(image_path: &Path, caption: Option<&str>, body: &mut String) {
+        let name = Self::file_name(image_path);
+        body.push_str(&format!(
+            "<figure><img src=\"images/{}\" alt=\"\"/>",
+            name
+        ));
+        if let Some(cap) = caption {
+            body.push_str(&format!("<figcaption>{}</figcaption>", Self::escape_xml(cap)));
+        }
+        body.push_str("</figure>\n");
+    }
+
+    fn append_image(image_path: &Path, body: &mut String) {
+        let name = Self::file_name(image_path);
+        body.push_str(&format!("<img src=\"images/{}\" alt=\"\"/>\n", name));
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    /// Escape `text` for XHTML the same way [`Self::escape_xml`] does,
+    /// except an already-rendered `<ruby>base<rt>reading</rt></ruby>` span
+    /// (produced upstream by furigana pairing, e.g.
+    /// `MarkdownGenerator::normalize_markdown_with_ruby`) passes through
+    /// verbatim instead of being escaped into inert literal text. Content
+    /// outside of a recognized `<ruby>...</ruby>` span is escaped as usual.
+    fn escape_xml_preserving_ruby(text: &str) -> String {
+        let mut out = String::new();
+        let mut plain_start = 0usize;
+        let mut i = 0usize;
+
+        while i < text.len() {
+            if text[i..].starts_with("<ruby>") {
+                if let Some(rel_end) = text[i..].find("</ruby>") {
+                    let end = i + rel_end + "</ruby>".len();
+                    out.push_str(&Self::escape_xml(&text[plain_start..i]));
+                    out.push_str(&text[i..end]);
+                    plain_start = end;
+                    i = end;
+                    continue;
+                }
+            }
+
+            let ch_len = text[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+            i += ch_len;
+        }
+
+        out.push_str(&Self::escape_xml(&text[plain_start..]));
+        out
+    }
+
+    fn media_type(file_name: &str) -> &'static str {
+        match file_name.rsplit('.').next().unwrap_or("").to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "svg" => "image/svg+xml",
+            _ => "image/png",
+        }
+    }
+
+    // Tables from scanned pages are not covered here: `ContentElement` has no
+    // table variant in this pipeline, so there's nothing to walk yet.
+
+    /// Manifest `id` attributes must be valid XML names; non-alphanumeric
+    /// characters in a file name (dots, spaces) are collapsed to `_`
+    fn sanitize_id(file_name: &str) -> String {
+        file_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    fn container_xml() -> String {
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#
+        .to_string()
+    }
+
+    fn content_opf(
+        &self,
+        title: &str,
+        chapters: &[Chapter],
+        rtl: bool,
+        images: &[ManifestImage],
+    ) -> String {
+        let mut manifest = String::new();
+        manifest.push_str(
+            "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+        );
+        for (i, _) in chapters.iter().enumerate() {
+            manifest.push_str(&format!(
+                "    <item id=\"chapter_{0:03}\" href=\"chapter_{0:03}.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+                i + 1
+            ));
+        }
+        for image in images {
+            let properties = if image.is_cover {
+                " properties=\"cover-image\""
+            } else {
+                ""
+            };
+            manifest.push_str(&format!(
+                "    <item id=\"{id}\" href=\"images/{file_name}\" media-type=\"{media_type}\"{properties}/>\n",
+                id = image.id,
+                file_name = image.file_name,
+                media_type = image.media_type,
+                properties = properties,
+            ));
+        }
+
+        let mut spine = String::new();
+        for (i, _) in chapters.iter().enumerate() {
+            spine.push_str(&format!(
+                "    <itemref idref=\"chapter_{:03}\"/>\n",
+                i + 1
+            ));
+        }
+
+        let progression = if rtl {
+            " page-progression-direction=\"rtl\""
+        } else {
+            ""
+        };
+
+        let creator = match &self.creator {
+            Some(creator) => format!("\n    <dc:creator>{}</dc:creator>", Self::escape_xml(creator)),
+            None => String::new(),
+        };
+        // Legacy EPUB2 convention some e-readers still look for, alongside
+        // the EPUB3 `properties="cover-image"` manifest entry above.
+        let cover_meta = match images.iter().find(|image| image.is_cover) {
+            Some(cover) => format!("\n    <meta name=\"cover\" content=\"{}\"/>", cover.id),
+            None => String::new(),
+        };
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">urn:uuid:{title}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{language}</dc:language>{creator}{cover_meta}
+  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine{progression}>
+{spine}  </spine>
+</package>
+"#,
+            title = Self::escape_xml(title),
+            language = Self::escape_xml(&self.language),
+            creator = creator,
+            cover_meta = cover_meta,
+            manifest = manifest,
+            progression = progression,
+            spine = spine,
+        )
+    }
+
+    fn nav_xhtml(title: &str, chapters: &[Chapter]) -> String {
+        let mut items = String::new();
+        for (i, chapter) in chapters.iter().enumerate() {
+            items.push_str(&format!(
+                "      <li><a href=\"chapter_{:03}.xhtml\">{}</a></li>\n",
+                i + 1,
+                Self::escape_xml(&chapter.title)
+            ));
+        }
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+  <head><title>{title}</title></head>
+  <body>
+    <nav epub:type="toc" id="toc">
+      <h1>{title}</h1>
+      <ol>
+{items}      </ol>
+    </nav>
+  </body>
+</html>
+"#,
+            title = Self::escape_xml(title),
+            items = items,
+        )
+    }
+
+    fn chapter_xhtml(chapter: &Chapter) -> String {
+        let style = if chapter.vertical {
+            // `-epub-writing-mode` is the prefixed property older reading
+            // systems (e.g. iBooks) look for before falling back to the
+            // unprefixed one.
+            "writing-mode: vertical-rl; -epub-writing-mode: vertical-rl;"
+        } else {
+            ""
+        };
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+  <head><title>{title}</title></head>
+  <body style="{style}">
+    <h1>{title}</h1>
+{body}  </body>
+</html>
+"#,
+            title = Self::escape_xml(&chapter.title),
+            style = style,
+            body = chapter.body_xhtml,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn text_page(content: &str, direction: TextDirection) -> PageContent {
+        PageContent {
+            page_index: 0,
+            elements: vec![
+                ContentElement::Text {
+                    content: content.to_string(),
+                    direction,
+                },
+                ContentElement::PageBreak,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_split_into_chapters_by_heading() {
+        let pages = vec![
+            text_page("## 第一章\n\n本文1", TextDirection::Horizontal),
+            text_page("## 第二章\n\n本文2", TextDirection::Horizontal),
+        ];
+        let chapters = EpubGenerator::split_into_chapters(&pages);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "第一章");
+        assert_eq!(chapters[1].title, "第二章");
+        assert!(chapters[0].body_xhtml.contains("本文1"));
+    }
+
+    #[test]
+    fn test_split_into_chapters_untitled_before_first_heading() {
+        let pages = vec![text_page("前書き本文\n\n## 本編", TextDirection::Horizontal)];
+        let chapters = EpubGenerator::split_into_chapters(&pages);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "Untitled");
+        assert!(chapters[0].body_xhtml.contains("前書き本文"));
+        assert_eq!(chapters[1].title, "本編");
+    }
+
+    #[test]
+    fn test_split_into_chapters_marks_vertical() {
+        let pages = vec![text_page("## 縦書きの章\n\n本文", TextDirection::Vertical)];
+        let chapters = EpubGenerator::split_into_chapters(&pages);
+        assert!(chapters[0].vertical);
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            EpubGenerator::escape_xml("<a> & \"b\""),
+            "&lt;a&gt; &amp; &quot;b&quot;"
+        );
+    }
+
+    #[test]
+    fn test_media_type_by_extension() {
+        assert_eq!(EpubGenerator::media_type("fig.jpg"), "image/jpeg");
+        assert_eq!(EpubGenerator::media_type("fig.JPEG"), "image/jpeg");
+        assert_eq!(EpubGenerator::media_type("fig.gif"), "image/gif");
+        assert_eq!(EpubGenerator::media_type("fig.png"), "image/png");
+    }
+
+    #[test]
+    fn test_content_opf_sets_rtl_progression() {
+        let chapters = vec![Chapter {
+            title: "章".into(),
+            body_xhtml: String::new(),
+            vertical: true,
+        }];
+        let gen = EpubGenerator::new(Path::new("images"));
+        let opf = gen.content_opf("本", &chapters, true, &[]);
+        assert!(opf.contains("page-progression-direction=\"rtl\""));
+    }
+
+    #[test]
+    fn test_content_opf_omits_rtl_when_not_vertical() {
+        let chapters = vec![Chapter {
+            title: "章".into(),
+            body_xhtml: String::new(),
+            vertical: false,
+        }];
+        let gen = EpubGenerator::new(Path::new("images"));
+        let opf = gen.content_opf("本", &chapters, false, &[]);
+        assert!(!opf.contains("page-progression-direction"));
+    }
+
+    #[test]
+    fn test_content_opf_includes_creator_and_language_when_set() {
+        let gen = EpubGenerator::new(Path::new("images")).with_creator("夏目漱石").with_language("en");
+        let opf = gen.content_opf("本", &[], false, &[]);
+        assert!(opf.contains("<dc:creator>夏目漱石</dc:creator>"));
+        assert!(opf.contains("<dc:language>en</dc:language>"));
+    }
+
+    #[test]
+    fn test_content_opf_omits_creator_when_unset() {
+        let gen = EpubGenerator::new(Path::new("images"));
+        let opf = gen.content_opf("本", &[], false, &[]);
+        assert!(!opf.contains("dc:creator"));
+    }
+
+    #[test]
+    fn test_content_opf_marks_cover_image_in_manifest() {
+        let gen = EpubGenerator::new(Path::new("images")).with_cover_image(Path::new("cover.png"));
+        let images = vec![ManifestImage {
+            id: "img_cover_png".to_string(),
+            file_name: "cover.png".to_string(),
+            media_type: "image/png",
+            is_cover: true,
+        }];
+        let opf = gen.content_opf("本", &[], false, &images);
+        assert!(opf.contains("properties=\"cover-image\""));
+        assert!(opf.contains("<meta name=\"cover\" content=\"img_cover_png\"/>"));
+    }
+
+    #[test]
+    fn test_generate_produces_valid_zip_with_stored_mimetype() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let images_dir = tmpdir.path().join("images");
+        std::fs::create_dir_all(&images_dir).unwrap();
+        std::fs::write(images_dir.join("fig.png"), b"fake-png-bytes").unwrap();
+
+        let gen = EpubGenerator::new(&images_dir);
+        let output_path = tmpdir.path().join("book.epub");
+        let pages = vec![text_page("## 章\n\n本文", TextDirection::Horizontal)];
+        gen.generate(&output_path, "テスト本", &pages).unwrap();
+
+        let file = std::fs::File::open(&output_path).unwrap();
+        let mut archive = zip::ZipArchive::new(file).unwrap();
+
+        let mut mimetype_file = archive.by_name("mimetype").unwrap();
+        assert_eq!(
+            mimetype_file.compression(),
+            zip::CompressionMethod::Stored
+        );
+        let mut contents = String::new();
+        mimetype_file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "application/epub+zip");
+        drop(mimetype_file);
+
+        assert!(archive.by_name("META-INF/container.xml").is_ok());
+        assert!(archive.by_name("OEBPS/content.opf").is_ok());
+        assert!(archive.by_name("OEBPS/nav.xhtml").is_ok());
+        assert!(archive.by_name("OEBPS/chapter_001.xhtml").is_ok());
+        assert!(archive.by_name("OEBPS/images/fig.png").is_ok());
+    }
+
+    #[test]
+    fn test_generate_wires_cover_image_through_to_content_opf() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let images_dir = tmpdir.path().join("images");
+        std::fs::create_dir_all(&images_dir).unwrap();
+        std::fs::write(images_dir.join("cover.png"), b"fake-png-bytes").unwrap();
+
+        let gen = EpubGenerator::new(&images_dir)
+            .with_cover_image(Path::new("cover.png"))
+            .with_creator("著者");
+        let output_path = tmpdir.path().join("book.epub");
+        let pages = vec![text_page("## 章\n\n本文", TextDirection::Horizontal)];
+        gen.generate(&output_path, "テスト本", &pages).unwrap();
+
+        let file = std::fs::File::open(&output_path).unwrap();
+        let mut archive = zip::ZipArchive::new(file).unwrap();
+        let mut opf_file = archive.by_name("OEBPS/content.opf").unwrap();
+        let mut opf = String::new();
+        opf_file.read_to_string(&mut opf).unwrap();
+
+        assert!(opf.contains("properties=\"cover-image\""));
+        assert!(opf.contains("<dc:creator>著者</dc:creator>"));
+    }
+
+    #[test]
+    fn test_sanitize_id_collapses_non_alphanumeric_characters() {
+        assert_eq!(EpubGenerator::sanitize_id("fig 01.png"), "fig_01_png");
+    }
+
+    #[test]
+    fn test_escape_xml_preserving_ruby_passes_ruby_span_through_unescaped() {
+        let escaped =
+            EpubGenerator::escape_xml_preserving_ruby("<ruby>漢字<rt>かんじ</rt></ruby>です");
+        assert_eq!(escaped, "<ruby>漢字<rt>かんじ</rt></ruby>です");
+    }
+
+    #[test]
+    fn test_escape_xml_preserving_ruby_still_escapes_surrounding_text() {
+        let escaped = EpubGenerator::escape_xml_preserving_ruby(
+            "A<B & <ruby>漢字<rt>かんじ</rt></ruby>",
+        );
+        assert_eq!(escaped, "A&lt;B &amp; <ruby>漢字<rt>かんじ</rt></ruby>");
+    }
+
+    #[test]
+    fn test_escape_xml_preserving_ruby_escapes_unterminated_ruby_tag() {
+        let escaped = EpubGenerator::escape_xml_preserving_ruby("<ruby>漢字");
+        assert_eq!(escaped, "&lt;ruby&gt;漢字");
+    }
+
+    #[test]
+    fn test_generate_preserves_ruby_element_in_chapter_xhtml() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let images_dir = tmpdir.path().join("images");
+        std::fs::create_dir_all(&images_dir).unwrap();
+
+        let gen = EpubGenerator::new(&images_dir);
+        let output_path = tmpdir.path().join("book.epub");
+        let pages = vec![text_page(
+            "## 章\n\n<ruby>漢字<rt>かんじ</rt></ruby>です",
+            TextDirection::Horizontal,
+        )];
+        gen.generate(&output_path, "ルビ本", &pages).unwrap();
+
+        let file = std::fs::File::open(&output_path).unwrap();
+        let mut archive = zip::ZipArchive::new(file).unwrap();
+        let mut chapter_file = archive.by_name("OEBPS/chapter_001.xhtml").unwrap();
+        let mut chapter_xhtml = String::new();
+        chapter_file.read_to_string(&mut chapter_xhtml).unwrap();
+
+        assert!(chapter_xhtml.contains("<ruby>漢字<rt>かんじ</rt></ruby>です"));
+    }
+}
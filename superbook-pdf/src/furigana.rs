@@ -0,0 +1,217 @@
+//! Optional furigana annotation of kanji runs
+//!
+//! Japanese OCR output in [`ContentElement::Text`](crate::markdown_gen::ContentElement::Text)
+//! is kanji-dense, which makes it hard to search or read for learners. This
+//! module implements a longest-match segmentation over a kanji-compound to
+//! reading dictionary: walk the (NFKC-normalized) input left to right, and at
+//! every kanji run try the longest dictionary key that matches at that
+//! offset. A hit is rendered as ruby (Markdown `{漢字|かんじ}` or an HTML
+//! `<ruby>` element); a miss passes the run through unchanged. The dictionary
+//! is a trait so callers can supply their own instead of [`BuiltinReadingDict`].
+
+use std::collections::HashMap;
+
+use unicode_normalization::UnicodeNormalization;
+
+/// A kanji-compound to reading lookup. `max_key_len` bounds how many
+/// characters the longest-match search tries at each position, so a single
+/// miss on a long candidate doesn't require re-scanning the whole dictionary.
+pub trait ReadingDictionary {
+    /// Look up the reading for an exact kanji-compound match
+    fn lookup(&self, compound: &str) -> Option<&str>;
+
+    /// Length (in chars) of the longest key in this dictionary
+    fn max_key_len(&self) -> usize;
+}
+
+/// Small bundled dictionary of common kanji compounds. A real deployment
+/// would generate this table at build time (e.g. via a `phf`-backed
+/// build script reading a binary dictionary file); this hand-written table
+/// is a stand-in with the same lookup interface.
+pub struct BuiltinReadingDict {
+    table: HashMap<&'static str, &'static str>,
+    max_key_len: usize,
+}
+
+const BUILTIN_READINGS: &[(&str, &str)] = &[
+    ("日本", "にほん"),
+    ("今日", "きょう"),
+    ("東京", "とうきょう"),
+    ("大阪", "おおさか"),
+    ("図書館", "としょかん"),
+    ("先生", "せんせい"),
+    ("学校", "がっこう"),
+    ("電車", "でんしゃ"),
+    ("新幹線", "しんかんせん"),
+    ("漢字", "かんじ"),
+    ("言葉", "ことば"),
+    ("時間", "じかん"),
+    ("世界", "せかい"),
+    ("会社", "かいしゃ"),
+    ("友達", "ともだち"),
+];
+
+impl BuiltinReadingDict {
+    pub fn new() -> Self {
+        let table: HashMap<&'static str, &'static str> = BUILTIN_READINGS.iter().copied().collect();
+        let max_key_len = table.keys().map(|k| k.chars().count()).max().unwrap_or(0);
+        Self { table, max_key_len }
+    }
+}
+
+impl Default for BuiltinReadingDict {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadingDictionary for BuiltinReadingDict {
+    fn lookup(&self, compound: &str) -> Option<&str> {
+        self.table.get(compound).copied()
+    }
+
+    fn max_key_len(&self) -> usize {
+        self.max_key_len
+    }
+}
+
+/// Output shape for an annotated kanji run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RubyFormat {
+    /// Markdown-extension ruby: `{漢字|かんじ}`
+    Brackets,
+    /// HTML ruby element: `<ruby>漢字<rt>かんじ</rt></ruby>`
+    Html,
+}
+
+impl RubyFormat {
+    fn render(self, base: &str, reading: &str) -> String {
+        match self {
+            RubyFormat::Brackets => format!("{{{}|{}}}", base, reading),
+            RubyFormat::Html => format!("<ruby>{}<rt>{}</rt></ruby>", base, reading),
+        }
+    }
+}
+
+fn is_kanji(c: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&c)
+}
+
+/// Length of the contiguous run of kanji characters starting at `start`
+fn kanji_run_len(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|&&c| is_kanji(c)).count()
+}
+
+/// Annotate every kanji run in `text` that has a dictionary match with its
+/// reading, rendered as `format`. Characters outside of matched runs (kana,
+/// latin, punctuation, and kanji with no dictionary hit) pass through
+/// unchanged. `text` is NFKC-normalized first so half/full-width variants
+/// collapse before lookup.
+pub fn annotate_with_furigana(text: &str, dict: &dyn ReadingDictionary, format: RubyFormat) -> String {
+    let normalized: String = text.nfkc().collect();
+    let chars: Vec<char> = normalized.chars().collect();
+    let mut out = String::with_capacity(normalized.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_kanji(chars[i]) {
+            let run_len = kanji_run_len(&chars, i);
+            let max_len = dict.max_key_len().min(run_len);
+            let mut matched = None;
+
+            for len in (1..=max_len).rev() {
+                let candidate: String = chars[i..i + len].iter().collect();
+                if let Some(reading) = dict.lookup(&candidate) {
+                    matched = Some((candidate, reading, len));
+                    break;
+                }
+            }
+
+            if let Some((base, reading, len)) = matched {
+                out.push_str(&format.render(&base, reading));
+                i += len;
+                continue;
+            }
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_brackets_format() {
+        let dict = BuiltinReadingDict::new();
+        let result = annotate_with_furigana("今日は東京に行く", &dict, RubyFormat::Brackets);
+        assert_eq!(result, "{今日|きょう}は{東京|とうきょう}に行く");
+    }
+
+    #[test]
+    fn test_annotate_html_format() {
+        let dict = BuiltinReadingDict::new();
+        let result = annotate_with_furigana("漢字", &dict, RubyFormat::Html);
+        assert_eq!(result, "<ruby>漢字<rt>かんじ</rt></ruby>");
+    }
+
+    #[test]
+    fn test_annotate_longest_match_preferred() {
+        let dict = BuiltinReadingDict::new();
+        // "新幹線" (3 chars) must win over any shorter prefix match
+        let result = annotate_with_furigana("新幹線に乗る", &dict, RubyFormat::Brackets);
+        assert!(result.starts_with("{新幹線|しんかんせん}"));
+    }
+
+    #[test]
+    fn test_annotate_unknown_kanji_passes_through() {
+        let dict = BuiltinReadingDict::new();
+        let result = annotate_with_furigana("未知の語", &dict, RubyFormat::Brackets);
+        assert_eq!(result, "未知の語");
+    }
+
+    #[test]
+    fn test_annotate_leaves_kana_and_latin_untouched() {
+        let dict = BuiltinReadingDict::new();
+        let result = annotate_with_furigana("ABC123 ひらがな カタカナ", &dict, RubyFormat::Brackets);
+        assert_eq!(result, "ABC123 ひらがな カタカナ");
+    }
+
+    #[test]
+    fn test_annotate_normalizes_fullwidth_before_lookup() {
+        let dict = BuiltinReadingDict::new();
+        // Fullwidth latin digits/letters around a real compound shouldn't break matching
+        let result = annotate_with_furigana("Ａ日本１", &dict, RubyFormat::Brackets);
+        assert_eq!(result, "A{日本|にほん}1");
+    }
+
+    #[test]
+    fn test_annotate_empty_string() {
+        let dict = BuiltinReadingDict::new();
+        assert_eq!(annotate_with_furigana("", &dict, RubyFormat::Brackets), "");
+    }
+
+    #[test]
+    fn test_builtin_dict_max_key_len() {
+        let dict = BuiltinReadingDict::new();
+        assert_eq!(dict.max_key_len(), 3); // "図書館" / "新幹線" etc.
+    }
+
+    #[test]
+    fn test_kanji_run_len_stops_at_kana() {
+        let chars: Vec<char> = "日本語はいい".chars().collect();
+        assert_eq!(kanji_run_len(&chars, 0), 3);
+    }
+
+    #[test]
+    fn test_is_kanji_boundaries() {
+        assert!(is_kanji('日'));
+        assert!(!is_kanji('あ'));
+        assert!(!is_kanji('ア'));
+        assert!(!is_kanji('A'));
+    }
+}
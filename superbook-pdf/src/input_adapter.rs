@@ -0,0 +1,284 @@
+//! Pluggable input-format adapters
+//!
+//! `MarkdownPipeline` only needs one thing from whatever file it's handed: a
+//! list of per-page images, rasterized at the requested DPI, sitting in a
+//! work directory in reading order. [`InputAdapter`] is that seam — every
+//! format-specific quirk (PDF rasterization, unpacking a CBZ, reading a
+//! directory of scans) lives behind it, so the OCR/figure/deskew stages
+//! downstream never need to know what the original file was.
+
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Error type for input-format adapters
+#[derive(Debug, Error)]
+pub enum InputAdapterError {
+    #[error("unsupported input format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("extraction failed: {0}")]
+    ExtractionFailed(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Produces per-page images from one input format
+///
+/// `--dpi`, `--deskew`, `--upscale`, and `--figure-sensitivity` are applied
+/// uniformly by the pipeline to whatever images an adapter returns, so an
+/// adapter only needs to answer "what are the pages" — it never sees those
+/// options itself (except `dpi`, which only matters to adapters that render
+/// vector content).
+pub trait InputAdapter {
+    /// File extensions (lowercase, no leading dot) this adapter handles
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// `true` if this format must be rendered at a DPI (e.g. a PDF's vector
+    /// content); `false` if it's already page images (e.g. a CBZ or a
+    /// directory of scans), in which case the DPI render step is skipped
+    /// and the images are used as-is.
+    fn needs_rasterization(&self) -> bool;
+
+    /// Produce one image file per page under `output_dir`, in reading
+    /// order. `dpi` is ignored by adapters where [`Self::needs_rasterization`]
+    /// is `false`.
+    fn rasterize(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        dpi: u32,
+    ) -> Result<Vec<PathBuf>, InputAdapterError>;
+}
+
+/// Wraps the existing PDF rasterizer ([`crate::LopdfExtractor`])
+pub struct PdfAdapter;
+
+impl InputAdapter for PdfAdapter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pdf"]
+    }
+
+    fn needs_rasterization(&self) -> bool {
+        true
+    }
+
+    fn rasterize(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        dpi: u32,
+    ) -> Result<Vec<PathBuf>, InputAdapterError> {
+        let extract_options = crate::ExtractOptions::builder().dpi(dpi).build();
+        let pages = crate::LopdfExtractor::extract_auto(input, output_dir, &extract_options)
+            .map_err(|e| InputAdapterError::ExtractionFailed(e.to_string()))?;
+        Ok(pages.into_iter().map(|p| p.path).collect())
+    }
+}
+
+/// Reads a directory of pre-rendered page images (sorted lexically by file
+/// name, so page numbers need zero-padding) without any rasterization step
+pub struct ImageSequenceAdapter;
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "tif", "tiff"];
+
+impl InputAdapter for ImageSequenceAdapter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn needs_rasterization(&self) -> bool {
+        false
+    }
+
+    fn rasterize(
+        &self,
+        input: &Path,
+        _output_dir: &Path,
+        _dpi: u32,
+    ) -> Result<Vec<PathBuf>, InputAdapterError> {
+        let mut pages: Vec<PathBuf> = std::fs::read_dir(input)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+                    .unwrap_or(false)
+            })
+            .collect();
+        pages.sort();
+        Ok(pages)
+    }
+}
+
+/// Unpacks a CBZ (a ZIP archive of scanned page images) into `output_dir`
+pub struct CbzAdapter;
+
+impl InputAdapter for CbzAdapter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["cbz", "zip"]
+    }
+
+    fn needs_rasterization(&self) -> bool {
+        false
+    }
+
+    fn rasterize(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        _dpi: u32,
+    ) -> Result<Vec<PathBuf>, InputAdapterError> {
+        let file = std::fs::File::open(input)?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| InputAdapterError::ExtractionFailed(e.to_string()))?;
+
+        std::fs::create_dir_all(output_dir)?;
+        let mut pages = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .map_err(|e| InputAdapterError::ExtractionFailed(e.to_string()))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let name = Path::new(entry.name())
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("page_{:05}.img", index));
+            let is_image = Path::new(&name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+                .unwrap_or(false);
+            if !is_image {
+                continue;
+            }
+            let dest = output_dir.join(&name);
+            let mut out_file = std::fs::File::create(&dest)?;
+            std::io::copy(&mut entry, &mut out_file)?;
+            pages.push(dest);
+        }
+        pages.sort();
+        Ok(pages)
+    }
+}
+
+/// Rasterizes a multi-page DjVu document by shelling out to `ddjvu`
+/// (DjVuLibre), the same way [`crate::yomitoku`] bridges to an external OCR
+/// process rather than reimplementing it in Rust
+pub struct DjvuAdapter;
+
+impl InputAdapter for DjvuAdapter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["djvu", "djv"]
+    }
+
+    fn needs_rasterization(&self) -> bool {
+        true
+    }
+
+    fn rasterize(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        dpi: u32,
+    ) -> Result<Vec<PathBuf>, InputAdapterError> {
+        std::fs::create_dir_all(output_dir)?;
+        let page_count = std::process::Command::new("djvused")
+            .args(["-e", "n"])
+            .arg(input)
+            .output()
+            .map_err(|e| InputAdapterError::ExtractionFailed(format!("djvused: {e}")))?;
+        let page_count: usize = String::from_utf8_lossy(&page_count.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| InputAdapterError::ExtractionFailed("could not read page count".to_string()))?;
+
+        let mut pages = Vec::with_capacity(page_count);
+        for page in 1..=page_count {
+            let dest = output_dir.join(format!("page_{:05}.png", page));
+            let status = std::process::Command::new("ddjvu")
+                .args(["-format=png", &format!("-page={page}"), &format!("-resolution={dpi}")])
+                .arg(input)
+                .arg(&dest)
+                .status()
+                .map_err(|e| InputAdapterError::ExtractionFailed(format!("ddjvu: {e}")))?;
+            if !status.success() {
+                return Err(InputAdapterError::ExtractionFailed(format!(
+                    "ddjvu exited with {status} on page {page}"
+                )));
+            }
+            pages.push(dest);
+        }
+        Ok(pages)
+    }
+}
+
+/// Picks the [`InputAdapter`] for a given input path: by file extension for
+/// single-file formats, or [`ImageSequenceAdapter`] if the path is a
+/// directory of page images
+pub struct InputAdapterRegistry;
+
+impl InputAdapterRegistry {
+    pub fn for_path(path: &Path) -> Option<Box<dyn InputAdapter>> {
+        if path.is_dir() {
+            return Some(Box::new(ImageSequenceAdapter));
+        }
+
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pdf" => Some(Box::new(PdfAdapter)),
+            "cbz" | "zip" => Some(Box::new(CbzAdapter)),
+            "djvu" | "djv" => Some(Box::new(DjvuAdapter)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_picks_pdf_adapter_by_extension() {
+        let adapter = InputAdapterRegistry::for_path(Path::new("book.pdf")).unwrap();
+        assert!(adapter.needs_rasterization());
+        assert_eq!(adapter.extensions(), &["pdf"]);
+    }
+
+    #[test]
+    fn test_registry_picks_cbz_adapter_by_extension() {
+        let adapter = InputAdapterRegistry::for_path(Path::new("book.cbz")).unwrap();
+        assert!(!adapter.needs_rasterization());
+    }
+
+    #[test]
+    fn test_registry_returns_none_for_unknown_extension() {
+        assert!(InputAdapterRegistry::for_path(Path::new("book.docx")).is_none());
+    }
+
+    #[test]
+    fn test_registry_picks_image_sequence_adapter_for_directory() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let adapter = InputAdapterRegistry::for_path(tmpdir.path()).unwrap();
+        assert!(!adapter.needs_rasterization());
+    }
+
+    #[test]
+    fn test_image_sequence_adapter_sorts_and_filters_images() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        std::fs::write(tmpdir.path().join("page_002.png"), b"fake").unwrap();
+        std::fs::write(tmpdir.path().join("page_001.png"), b"fake").unwrap();
+        std::fs::write(tmpdir.path().join("notes.txt"), b"not an image").unwrap();
+
+        let pages = ImageSequenceAdapter
+            .rasterize(tmpdir.path(), tmpdir.path(), 300)
+            .unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].ends_with("page_001.png"));
+        assert!(pages[1].ends_with("page_002.png"));
+    }
+}
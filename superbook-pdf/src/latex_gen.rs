@@ -0,0 +1,478 @@
+//! upLaTeX generation module
+//!
+//! Generates compilable upLaTeX documents from the same `PageContent`/
+//! `ContentElement` values produced by [`MarkdownGenerator::build_page_content`],
+//! so pLaTeX output stays consistent with the Markdown backend instead of
+//! re-deriving block sorting and heading detection from scratch. Vertical
+//! (tategaki) Japanese pages are wrapped in a `plext` `tate` environment,
+//! which Markdown has no way to represent.
+//!
+//! [`MarkdownGenerator::build_page_content`]: crate::markdown_gen::MarkdownGenerator::build_page_content
+
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::markdown_gen::{
+    relative_image_path, render_page_content, sanitize_filename, ContentElement, DocumentRenderer,
+    PageContent,
+};
+use crate::yomitoku::TextDirection;
+
+/// Error type for LaTeX generation
+#[derive(Debug, Error)]
+pub enum LatexGenError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Generation error: {0}")]
+    GenerationError(String),
+}
+
+/// upLaTeX generator
+pub struct LatexGenerator {
+    output_dir: PathBuf,
+    images_dir: PathBuf,
+    pages_dir: PathBuf,
+    /// (vertical, horizontal/mixed) page counts seen so far, tallied by
+    /// `generate_page_latex` so `merge_tex` can pick a document-wide
+    /// typesetting mode without needing the original `PageContent`s again
+    direction_counts: std::cell::RefCell<(usize, usize)>,
+}
+
+impl LatexGenerator {
+    /// Create a new generator with output directories
+    pub fn new(output_dir: &Path) -> Result<Self, LatexGenError> {
+        let images_dir = output_dir.join("images");
+        let pages_dir = output_dir.join("pages_tex");
+
+        std::fs::create_dir_all(&images_dir)?;
+        std::fs::create_dir_all(&pages_dir)?;
+
+        Ok(Self {
+            output_dir: output_dir.to_path_buf(),
+            images_dir,
+            pages_dir,
+            direction_counts: std::cell::RefCell::new((0, 0)),
+        })
+    }
+
+    /// Generate upLaTeX for a single page, reusing the `ContentElement`s
+    /// already assembled by `MarkdownGenerator::build_page_content` via the
+    /// shared [`DocumentRenderer`] walk
+    pub fn generate_page_latex(
+        &self,
+        page_content: &PageContent,
+    ) -> Result<String, LatexGenError> {
+        let vertical = page_content.elements.iter().any(|element| {
+            matches!(
+                element,
+                ContentElement::Text {
+                    direction: TextDirection::Vertical,
+                    ..
+                }
+            )
+        });
+
+        if vertical {
+            self.direction_counts.borrow_mut().0 += 1;
+        } else {
+            self.direction_counts.borrow_mut().1 += 1;
+        }
+
+        let body = render_page_content(self, page_content);
+
+        let mut tex = String::new();
+        if vertical {
+            writeln!(tex, "\\begin{{tate}}").ok();
+        }
+        tex.push_str(&body);
+        if vertical {
+            writeln!(tex, "\\end{{tate}}").ok();
+        }
+
+        Ok(tex)
+    }
+
+    /// Escape LaTeX special characters so OCR'd text can't break compilation
+    fn escape(text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\\' => out.push_str("\\textbackslash{}"),
+                '{' => out.push_str("\\{"),
+                '}' => out.push_str("\\}"),
+                '#' => out.push_str("\\#"),
+                '$' => out.push_str("\\$"),
+                '%' => out.push_str("\\%"),
+                '&' => out.push_str("\\&"),
+                '_' => out.push_str("\\_"),
+                '^' => out.push_str("\\textasciicircum{}"),
+                '~' => out.push_str("\\textasciitilde{}"),
+                '`' => out.push_str("\\textasciigrave{}"),
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Save page LaTeX to the pages directory
+    pub fn save_page_latex(
+        &self,
+        page_index: usize,
+        content: &str,
+    ) -> Result<PathBuf, LatexGenError> {
+        let page_path = self
+            .pages_dir
+            .join(format!("page_{:03}.tex", page_index + 1));
+        std::fs::write(&page_path, content)?;
+        Ok(page_path)
+    }
+
+    /// Merge all page fragments into a single compilable upLaTeX document,
+    /// wrapped in a title-parameterized preamble with the CJK packages
+    /// needed for vertical Japanese text
+    pub fn merge_pages(
+        &self,
+        title: &str,
+        total_pages: usize,
+    ) -> Result<PathBuf, LatexGenError> {
+        let output_path = self
+            .output_dir
+            .join(format!("{}.tex", sanitize_filename(title)));
+        let mut merged = String::new();
+
+        writeln!(merged, "\\documentclass[uplatex,a4paper]{{jsbook}}").ok();
+        writeln!(merged, "\\usepackage[uplatex]{{otf}}").ok();
+        writeln!(merged, "\\usepackage{{plext}}").ok();
+        writeln!(merged, "\\usepackage{{graphicx}}").ok();
+        writeln!(merged, "\\title{{{}}}", Self::escape(title)).ok();
+        writeln!(merged, "\\begin{{document}}").ok();
+        writeln!(merged, "\\maketitle").ok();
+        writeln!(merged).ok();
+
+        for i in 0..total_pages {
+            let page_path = self.pages_dir.join(format!("page_{:03}.tex", i + 1));
+            if page_path.exists() {
+                let content = std::fs::read_to_string(&page_path)?;
+                merged.push_str(&content);
+            }
+        }
+
+        writeln!(merged, "\\end{{document}}").ok();
+
+        std::fs::write(&output_path, &merged)?;
+        Ok(output_path)
+    }
+
+    /// `merge_pages`-equivalent entry point that additionally selects the
+    /// `tate` `jsbook` class option when the pages seen so far (via
+    /// `generate_page_latex`) are predominantly vertical, so a wholly
+    /// tategaki book gets document-wide vertical typesetting instead of
+    /// per-page `plext` `tate` environments fighting a horizontal layout.
+    pub fn merge_tex(&self, title: &str, total_pages: usize) -> Result<PathBuf, LatexGenError> {
+        let output_path = self
+            .output_dir
+            .join(format!("{}.tex", sanitize_filename(title)));
+        let mut merged = String::new();
+
+        let class_options = if self.dominant_direction_is_vertical() {
+            "tate,uplatex,a4paper"
+        } else {
+            "uplatex,a4paper"
+        };
+        writeln!(merged, "\\documentclass[{}]{{jsbook}}", class_options).ok();
+        writeln!(merged, "\\usepackage[uplatex]{{otf}}").ok();
+        writeln!(merged, "\\usepackage{{plext}}").ok();
+        writeln!(merged, "\\usepackage{{graphicx}}").ok();
+        writeln!(merged, "\\title{{{}}}", Self::escape(title)).ok();
+        writeln!(merged, "\\begin{{document}}").ok();
+        writeln!(merged, "\\maketitle").ok();
+        writeln!(merged).ok();
+
+        for i in 0..total_pages {
+            let page_path = self.pages_dir.join(format!("page_{:03}.tex", i + 1));
+            if page_path.exists() {
+                let content = std::fs::read_to_string(&page_path)?;
+                merged.push_str(&content);
+            }
+        }
+
+        writeln!(merged, "\\end{{document}}").ok();
+
+        std::fs::write(&output_path, &merged)?;
+        Ok(output_path)
+    }
+
+    /// Whether more pages seen by `generate_page_latex` so far were vertical
+    /// than not
+    fn dominant_direction_is_vertical(&self) -> bool {
+        let (vertical, horizontal) = *self.direction_counts.borrow();
+        vertical > horizontal
+    }
+
+    /// Get images directory path
+    pub fn images_dir(&self) -> &Path {
+        &self.images_dir
+    }
+
+    /// Get pages directory path
+    pub fn pages_dir(&self) -> &Path {
+        &self.pages_dir
+    }
+
+    /// Get image path relative to the output directory for `\includegraphics`
+    fn relative_image_path(&self, abs_path: &Path) -> String {
+        relative_image_path(&self.output_dir, abs_path)
+    }
+}
+
+impl DocumentRenderer for LatexGenerator {
+    fn emit_heading(&self, level: u8, text: &str) -> String {
+        let command = if level >= 3 { "subsection" } else { "section" };
+        format!("\\{}{{{}}}\n", command, Self::escape(text))
+    }
+
+    fn emit_paragraph(&self, text: &str, _direction: TextDirection) -> String {
+        let mut out = String::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if !line.is_empty() {
+                writeln!(out, "{}", Self::escape(line)).ok();
+            }
+        }
+        writeln!(out).ok();
+        out
+    }
+
+    fn emit_figure(&self, image_path: &Path, caption: Option<&str>) -> String {
+        let rel_path = self.relative_image_path(image_path);
+        let mut out = String::new();
+        writeln!(out, "\\begin{{figure}}[h]").ok();
+        writeln!(out, "\\centering").ok();
+        writeln!(out, "\\includegraphics[width=\\linewidth]{{{}}}", rel_path).ok();
+        if let Some(cap) = caption {
+            writeln!(out, "\\caption{{{}}}", Self::escape(cap)).ok();
+        }
+        writeln!(out, "\\end{{figure}}").ok();
+        writeln!(out).ok();
+        out
+    }
+
+    fn emit_full_page_image(&self, image_path: &Path) -> String {
+        self.emit_figure(image_path, None)
+    }
+
+    fn emit_page_break(&self) -> String {
+        "\\clearpage\n\n".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_page_latex_text() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![
+                ContentElement::Text {
+                    content: "テスト段落です。".into(),
+                    direction: TextDirection::Horizontal,
+                },
+                ContentElement::PageBreak,
+            ],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.contains("テスト段落です。"));
+        assert!(tex.contains("\\clearpage"));
+        assert!(!tex.contains("\\begin{tate}"));
+    }
+
+    #[test]
+    fn test_generate_page_latex_vertical_wraps_in_tate() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "縦書きテスト".into(),
+                direction: TextDirection::Vertical,
+            }],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.contains("\\begin{tate}"));
+        assert!(tex.contains("\\end{tate}"));
+    }
+
+    #[test]
+    fn test_generate_page_latex_maps_headings() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "## 大見出し\n\n### 小見出し\n\n本文".into(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.contains("\\section{大見出し}"));
+        assert!(tex.contains("\\subsection{小見出し}"));
+        assert!(tex.contains("本文"));
+    }
+
+    #[test]
+    fn test_generate_page_latex_figure_with_caption() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+        let img_path = tmpdir.path().join("images").join("fig.png");
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Figure {
+                image_path: img_path,
+                caption: Some("テスト図".into()),
+            }],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.contains("\\includegraphics[width=\\linewidth]{images/fig.png}"));
+        assert!(tex.contains("\\caption{テスト図}"));
+    }
+
+    #[test]
+    fn test_generate_page_latex_full_page_image_no_caption() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+        let img_path = tmpdir.path().join("images").join("cover.png");
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::FullPageImage {
+                image_path: img_path,
+            }],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.contains("\\includegraphics[width=\\linewidth]{images/cover.png}"));
+        assert!(!tex.contains("\\caption"));
+    }
+
+    #[test]
+    fn test_escape_special_characters() {
+        let escaped = LatexGenerator::escape("50% & #1_2^3 {test} \\ end");
+        assert!(escaped.contains("\\%"));
+        assert!(escaped.contains("\\&"));
+        assert!(escaped.contains("\\#"));
+        assert!(escaped.contains("\\_"));
+        assert!(escaped.contains("\\textasciicircum{}"));
+        assert!(escaped.contains("\\{"));
+        assert!(escaped.contains("\\}"));
+        assert!(escaped.contains("\\textbackslash{}"));
+    }
+
+    #[test]
+    fn test_escape_backtick() {
+        let escaped = LatexGenerator::escape("`quoted`");
+        assert!(escaped.contains("\\textasciigrave{}"));
+    }
+
+    #[test]
+    fn test_save_and_merge_pages() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        gen.save_page_latex(0, "Page 1 content\n\n").unwrap();
+        gen.save_page_latex(1, "Page 2 content\n\n").unwrap();
+
+        let merged_path = gen.merge_pages("テストブック", 2).unwrap();
+        assert!(merged_path.exists());
+
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(content.contains("\\documentclass[uplatex,a4paper]{jsbook}"));
+        assert!(content.contains("\\usepackage{plext}"));
+        assert!(content.contains("\\title{テストブック}"));
+        assert!(content.contains("Page 1 content"));
+        assert!(content.contains("Page 2 content"));
+        assert!(content.contains("\\end{document}"));
+    }
+
+    #[test]
+    fn test_merge_pages_missing_page() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        gen.save_page_latex(0, "Page 1 content\n\n").unwrap();
+        gen.save_page_latex(2, "Page 3 content\n\n").unwrap();
+
+        let merged_path = gen.merge_pages("テスト", 3).unwrap();
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(content.contains("Page 1 content"));
+        assert!(content.contains("Page 3 content"));
+    }
+
+    #[test]
+    fn test_relative_image_path_outside_output() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let outside_path = PathBuf::from("/some/other/path/image.png");
+        let rel = gen.relative_image_path(&outside_path);
+        assert_eq!(rel, "/some/other/path/image.png");
+    }
+
+    #[test]
+    fn test_page_content_no_elements() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![],
+        };
+
+        let tex = gen.generate_page_latex(&content).unwrap();
+        assert!(tex.is_empty());
+    }
+
+    #[test]
+    fn test_merge_tex_defaults_to_horizontal_documentclass() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        gen.save_page_latex(0, "Page 1 content\n\n").unwrap();
+        let merged_path = gen.merge_tex("テスト", 1).unwrap();
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(content.contains("\\documentclass[uplatex,a4paper]{jsbook}"));
+    }
+
+    #[test]
+    fn test_merge_tex_selects_tate_documentclass_when_dominant_vertical() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = LatexGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "縦書きテスト".into(),
+                direction: TextDirection::Vertical,
+            }],
+        };
+        let tex = gen.generate_page_latex(&content).unwrap();
+        gen.save_page_latex(0, &tex).unwrap();
+
+        let merged_path = gen.merge_tex("縦書き本", 1).unwrap();
+        let merged = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(merged.contains("\\documentclass[tate,uplatex,a4paper]{jsbook}"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Structural element detection
+//!
+//! Types for the coarse structural elements (headings, paragraphs, tables,
+//! figures) of a page, and, for raw OCR [`TextBlock`]s already known to form
+//! a table, a [`TableStructure`] grid with merged-cell (rowspan/colspan) and
+//! column-alignment metadata that [`crate::markdown::MarkdownRenderer`]
+//! downstream uses to pick between a GFM pipe table and an HTML fallback.
+//! Only the table grid is built here; nothing in this module classifies
+//! blocks into an [`ElementType`].
+
+use super::types::{BoundingBox, TextBlock};
+
+/// Column alignment hint inferred from where a cell's text centers within
+/// its column band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Kind of structural element a block of text was classified as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    Heading(u8),
+    Paragraph,
+    Table,
+    Figure,
+}
+
+/// A classified element, still holding its source text and geometry
+#[derive(Debug, Clone)]
+pub struct DetectedElement {
+    pub element_type: ElementType,
+    pub text: String,
+    pub bbox: BoundingBox,
+}
+
+/// One table cell. `row`/`col` are the grid position its span *starts* at;
+/// positions it additionally covers via `rowspan`/`colspan` have no cell of
+/// their own (see [`TableStructure::cell_at`])
+#[derive(Debug, Clone)]
+pub struct TableCell {
+    pub text: String,
+    pub row: usize,
+    pub col: usize,
+    pub rowspan: usize,
+    pub colspan: usize,
+}
+
+/// A detected table: a grid of (possibly merged) cells plus per-column
+/// alignment hints
+#[derive(Debug, Clone)]
+pub struct TableStructure {
+    pub rows: usize,
+    pub cols: usize,
+    pub cells: Vec<TableCell>,
+    pub column_alignment: Vec<ColumnAlignment>,
+}
+
+impl TableStructure {
+    /// `true` if any cell spans more than one row or column; these can't be
+    /// represented by a GFM pipe table and need the HTML fallback
+    pub fn has_merged_cells(&self) -> bool {
+        self.cells.iter().any(|c| c.rowspan > 1 || c.colspan > 1)
+    }
+
+    /// The cell whose span *starts* at `(row, col)`, if any. Grid positions
+    /// covered only by a previous cell's rowspan/colspan return `None`.
+    pub fn cell_at(&self, row: usize, col: usize) -> Option<&TableCell> {
+        self.cells.iter().find(|c| c.row == row && c.col == col)
+    }
+}
+
+/// Maximum pixel gap between two blocks' vertical extents for them to be
+/// considered the same table row
+const ROW_BAND_TOLERANCE: i32 = 4;
+
+/// Maximum pixel gap between two blocks' horizontal extents for them to be
+/// considered the same table column
+const COL_BAND_TOLERANCE: i32 = 4;
+
+/// Builds table grids from raw OCR text blocks (see [`Self::build_table`])
+pub struct ElementDetector;
+
+impl ElementDetector {
+    /// Cluster `blocks` (already known to form a table) into a grid: row
+    /// bands from the blocks' vertical extents, then column bands from the
+    /// horizontal extents of the blocks in the densest row. A block whose
+    /// bbox spans more than one row/column band becomes
+    /// a merged cell with the matching `rowspan`/`colspan`.
+    pub fn build_table(blocks: &[TextBlock]) -> TableStructure {
+        if blocks.is_empty() {
+            return TableStructure {
+                rows: 0,
+                cols: 0,
+                cells: Vec::new(),
+                column_alignment: Vec::new(),
+            };
+        }
+
+        let row_bands = Self::cluster_bands(
+            blocks.iter().map(|b| (b.bbox.y, b.bbox.y + b.bbox.height as i32)),
+            ROW_BAND_TOLERANCE,
+        );
+
+        // Column bands come from the row with the most blocks: a row with a
+        // merged (colspan) header cell has fewer, wider blocks than the data
+        // rows below it, so the densest row is the one least likely to be
+        // hiding a merge and the most reliable source for the real grid.
+        let finest_row_blocks = row_bands
+            .iter()
+            .map(|&row_range| {
+                blocks
+                    .iter()
+                    .filter(|b| {
+                        Self::ranges_overlap(
+                            row_range,
+                            (b.bbox.y, b.bbox.y + b.bbox.height as i32),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .max_by_key(|row_blocks| row_blocks.len())
+            .unwrap_or_default();
+
+        let col_bands = Self::cluster_bands(
+            finest_row_blocks
+                .iter()
+                .map(|b| (b.bbox.x, b.bbox.x + b.bbox.width as i32)),
+            COL_BAND_TOLERANCE,
+        );
+
+        let mut cells = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let (row_start, row_span) =
+                Self::span(&row_bands, block.bbox.y, block.bbox.y + block.bbox.height as i32);
+            let (col_start, col_span) = Self::span(
+                &col_bands,
+                block.bbox.x,
+                block.bbox.x + block.bbox.width as i32,
+            );
+            cells.push(TableCell {
+                text: block.text.clone(),
+                row: row_start,
+                col: col_start,
+                rowspan: row_span.max(1),
+                colspan: col_span.max(1),
+            });
+        }
+
+        let column_alignment = (0..col_bands.len())
+            .map(|col| Self::infer_column_alignment(blocks, &col_bands, col))
+            .collect();
+
+        TableStructure {
+            rows: row_bands.len(),
+            cols: col_bands.len(),
+            cells,
+            column_alignment,
+        }
+    }
+
+    /// Merge `(start, end)` ranges into non-overlapping bands, sorted by
+    /// position, treating ranges within `tolerance` pixels of an existing
+    /// band's edge as belonging to it
+    fn cluster_bands(ranges: impl Iterator<Item = (i32, i32)>, tolerance: i32) -> Vec<(i32, i32)> {
+        let mut bands: Vec<(i32, i32)> = Vec::new();
+        for (start, end) in ranges {
+            if let Some(band) = bands
+                .iter_mut()
+                .find(|(bs, be)| start <= *be + tolerance && end >= *bs - tolerance)
+            {
+                band.0 = band.0.min(start);
+                band.1 = band.1.max(end);
+            } else {
+                bands.push((start, end));
+            }
+        }
+        bands.sort_by_key(|(start, _)| *start);
+        bands
+    }
+
+    /// Whether ranges `a` and `b` overlap at all
+    fn ranges_overlap(a: (i32, i32), b: (i32, i32)) -> bool {
+        a.0 < b.1 && a.1 > b.0
+    }
+
+    /// Index of the first band `(start, end)` overlaps, and how many
+    /// consecutive bands it overlaps (its span)
+    fn span(bands: &[(i32, i32)], start: i32, end: i32) -> (usize, usize) {
+        let overlaps = |band: &(i32, i32)| Self::ranges_overlap((start, end), *band);
+        let first = bands.iter().position(overlaps).unwrap_or(0);
+        let count = bands[first..].iter().take_while(|b| overlaps(b)).count();
+        (first, count)
+    }
+
+    /// Majority-vote alignment for `col`: each block whose bbox falls in
+    /// this column band contributes based on where its text center sits
+    /// within the band's width (left of 40%, right of 60%, center otherwise;
+    /// ties go to right, then center)
+    fn infer_column_alignment(
+        blocks: &[TextBlock],
+        col_bands: &[(i32, i32)],
+        col: usize,
+    ) -> ColumnAlignment {
+        let (band_start, band_end) = col_bands[col];
+        let band_width = (band_end - band_start).max(1);
+
+        let mut left = 0;
+        let mut center = 0;
+        let mut right = 0;
+
+        for block in blocks {
+            let block_start = block.bbox.x;
+            let block_end = block.bbox.x + block.bbox.width as i32;
+            if block_start >= band_end || block_end <= band_start {
+                continue;
+            }
+            let relative = (block.bbox.center_x() - band_start) as f32 / band_width as f32;
+            if relative < 0.4 {
+                left += 1;
+            } else if relative > 0.6 {
+                right += 1;
+            } else {
+                center += 1;
+            }
+        }
+
+        if right >= left && right >= center {
+            ColumnAlignment::Right
+        } else if center >= left {
+            ColumnAlignment::Center
+        } else {
+            ColumnAlignment::Left
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::types::TextDirectionOption;
+
+    fn block(text: &str, x: i32, y: i32, width: u32, height: u32) -> TextBlock {
+        TextBlock {
+            text: text.to_string(),
+            bbox: BoundingBox::new(x, y, width, height),
+            font_size: 12.0,
+            direction: TextDirectionOption::Horizontal,
+        }
+    }
+
+    #[test]
+    fn test_build_table_clusters_simple_grid_into_rows_and_cols() {
+        let blocks = vec![
+            block("A", 0, 0, 40, 20),
+            block("B", 60, 0, 40, 20),
+            block("1", 0, 30, 40, 20),
+            block("2", 60, 30, 40, 20),
+        ];
+        let table = ElementDetector::build_table(&blocks);
+        assert_eq!(table.rows, 2);
+        assert_eq!(table.cols, 2);
+        assert!(!table.has_merged_cells());
+        assert_eq!(table.cell_at(0, 0).unwrap().text, "A");
+        assert_eq!(table.cell_at(1, 1).unwrap().text, "2");
+    }
+
+    #[test]
+    fn test_build_table_detects_colspan_for_wide_header_cell() {
+        let blocks = vec![
+            block("Header", 0, 0, 100, 20),
+            block("1", 0, 30, 40, 20),
+            block("2", 60, 30, 40, 20),
+        ];
+        let table = ElementDetector::build_table(&blocks);
+        assert!(table.has_merged_cells());
+        let header = table.cell_at(0, 0).unwrap();
+        assert_eq!(header.colspan, 2);
+    }
+
+    #[test]
+    fn test_infer_column_alignment_prefers_right_for_right_leaning_text() {
+        let blocks = vec![
+            block("1", 40, 0, 10, 20),
+            block("22", 35, 30, 10, 20),
+        ];
+        let col_bands = vec![(0, 50)];
+        let alignment = ElementDetector::infer_column_alignment(&blocks, &col_bands, 0);
+        assert_eq!(alignment, ColumnAlignment::Right);
+    }
+}
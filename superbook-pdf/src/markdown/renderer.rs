@@ -0,0 +1,258 @@
+//! Markdown rendering of detected elements
+//!
+//! Turns a [`super::element_detect::TableStructure`] into Markdown text.
+//! GFM pipe tables can't express merged header cells (rowspan/colspan), so
+//! [`MarkdownRenderOptions::table_mode`] lets callers opt into an HTML
+//! `<table>` fallback instead, either always or only when a table actually
+//! has merges.
+
+use super::element_detect::{ColumnAlignment, TableStructure};
+
+/// How to render a detected table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableMode {
+    /// Always emit a GFM pipe table; merged cells are flattened (their text
+    /// repeated into every grid position they cover), losing the span
+    Gfm,
+    /// Always emit an HTML `<table>` with `rowspan`/`colspan` attributes
+    HtmlFallback,
+    /// Per-table: HTML fallback if [`TableStructure::has_merged_cells`],
+    /// GFM otherwise
+    Auto,
+}
+
+/// Options controlling how detected elements are rendered to Markdown
+#[derive(Debug, Clone, Copy)]
+pub struct MarkdownRenderOptions {
+    pub table_mode: TableMode,
+}
+
+impl Default for MarkdownRenderOptions {
+    fn default() -> Self {
+        Self {
+            table_mode: TableMode::Auto,
+        }
+    }
+}
+
+/// Renders detected elements to Markdown text
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    /// Render `table` per `options.table_mode`
+    pub fn render_table(table: &TableStructure, options: &MarkdownRenderOptions) -> String {
+        let use_html = match options.table_mode {
+            TableMode::Gfm => false,
+            TableMode::HtmlFallback => true,
+            TableMode::Auto => table.has_merged_cells(),
+        };
+
+        if use_html {
+            Self::render_html_table(table)
+        } else {
+            Self::render_gfm_table(table)
+        }
+    }
+
+    /// GFM pipe table with an alignment row built from `column_alignment`
+    fn render_gfm_table(table: &TableStructure) -> String {
+        let mut out = String::new();
+        if table.rows == 0 || table.cols == 0 {
+            return out;
+        }
+
+        let cell_text = |row: usize, col: usize| -> String {
+            table
+                .cells
+                .iter()
+                .find(|c| {
+                    row >= c.row
+                        && row < c.row + c.rowspan
+                        && col >= c.col
+                        && col < c.col + c.colspan
+                })
+                .map(|c| c.text.replace('|', "\\|"))
+                .unwrap_or_default()
+        };
+
+        Self::push_gfm_row(&mut out, table.cols, |col| cell_text(0, col));
+
+        out.push('|');
+        for col in 0..table.cols {
+            let sep = match table
+                .column_alignment
+                .get(col)
+                .copied()
+                .unwrap_or(ColumnAlignment::Left)
+            {
+                ColumnAlignment::Left => "---",
+                ColumnAlignment::Center => ":---:",
+                ColumnAlignment::Right => "---:",
+            };
+            out.push_str(sep);
+            out.push('|');
+        }
+        out.push('\n');
+
+        for row in 1..table.rows {
+            Self::push_gfm_row(&mut out, table.cols, |col| cell_text(row, col));
+        }
+
+        out
+    }
+
+    fn push_gfm_row(out: &mut String, cols: usize, mut cell_text: impl FnMut(usize) -> String) {
+        out.push('|');
+        for col in 0..cols {
+            out.push(' ');
+            out.push_str(&cell_text(col));
+            out.push_str(" |");
+        }
+        out.push('\n');
+    }
+
+    /// HTML `<table>` with `rowspan`/`colspan` attributes, preserving merges
+    /// that a GFM pipe table would flatten
+    fn render_html_table(table: &TableStructure) -> String {
+        let mut out = String::from("<table>\n");
+        for row in 0..table.rows {
+            out.push_str("  <tr>\n");
+            for col in 0..table.cols {
+                let Some(cell) = table.cell_at(row, col) else {
+                    continue;
+                };
+                let align = match table
+                    .column_alignment
+                    .get(col)
+                    .copied()
+                    .unwrap_or(ColumnAlignment::Left)
+                {
+                    ColumnAlignment::Left => "left",
+                    ColumnAlignment::Center => "center",
+                    ColumnAlignment::Right => "right",
+                };
+                let tag = if row == 0 { "th" } else { "td" };
+                out.push_str(&format!("    <{tag} align=\"{align}\""));
+                if cell.rowspan > 1 {
+                    out.push_str(&format!(" rowspan=\"{}\"", cell.rowspan));
+                }
+                if cell.colspan > 1 {
+                    out.push_str(&format!(" colspan=\"{}\"", cell.colspan));
+                }
+                out.push_str(&format!(">{}</{tag}>\n", cell.text));
+            }
+            out.push_str("  </tr>\n");
+        }
+        out.push_str("</table>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::element_detect::TableCell;
+
+    fn simple_table() -> TableStructure {
+        TableStructure {
+            rows: 2,
+            cols: 2,
+            cells: vec![
+                TableCell {
+                    text: "A".to_string(),
+                    row: 0,
+                    col: 0,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+                TableCell {
+                    text: "B".to_string(),
+                    row: 0,
+                    col: 1,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+                TableCell {
+                    text: "1".to_string(),
+                    row: 1,
+                    col: 0,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+                TableCell {
+                    text: "2".to_string(),
+                    row: 1,
+                    col: 1,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+            ],
+            column_alignment: vec![ColumnAlignment::Left, ColumnAlignment::Right],
+        }
+    }
+
+    fn merged_table() -> TableStructure {
+        TableStructure {
+            rows: 2,
+            cols: 2,
+            cells: vec![
+                TableCell {
+                    text: "Header".to_string(),
+                    row: 0,
+                    col: 0,
+                    rowspan: 1,
+                    colspan: 2,
+                },
+                TableCell {
+                    text: "1".to_string(),
+                    row: 1,
+                    col: 0,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+                TableCell {
+                    text: "2".to_string(),
+                    row: 1,
+                    col: 1,
+                    rowspan: 1,
+                    colspan: 1,
+                },
+            ],
+            column_alignment: vec![ColumnAlignment::Left, ColumnAlignment::Left],
+        }
+    }
+
+    #[test]
+    fn test_render_gfm_table_emits_pipe_rows_and_alignment() {
+        let out = MarkdownRenderer::render_table(&simple_table(), &MarkdownRenderOptions {
+            table_mode: TableMode::Gfm,
+        });
+        assert_eq!(out, "| A | B |\n|---|---:|\n| 1 | 2 |\n");
+    }
+
+    #[test]
+    fn test_render_gfm_table_flattens_merged_cell_into_every_covered_column() {
+        let out = MarkdownRenderer::render_table(&merged_table(), &MarkdownRenderOptions {
+            table_mode: TableMode::Gfm,
+        });
+        assert!(out.starts_with("| Header | Header |\n"));
+    }
+
+    #[test]
+    fn test_render_html_table_preserves_colspan() {
+        let out = MarkdownRenderer::render_table(&merged_table(), &MarkdownRenderOptions {
+            table_mode: TableMode::HtmlFallback,
+        });
+        assert!(out.contains("colspan=\"2\""));
+        assert!(out.contains("<th align=\"left\" colspan=\"2\">Header</th>"));
+    }
+
+    #[test]
+    fn test_auto_mode_picks_html_for_merged_table_and_gfm_otherwise() {
+        let merged_out = MarkdownRenderer::render_table(&merged_table(), &MarkdownRenderOptions::default());
+        assert!(merged_out.starts_with("<table>"));
+
+        let simple_out = MarkdownRenderer::render_table(&simple_table(), &MarkdownRenderOptions::default());
+        assert!(simple_out.starts_with("| A | B |"));
+    }
+}
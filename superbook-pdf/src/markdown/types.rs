@@ -0,0 +1,108 @@
+//! Shared types for the `markdown` module
+//!
+//! Kept separate from [`crate::markdown_gen`]'s `PageContent`/`ContentElement`:
+//! those model the already-interleaved element stream the generator backends
+//! render, while these model the Issue #36 pipeline's intermediate detection
+//! output (raw OCR blocks plus their geometry) before that interleaving happens.
+
+use thiserror::Error;
+
+/// Axis-aligned bounding box in page pixel coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Horizontal center, used to infer column alignment within a table cell
+    pub fn center_x(&self) -> i32 {
+        self.x + self.width as i32 / 2
+    }
+}
+
+/// Error type for the `markdown` module's conversion pipeline
+#[derive(Debug, Error)]
+pub enum MarkdownError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Conversion error: {0}")]
+    ConversionError(String),
+}
+
+/// Reading direction for a detected text block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirectionOption {
+    Horizontal,
+    Vertical,
+}
+
+/// A single OCR text block with its source geometry
+#[derive(Debug, Clone)]
+pub struct TextBlock {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub font_size: f32,
+    pub direction: TextDirectionOption,
+}
+
+/// Raw per-page OCR output fed into [`crate::markdown::ElementDetector`]
+#[derive(Debug, Clone)]
+pub struct PageContent {
+    pub page_index: usize,
+    pub blocks: Vec<TextBlock>,
+}
+
+/// Options controlling the conversion pipeline end to end
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    pub min_confidence: f32,
+    pub detect_tables: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.3,
+            detect_tables: true,
+        }
+    }
+}
+
+/// Builder for [`MarkdownOptions`]
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptionsBuilder {
+    options: MarkdownOptions,
+}
+
+impl MarkdownOptionsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_confidence(mut self, min_confidence: f32) -> Self {
+        self.options.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn detect_tables(mut self, detect_tables: bool) -> Self {
+        self.options.detect_tables = detect_tables;
+        self
+    }
+
+    pub fn build(self) -> MarkdownOptions {
+        self.options
+    }
+}
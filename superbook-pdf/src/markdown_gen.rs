@@ -7,7 +7,12 @@ use std::fmt::Write as FmtWrite;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use serde::{Deserialize, Serialize};
+
+use crate::document_tree::DocumentTree;
 use crate::figure_detect::{FigureRegion, PageClassification};
+use crate::furigana::{BuiltinReadingDict, ReadingDictionary, RubyFormat};
+use crate::romaji::FilenameStyle;
 use crate::yomitoku::{OcrResult, TextBlock, TextDirection};
 
 /// Minimum OCR confidence to include a text block (0.0-1.0)
@@ -28,6 +33,13 @@ const MAX_NOISE_RATIO: f32 = 0.6;
 /// Minimum text length to apply noise filtering (short blocks like "1900" are kept)
 const NOISE_FILTER_MIN_LEN: usize = 8;
 
+/// Relative weight given to a heading's terms in the search index, versus 1.0 for body text
+const HEADING_TERM_WEIGHT: f32 = 3.0;
+
+/// Maximum length ratio (longer/shorter) allowed between a furigana reading
+/// and its candidate kanji line before the pairing is rejected as unlikely
+const FURIGANA_LENGTH_RATIO_MAX: f32 = 3.0;
+
 /// Error type for Markdown generation
 #[derive(Debug, Error)]
 pub enum MarkdownGenError {
@@ -67,11 +79,264 @@ pub struct PageContent {
     pub elements: Vec<ContentElement>,
 }
 
+/// One occurrence of a search term, produced by [`MarkdownGenerator::build_search_index`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Posting {
+    pub page: usize,
+    pub bbox: (i32, i32, i32, i32),
+    /// Relative weight of this occurrence; headings score [`HEADING_TERM_WEIGHT`],
+    /// body text scores 1.0
+    pub weight: f32,
+}
+
+/// Term to postings map covering every retained block across a document,
+/// written alongside the merged Markdown as a compact JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub terms: std::collections::HashMap<String, Vec<Posting>>,
+}
+
+/// Visitor-style interface for turning a `PageContent`'s `ContentElement`s into
+/// a specific output format. `build_page_content` produces the structured
+/// element stream once; each output backend implements this trait instead of
+/// re-deriving the interleaving, sorting, and heading-detection logic baked
+/// into that stream. [`MarkdownRenderer`] is the default impl; [`LatexGenerator`](crate::latex_gen::LatexGenerator)
+/// is another.
+pub trait DocumentRenderer {
+    /// Called once per page, before any elements are emitted
+    fn begin_text(&self) -> String {
+        String::new()
+    }
+
+    /// Render a heading (`level` 2 or 3, matching [`MarkdownGenerator::heading_level`]'s
+    /// `##`/`###` scale)
+    fn emit_heading(&self, level: u8, text: &str) -> String;
+
+    /// Render a run of body text that isn't a heading
+    fn emit_paragraph(&self, text: &str, direction: TextDirection) -> String;
+
+    /// Render a figure, with an optional caption
+    fn emit_figure(&self, image_path: &Path, caption: Option<&str>) -> String;
+
+    /// Render a full-page image (cover or illustration)
+    fn emit_full_page_image(&self, image_path: &Path) -> String;
+
+    /// Render the separator between pages
+    fn emit_page_break(&self) -> String;
+}
+
+/// Walk a page's `ContentElement`s, dispatching each to `renderer`. Shared by
+/// every [`DocumentRenderer`] impl so the layout pipeline (element order,
+/// heading/paragraph splitting) stays in one place.
+pub(crate) fn render_page_content<R: DocumentRenderer + ?Sized>(
+    renderer: &R,
+    page_content: &PageContent,
+) -> String {
+    let mut out = renderer.begin_text();
+
+    for element in &page_content.elements {
+        match element {
+            ContentElement::Text { content, direction } => {
+                out.push_str(&render_text_element(renderer, content, *direction));
+            }
+            ContentElement::Figure {
+                image_path,
+                caption,
+            } => {
+                out.push_str(&renderer.emit_figure(image_path, caption.as_deref()));
+            }
+            ContentElement::FullPageImage { image_path } => {
+                out.push_str(&renderer.emit_full_page_image(image_path));
+            }
+            ContentElement::PageBreak => {
+                out.push_str(&renderer.emit_page_break());
+            }
+        }
+    }
+
+    out
+}
+
+/// Split a `Text` element's already-assembled content into the `"## "`/`"### "`
+/// heading line `format_block_group` embeds as the first line of its paragraph
+/// (if any) and the remaining body, dispatching each to the renderer
+fn render_text_element<R: DocumentRenderer + ?Sized>(
+    renderer: &R,
+    content: &str,
+    direction: TextDirection,
+) -> String {
+    let mut out = String::new();
+
+    for paragraph in content.split("\n\n") {
+        let trimmed = paragraph.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let mut lines = trimmed.splitn(2, '\n');
+        let first = lines.next().unwrap_or("");
+        let rest = lines.next();
+
+        if let Some(title) = first.strip_prefix("### ") {
+            out.push_str(&renderer.emit_heading(3, title.trim()));
+        } else if let Some(title) = first.strip_prefix("## ") {
+            out.push_str(&renderer.emit_heading(2, title.trim()));
+        } else {
+            out.push_str(&renderer.emit_paragraph(trimmed, direction));
+            continue;
+        }
+
+        if let Some(rest) = rest {
+            let rest = rest.trim();
+            if !rest.is_empty() {
+                out.push_str(&renderer.emit_paragraph(rest, direction));
+            }
+        }
+    }
+
+    out
+}
+
+/// Get an image path relative to `output_dir` for use in a rendered document;
+/// falls back to the absolute path if `abs_path` doesn't live under it
+pub(crate) fn relative_image_path(output_dir: &Path, abs_path: &Path) -> String {
+    if let Ok(rel) = abs_path.strip_prefix(output_dir) {
+        rel.to_string_lossy().to_string()
+    } else {
+        abs_path.to_string_lossy().to_string()
+    }
+}
+
+/// Default [`DocumentRenderer`]: emits Markdown
+struct MarkdownRenderer<'a> {
+    output_dir: &'a Path,
+}
+
+impl DocumentRenderer for MarkdownRenderer<'_> {
+    fn emit_heading(&self, level: u8, text: &str) -> String {
+        format!("{} {}\n\n", "#".repeat(level as usize), text)
+    }
+
+    fn emit_paragraph(&self, text: &str, _direction: TextDirection) -> String {
+        format!("{}\n\n", text)
+    }
+
+    fn emit_figure(&self, image_path: &Path, caption: Option<&str>) -> String {
+        let rel_path = relative_image_path(self.output_dir, image_path);
+        match caption {
+            Some(cap) => format!("![{}]({})\n\n", cap, rel_path),
+            None => format!("![図]({})\n\n", rel_path),
+        }
+    }
+
+    fn emit_full_page_image(&self, image_path: &Path) -> String {
+        let rel_path = relative_image_path(self.output_dir, image_path);
+        format!("![]({})\n\n", rel_path)
+    }
+
+    fn emit_page_break(&self) -> String {
+        "---\n\n".to_string()
+    }
+}
+
+/// Furigana annotation settings set via [`MarkdownGenerator::with_furigana_annotation`]
+struct FuriganaConfig {
+    dict: Box<dyn ReadingDictionary>,
+    format: RubyFormat,
+}
+
+/// Output shape for a furigana line preserved as ruby, set via
+/// [`MarkdownGenerator::with_furigana_ruby_preservation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RubyPairingFormat {
+    /// HTML ruby element: `<ruby>漢字<rt>かんじ</rt></ruby>`
+    Html,
+    /// Lightweight novel-style annotation: `漢字《かんじ》`
+    Novel,
+}
+
+/// Line ending detected in raw input text by [`detect_line_ending`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedLineEnding {
+    /// Only bare `\n` line breaks
+    Lf,
+    /// Only bare `\r` line breaks (classic Mac OS)
+    Cr,
+    /// Only `\r\n` line breaks
+    Crlf,
+    /// More than one kind of line break present, with counts of each
+    Mixed { cr: usize, lf: usize, crlf: usize },
+}
+
+/// Line ending [`MarkdownGenerator::save_page_markdown`]/`merge_pages`
+/// re-encode their output to, set via
+/// [`MarkdownGenerator::with_line_ending`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Bare `\n` (today's default)
+    #[default]
+    Lf,
+    /// `\r\n`, for Windows-native consumers
+    CrLf,
+}
+
+/// Whether [`MarkdownGenerator::generate_page_markdown`] inserts spacing and
+/// normalizes full-width ASCII at CJK/Latin boundaries, set via
+/// [`MarkdownGenerator::with_cjk_latin_spacing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CjkLatinSpacing {
+    /// Leave mixed-script spacing exactly as OCR produced it (today's default)
+    #[default]
+    Preserve,
+    /// Insert a half-width space at CJK/Latin boundaries and convert
+    /// full-width ASCII letters/digits/punctuation to half-width when they
+    /// neighbor Latin text
+    Normalize,
+}
+
+/// A block in the minimal CommonMark-like AST
+/// [`MarkdownGenerator::normalize_via_ast`] round-trips generated Markdown
+/// through
+#[derive(Debug, Clone, PartialEq)]
+enum AstBlock {
+    /// An ATX heading (`"# "` through `"###### "`)
+    Heading { level: u8, text: String },
+    /// A `---`/`***`/`___` thematic break (horizontal rule)
+    ThematicBreak,
+    /// Body text; `had_accidental_markup` is set when re-parsing the
+    /// original text would have split it into extra blocks (a line-initial
+    /// heading/list/thematic-break marker that wasn't meant as markup)
+    Paragraph {
+        text: String,
+        had_accidental_markup: bool,
+    },
+}
+
+/// One mdBook chapter: everything between a `"## "` heading and the next,
+/// written to its own `src/chapter_NN.md` file by
+/// [`MarkdownGenerator::export_mdbook`]
+struct MdBookChapter {
+    title: String,
+    body: String,
+    /// `"### "` subsection titles within this chapter, in order, used to
+    /// render nested `SUMMARY.md` entries
+    subsection_titles: Vec<String>,
+}
+
 /// Markdown generator
 pub struct MarkdownGenerator {
     output_dir: PathBuf,
     images_dir: PathBuf,
     pages_dir: PathBuf,
+    furigana: Option<FuriganaConfig>,
+    furigana_ruby: Option<RubyPairingFormat>,
+    filename_style: FilenameStyle,
+    line_ending: LineEnding,
+    cjk_latin_spacing: CjkLatinSpacing,
+    /// Whether [`Self::generate_page_markdown`]'s final
+    /// [`Self::normalize_via_ast`] pass logs a warning for accidental
+    /// markup it had to escape, see [`Self::with_ast_normalize_strict`]
+    ast_normalize_strict: bool,
 }
 
 impl MarkdownGenerator {
@@ -87,53 +352,125 @@ impl MarkdownGenerator {
             output_dir: output_dir.to_path_buf(),
             images_dir,
             pages_dir,
+            furigana: None,
+            furigana_ruby: None,
+            filename_style: FilenameStyle::Preserve,
+            line_ending: LineEnding::Lf,
+            cjk_latin_spacing: CjkLatinSpacing::Preserve,
+            ast_normalize_strict: false,
         })
     }
 
+    /// Enable furigana annotation of kanji runs in generated Markdown, looking
+    /// readings up in `dict` and rendering them as `format`. Off by default,
+    /// so callers who want plain text see no change in behavior.
+    pub fn with_furigana_annotation(
+        mut self,
+        dict: Box<dyn ReadingDictionary>,
+        format: RubyFormat,
+    ) -> Self {
+        self.furigana = Some(FuriganaConfig { dict, format });
+        self
+    }
+
+    /// Preserve detected furigana lines as ruby annotations on their
+    /// adjacent kanji line instead of discarding them (today's default).
+    /// Pairing only happens when the kanji line is an unambiguous match
+    /// (see [`MarkdownGenerator::normalize_markdown_with_ruby`]); otherwise
+    /// the furigana line is still dropped as before.
+    pub fn with_furigana_ruby_preservation(mut self, format: RubyPairingFormat) -> Self {
+        self.furigana_ruby = Some(format);
+        self
+    }
+
+    /// Choose how [`MarkdownGenerator::merge_pages`] and
+    /// [`MarkdownGenerator::merge_pages_with_toc`] derive their output
+    /// filename from the document title. Defaults to
+    /// [`FilenameStyle::Preserve`], so callers see no change in behavior
+    /// unless they opt into [`FilenameStyle::RomajiSlug`].
+    pub fn with_filename_style(mut self, style: FilenameStyle) -> Self {
+        self.filename_style = style;
+        self
+    }
+
+    /// Choose the line ending [`MarkdownGenerator::save_page_markdown`] and
+    /// [`MarkdownGenerator::merge_pages`] re-encode their output to.
+    /// Defaults to [`LineEnding::Lf`]; internal processing always works in
+    /// `\n` regardless of this setting, so this only affects bytes written
+    /// to disk.
+    pub fn with_line_ending(mut self, ending: LineEnding) -> Self {
+        self.line_ending = ending;
+        self
+    }
+
+    /// Re-encode `\n`-normalized text to `self.line_ending` for writing to disk
+    fn encode_line_ending(&self, text: &str) -> String {
+        match self.line_ending {
+            LineEnding::Lf => text.to_string(),
+            LineEnding::CrLf => text.replace('\n', "\r\n"),
+        }
+    }
+
+    /// Opt in to CJK/Latin boundary spacing and full-width-ASCII
+    /// normalization in [`Self::generate_page_markdown`] (default:
+    /// [`CjkLatinSpacing::Preserve`], leaving OCR spacing untouched)
+    pub fn with_cjk_latin_spacing(mut self, spacing: CjkLatinSpacing) -> Self {
+        self.cjk_latin_spacing = spacing;
+        self
+    }
+
+    /// Make [`Self::generate_page_markdown`]'s final AST-normalization pass
+    /// use [`Self::normalize_via_ast_strict`] instead of
+    /// [`Self::normalize_via_ast`], logging a warning for every paragraph
+    /// where accidental markup had to be escaped. Off by default.
+    pub fn with_ast_normalize_strict(mut self, strict: bool) -> Self {
+        self.ast_normalize_strict = strict;
+        self
+    }
+
+    /// Derive the output filename (without extension) for `title`, honoring
+    /// `self.filename_style`.
+    fn output_filename(&self, title: &str) -> String {
+        match self.filename_style {
+            FilenameStyle::Preserve => sanitize_filename(title),
+            FilenameStyle::RomajiSlug => {
+                crate::romaji::romaji_slug(title, &BuiltinReadingDict::new())
+            }
+        }
+    }
+
     /// Generate Markdown for a single page
     pub fn generate_page_markdown(
         &self,
         page_content: &PageContent,
     ) -> Result<String, MarkdownGenError> {
-        let mut md = String::new();
-
-        for element in &page_content.elements {
-            match element {
-                ContentElement::Text { content, .. } => {
-                    // Write text content, preserving paragraph structure
-                    for paragraph in content.split("\n\n") {
-                        let trimmed = paragraph.trim();
-                        if !trimmed.is_empty() {
-                            writeln!(md, "{}", trimmed).ok();
-                            writeln!(md).ok();
-                        }
-                    }
-                }
-                ContentElement::Figure {
-                    image_path,
-                    caption,
-                } => {
-                    let rel_path = self.relative_image_path(image_path);
-                    match caption {
-                        Some(cap) => writeln!(md, "![{}]({})", cap, rel_path).ok(),
-                        None => writeln!(md, "![図]({})", rel_path).ok(),
-                    };
-                    writeln!(md).ok();
-                }
-                ContentElement::FullPageImage { image_path } => {
-                    let rel_path = self.relative_image_path(image_path);
-                    writeln!(md, "![]({})", rel_path).ok();
-                    writeln!(md).ok();
-                }
-                ContentElement::PageBreak => {
-                    writeln!(md, "---").ok();
-                    writeln!(md).ok();
-                }
-            }
+        let renderer = MarkdownRenderer {
+            output_dir: &self.output_dir,
+        };
+        let mut md = render_page_content(&renderer, page_content);
+
+        if let Some(furigana) = &self.furigana {
+            md = crate::furigana::annotate_with_furigana(&md, furigana.dict.as_ref(), furigana.format);
+        }
+
+        if self.cjk_latin_spacing == CjkLatinSpacing::Normalize {
+            md = normalize_cjk_latin_spacing(&md);
         }
 
         // Post-process: normalize spacing
-        Ok(Self::normalize_markdown(&md))
+        md = match self.furigana_ruby {
+            Some(format) => Self::normalize_markdown_with_ruby(&md, format),
+            None => Self::normalize_markdown(&md),
+        };
+
+        // Final pass: round-trip through the CommonMark AST so OCR noise
+        // that happens to look like Markdown markup can't silently corrupt
+        // downstream rendering
+        Ok(if self.ast_normalize_strict {
+            Self::normalize_via_ast_strict(&md)
+        } else {
+            Self::normalize_via_ast(&md)
+        })
     }
 
     /// Save page markdown to pages directory
@@ -145,7 +482,7 @@ impl MarkdownGenerator {
         let page_path = self
             .pages_dir
             .join(format!("page_{:03}.md", page_index + 1));
-        std::fs::write(&page_path, content)?;
+        std::fs::write(&page_path, self.encode_line_ending(content))?;
         Ok(page_path)
     }
 
@@ -161,27 +498,43 @@ impl MarkdownGenerator {
 
         match classification {
             PageClassification::Cover => {
-                // Look for a saved cover image
-                let cover_path = self
-                    .images_dir
-                    .join(format!("cover_{:03}.png", page_index + 1));
+                // Use whatever path/extension save_page_images actually wrote
+                // (PNG, WebP, ... depending on FigureDetectOptions::image_format)
+                let cover_path = figure_images
+                    .first()
+                    .map(|(_, path)| path.clone())
+                    .unwrap_or_else(|| {
+                        self.images_dir
+                            .join(format!("cover_{:03}.png", page_index + 1))
+                    });
                 elements.push(ContentElement::FullPageImage {
                     image_path: cover_path,
                 });
             }
             PageClassification::FullPageImage => {
-                let img_path = self
-                    .images_dir
-                    .join(format!("page_{:03}_full.png", page_index + 1));
+                let img_path = figure_images
+                    .first()
+                    .map(|(_, path)| path.clone())
+                    .unwrap_or_else(|| {
+                        self.images_dir
+                            .join(format!("page_{:03}_full.png", page_index + 1))
+                    });
                 elements.push(ContentElement::FullPageImage {
                     image_path: img_path,
                 });
             }
             PageClassification::TextOnly => {
-                let text = Self::sort_and_join_text_blocks(
-                    &ocr_result.text_blocks,
-                    &ocr_result.text_direction,
-                );
+                let text = match self.furigana_ruby {
+                    Some(format) => Self::build_structured_text_with_ruby(
+                        &ocr_result.text_blocks,
+                        &ocr_result.text_direction,
+                        format,
+                    ),
+                    None => Self::sort_and_join_text_blocks(
+                        &ocr_result.text_blocks,
+                        &ocr_result.text_direction,
+                    ),
+                };
                 if !text.is_empty() {
                     elements.push(ContentElement::Text {
                         content: text,
@@ -276,6 +629,234 @@ impl MarkdownGenerator {
         }
     }
 
+    /// Serialize a page's `ContentElement` stream and the intermediate
+    /// layout decisions `build_page_content` makes (median font size/line
+    /// height, which blocks were dropped by noise/confidence filtering) as a
+    /// nested s-expression. Unlike `build_page_content`'s output, this is
+    /// meant to be read or diffed directly, so noise-dropped blocks are kept
+    /// visible instead of silently discarded.
+    pub fn dump_sexp(
+        &self,
+        page_index: usize,
+        ocr_result: &OcrResult,
+        classification: &PageClassification,
+        figure_images: &[(FigureRegion, PathBuf)],
+    ) -> String {
+        let mut out = String::new();
+        writeln!(out, "(page {}", page_index).ok();
+
+        match classification {
+            PageClassification::Cover => {
+                writeln!(out, "  (cover)").ok();
+            }
+            PageClassification::FullPageImage => {
+                writeln!(out, "  (full-page-image)").ok();
+            }
+            PageClassification::TextOnly => {
+                Self::dump_text_blocks_sexp(
+                    &ocr_result.text_blocks,
+                    &ocr_result.text_direction,
+                    &mut out,
+                );
+            }
+            PageClassification::Mixed { figures } => {
+                Self::dump_mixed_sexp(ocr_result, figures, figure_images, &mut out);
+            }
+        }
+
+        writeln!(out, "  (page-break))").ok();
+        out
+    }
+
+    /// `TextDirection` rendered as an s-expression keyword
+    fn direction_sexp(direction: &TextDirection) -> &'static str {
+        match direction {
+            TextDirection::Vertical => "vertical",
+            TextDirection::Horizontal => "horizontal",
+            TextDirection::Mixed => "mixed",
+        }
+    }
+
+    /// Escape a string for embedding as an s-expression string literal
+    fn sexp_string(text: &str) -> String {
+        format!("{:?}", text)
+    }
+
+    /// Emit `(dropped ...)` forms for every block that `filter_low_confidence`
+    /// would remove, so noise filtering is visible in the dump
+    fn dump_dropped_blocks_sexp(blocks: &[TextBlock], out: &mut String) {
+        for block in blocks {
+            let dropped_reason = if block.confidence < MIN_CONFIDENCE {
+                Some("low-confidence")
+            } else if Self::is_noise_text(&block.text) {
+                Some("noise")
+            } else if Self::clean_block_text(&block.text).trim().is_empty() {
+                Some("empty-after-clean")
+            } else {
+                None
+            };
+
+            if let Some(reason) = dropped_reason {
+                writeln!(
+                    out,
+                    "  (dropped :reason {} :confidence {:.2} :text {})",
+                    reason,
+                    block.confidence,
+                    Self::sexp_string(block.text.trim())
+                )
+                .ok();
+            }
+        }
+    }
+
+    /// Emit `(meta ...)`, `(dropped ...)`, and `(heading ...)`/`(paragraph ...)`
+    /// forms for a page's text blocks
+    fn dump_text_blocks_sexp(blocks: &[TextBlock], direction: &TextDirection, out: &mut String) {
+        Self::dump_dropped_blocks_sexp(blocks, out);
+
+        let filtered = Self::filter_low_confidence(blocks);
+        let sorted = Self::sort_text_blocks(&filtered, direction);
+        let median_size = Self::median_font_size(&sorted);
+        let median_height = Self::median_line_height(&sorted);
+
+        writeln!(
+            out,
+            "  (meta :median-font-size {} :median-line-height {:.1})",
+            median_size
+                .map(|s| format!("{:.1}", s))
+                .unwrap_or_else(|| "nil".to_string()),
+            median_height
+        )
+        .ok();
+
+        for block in &sorted {
+            let level = median_size.and_then(|size| Self::heading_level(block, size));
+            let bbox = format!(
+                "({} {} {} {})",
+                block.bbox.0, block.bbox.1, block.bbox.2, block.bbox.3
+            );
+
+            match level {
+                Some(level) => {
+                    writeln!(
+                        out,
+                        "  (heading {} {} :font {} :bbox {})",
+                        level,
+                        Self::sexp_string(block.text.trim()),
+                        block.font_size.unwrap_or(0.0),
+                        bbox
+                    )
+                    .ok();
+                }
+                None => {
+                    writeln!(
+                        out,
+                        "  (paragraph :dir {} {} :bbox {})",
+                        Self::direction_sexp(direction),
+                        Self::sexp_string(block.text.trim()),
+                        bbox
+                    )
+                    .ok();
+                }
+            }
+        }
+    }
+
+    /// Emit the same forms as `dump_text_blocks_sexp`, plus `(figure ...)`
+    /// forms interleaved by vertical position, mirroring the Mixed-page
+    /// logic in `build_page_content`
+    fn dump_mixed_sexp(
+        ocr_result: &OcrResult,
+        figures: &[FigureRegion],
+        figure_images: &[(FigureRegion, PathBuf)],
+        out: &mut String,
+    ) {
+        Self::dump_dropped_blocks_sexp(&ocr_result.text_blocks, out);
+
+        let filtered = Self::filter_low_confidence(&ocr_result.text_blocks);
+        let sorted = Self::sort_text_blocks(&filtered, &ocr_result.text_direction);
+        let median_size = Self::median_font_size(&sorted);
+        let median_height = Self::median_line_height(&sorted);
+
+        writeln!(
+            out,
+            "  (meta :median-font-size {} :median-line-height {:.1})",
+            median_size
+                .map(|s| format!("{:.1}", s))
+                .unwrap_or_else(|| "nil".to_string()),
+            median_height
+        )
+        .ok();
+
+        let mut figure_idx = 0;
+        for block in &sorted {
+            while figure_idx < figures.len() {
+                let fig = &figures[figure_idx];
+                if fig.bbox.1 < block.bbox.1 {
+                    Self::write_figure_sexp(figure_images, figure_idx, out);
+                    figure_idx += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let level = median_size.and_then(|size| Self::heading_level(block, size));
+            let bbox = format!(
+                "({} {} {} {})",
+                block.bbox.0, block.bbox.1, block.bbox.2, block.bbox.3
+            );
+
+            match level {
+                Some(level) => {
+                    writeln!(
+                        out,
+                        "  (heading {} {} :font {} :bbox {})",
+                        level,
+                        Self::sexp_string(block.text.trim()),
+                        block.font_size.unwrap_or(0.0),
+                        bbox
+                    )
+                    .ok();
+                }
+                None => {
+                    writeln!(
+                        out,
+                        "  (paragraph :dir {} {} :bbox {})",
+                        Self::direction_sexp(&ocr_result.text_direction),
+                        Self::sexp_string(block.text.trim()),
+                        bbox
+                    )
+                    .ok();
+                }
+            }
+        }
+
+        while figure_idx < figures.len() {
+            Self::write_figure_sexp(figure_images, figure_idx, out);
+            figure_idx += 1;
+        }
+    }
+
+    fn write_figure_sexp(
+        figure_images: &[(FigureRegion, PathBuf)],
+        figure_idx: usize,
+        out: &mut String,
+    ) {
+        match figure_images.get(figure_idx) {
+            Some((_, path)) => {
+                writeln!(
+                    out,
+                    "  (figure {} :caption nil)",
+                    Self::sexp_string(&path.to_string_lossy())
+                )
+                .ok();
+            }
+            None => {
+                writeln!(out, "  (figure nil :caption nil)").ok();
+            }
+        }
+    }
+
     /// Merge all page markdowns into a single output file
     pub fn merge_pages(
         &self,
@@ -284,7 +865,7 @@ impl MarkdownGenerator {
     ) -> Result<PathBuf, MarkdownGenError> {
         let output_path = self
             .output_dir
-            .join(format!("{}.md", sanitize_filename(title)));
+            .join(format!("{}.md", self.output_filename(title)));
         let mut merged = String::new();
 
         // Title header
@@ -296,66 +877,326 @@ impl MarkdownGenerator {
             let page_path = self.pages_dir.join(format!("page_{:03}.md", i + 1));
             if page_path.exists() {
                 let content = std::fs::read_to_string(&page_path)?;
-                merged.push_str(&content);
+                merged.push_str(&normalize_line_endings(strip_bom(&content)));
             }
         }
 
-        std::fs::write(&output_path, &merged)?;
+        std::fs::write(&output_path, self.encode_line_ending(&merged))?;
         Ok(output_path)
     }
 
-    /// Get images directory path
-    pub fn images_dir(&self) -> &Path {
-        &self.images_dir
-    }
+    /// Same as [`MarkdownGenerator::merge_pages`], but assembling a
+    /// [`DocumentTree`] from `pages` first and prefixing the merged output
+    /// with an anchored `# Contents` list generated from it
+    pub fn merge_pages_with_toc(
+        &self,
+        title: &str,
+        pages: &[PageContent],
+    ) -> Result<PathBuf, MarkdownGenError> {
+        let output_path = self
+            .output_dir
+            .join(format!("{}.md", self.output_filename(title)));
 
-    /// Get pages directory path
-    pub fn pages_dir(&self) -> &Path {
-        &self.pages_dir
-    }
+        let mut tree = DocumentTree::new();
+        let mut stack = Vec::new();
+        for page in pages {
+            tree.push_page(page, &mut stack);
+        }
 
-    /// Sort text blocks by reading order and join into structured text
-    /// with heading detection, confidence filtering, and paragraph breaks
-    fn sort_and_join_text_blocks(blocks: &[TextBlock], direction: &TextDirection) -> String {
-        Self::build_structured_text(blocks, direction)
+        let mut merged = String::new();
+        writeln!(merged, "# {}", title).ok();
+        writeln!(merged).ok();
+        merged.push_str(&tree.render_toc());
+
+        for page in pages {
+            merged.push_str(&self.generate_page_markdown(page)?);
+        }
+
+        std::fs::write(&output_path, self.encode_line_ending(&merged))?;
+        Ok(output_path)
     }
 
-    /// Sort text blocks by reading order
-    /// Vertical (Japanese): right-to-left columns, then top-to-bottom within each column
-    /// Horizontal: top-to-bottom rows, then left-to-right within each row
-    fn sort_text_blocks(blocks: &[TextBlock], direction: &TextDirection) -> Vec<TextBlock> {
-        let mut sorted = blocks.to_vec();
+    /// Write an mdBook-style multi-file book (`book.toml` plus a `src/`
+    /// directory of `chapter_NN.md` files and a generated `SUMMARY.md`) to
+    /// `<output_dir>/<title>_book`, splitting the merged per-page Markdown
+    /// into chapters at `"## "` headings the same way
+    /// [`crate::epub_gen::EpubGenerator`] splits into EPUB chapters. Missing
+    /// page files (as in
+    /// `test_merge_pages_missing_page`) are skipped rather than erroring;
+    /// `PageBreak` elements render as a `"---"` rule and never start a new
+    /// chapter on their own.
+    pub fn export_mdbook(
+        &self,
+        title: &str,
+        total_pages: usize,
+    ) -> Result<PathBuf, MarkdownGenError> {
+        let book_dir = self
+            .output_dir
+            .join(format!("{}_book", sanitize_filename(title)));
+        let src_dir = book_dir.join("src");
+        std::fs::create_dir_all(&src_dir)?;
 
-        match direction {
-            TextDirection::Vertical => {
-                // Right-to-left, then top-to-bottom
-                sorted.sort_by(|a, b| {
-                    // Compare X in reverse (right to left)
-                    let ax = a.bbox.0;
-                    let bx = b.bbox.0;
-                    let x_cmp = bx.cmp(&ax);
-                    if x_cmp != std::cmp::Ordering::Equal {
-                        return x_cmp;
-                    }
-                    // Then top to bottom
-                    a.bbox.1.cmp(&b.bbox.1)
-                });
+        let mut merged = String::new();
+        for i in 0..total_pages {
+            let page_path = self.pages_dir.join(format!("page_{:03}.md", i + 1));
+            if page_path.exists() {
+                let content = std::fs::read_to_string(&page_path)?;
+                merged.push_str(&normalize_line_endings(strip_bom(&content)));
             }
-            TextDirection::Horizontal | TextDirection::Mixed => {
-                // Top-to-bottom, then left-to-right
-                sorted.sort_by(|a, b| {
-                    let ay = a.bbox.1;
-                    let by = b.bbox.1;
-                    let y_cmp = ay.cmp(&by);
-                    if y_cmp != std::cmp::Ordering::Equal {
-                        return y_cmp;
-                    }
-                    a.bbox.0.cmp(&b.bbox.0)
-                });
+        }
+
+        let chapters = Self::split_into_mdbook_chapters(&merged);
+
+        let mut summary = String::from("# Summary\n\n");
+        for (i, chapter) in chapters.iter().enumerate() {
+            let file_name = format!("chapter_{:02}.md", i + 1);
+            summary.push_str(&format!("- [{}]({})\n", chapter.title, file_name));
+
+            let mut slug_counts = std::collections::HashMap::new();
+            for sub_title in &chapter.subsection_titles {
+                let anchor = mdbook_heading_anchor(sub_title, &mut slug_counts);
+                summary.push_str(&format!("  - [{}]({}#{})\n", sub_title, file_name, anchor));
             }
+
+            let mut chapter_md = String::new();
+            writeln!(chapter_md, "# {}", chapter.title).ok();
+            writeln!(chapter_md).ok();
+            chapter_md.push_str(&chapter.body);
+            std::fs::write(
+                src_dir.join(&file_name),
+                self.encode_line_ending(&chapter_md),
+            )?;
         }
 
-        sorted
+        std::fs::write(src_dir.join("SUMMARY.md"), self.encode_line_ending(&summary))?;
+
+        let book_toml = format!(
+            "[book]\ntitle = \"{}\"\nsrc = \"src\"\nlanguage = \"ja\"\n",
+            title.replace('"', "\\\"")
+        );
+        std::fs::write(book_dir.join("book.toml"), book_toml)?;
+
+        Ok(book_dir)
+    }
+
+    /// Split already-merged Markdown into mdBook chapters, cutting a new one
+    /// at each `"## "` heading rather than at page boundaries (the same
+    /// cut point `EpubGenerator::split_into_chapters` uses for EPUB).
+    /// Content before the first heading (if any) becomes an "Untitled"
+    /// chapter.
+    fn split_into_mdbook_chapters(markdown: &str) -> Vec<MdBookChapter> {
+        let mut chapters = Vec::new();
+        let mut title = String::from("Untitled");
+        let mut body = String::new();
+        let mut subsection_titles = Vec::new();
+        let mut started = false;
+
+        for paragraph in markdown.split("\n\n") {
+            let trimmed = paragraph.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let mut lines = trimmed.splitn(2, '\n');
+            let first = lines.next().unwrap_or("");
+            let rest = lines.next();
+
+            if let Some(heading) = first.strip_prefix("## ") {
+                if started {
+                    chapters.push(MdBookChapter {
+                        title: std::mem::take(&mut title),
+                        body: std::mem::take(&mut body),
+                        subsection_titles: std::mem::take(&mut subsection_titles),
+                    });
+                }
+                title = heading.trim().to_string();
+                started = true;
+                Self::append_mdbook_paragraph(rest, &mut body);
+                continue;
+            }
+
+            started = true;
+
+            if let Some(sub_heading) = first.strip_prefix("### ") {
+                let sub_heading = sub_heading.trim();
+                subsection_titles.push(sub_heading.to_string());
+                writeln!(body, "### {}", sub_heading).ok();
+                writeln!(body).ok();
+                Self::append_mdbook_paragraph(rest, &mut body);
+            } else {
+                writeln!(body, "{}", trimmed).ok();
+                writeln!(body).ok();
+            }
+        }
+
+        if started {
+            chapters.push(MdBookChapter {
+                title,
+                body,
+                subsection_titles,
+            });
+        }
+
+        chapters
+    }
+
+    fn append_mdbook_paragraph(rest: Option<&str>, body: &mut String) {
+        let Some(rest) = rest else { return };
+        let rest = rest.trim();
+        if !rest.is_empty() {
+            writeln!(body, "{}", rest).ok();
+            writeln!(body).ok();
+        }
+    }
+
+    /// Build a term→postings search index from each page's OCR blocks,
+    /// skipping the same low-confidence/noise blocks `build_page_content`
+    /// does, and weighting heading blocks higher than body text
+    pub fn build_search_index(&self, pages: &[(usize, OcrResult)]) -> SearchIndex {
+        let mut index = SearchIndex::default();
+
+        for (page_index, ocr_result) in pages {
+            let filtered = Self::filter_low_confidence(&ocr_result.text_blocks);
+            if filtered.is_empty() {
+                continue;
+            }
+            let median_size = Self::median_font_size(&filtered);
+
+            for block in &filtered {
+                let is_heading = median_size
+                    .map(|size| Self::heading_level(block, size).is_some())
+                    .unwrap_or(false);
+                let weight = if is_heading { HEADING_TERM_WEIGHT } else { 1.0 };
+
+                for term in Self::tokenize(&block.text) {
+                    index.terms.entry(term).or_default().push(Posting {
+                        page: *page_index,
+                        bbox: block.bbox,
+                        weight,
+                    });
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Build the search index for `pages` and write it as compact JSON next
+    /// to the merged Markdown output (`<title>.search.json`)
+    pub fn write_search_index(
+        &self,
+        title: &str,
+        pages: &[(usize, OcrResult)],
+    ) -> Result<PathBuf, MarkdownGenError> {
+        let index = self.build_search_index(pages);
+        let output_path = self
+            .output_dir
+            .join(format!("{}.search.json", sanitize_filename(title)));
+        let json = serde_json::to_string(&index)
+            .map_err(|e| MarkdownGenError::GenerationError(e.to_string()))?;
+        std::fs::write(&output_path, json)?;
+        Ok(output_path)
+    }
+
+    /// Check if a character belongs to a CJK script (kanji, hiragana, or katakana)
+    fn is_cjk(c: char) -> bool {
+        ('\u{4E00}'..='\u{9FFF}').contains(&c)
+            || ('\u{3040}'..='\u{309F}').contains(&c)
+            || ('\u{30A0}'..='\u{30FF}').contains(&c)
+    }
+
+    /// Tokenize text for the search index: CJK runs are split into
+    /// character bigrams (there are no spaces to split on), Latin/other
+    /// runs are split on whitespace and lowercased
+    fn tokenize(text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            if Self::is_cjk(chars[i]) {
+                let start = i;
+                while i < chars.len() && Self::is_cjk(chars[i]) {
+                    i += 1;
+                }
+                let run = &chars[start..i];
+                if run.len() == 1 {
+                    tokens.push(run[0].to_string());
+                } else {
+                    for pair in run.windows(2) {
+                        tokens.push(pair.iter().collect());
+                    }
+                }
+            } else {
+                let start = i;
+                while i < chars.len() && !Self::is_cjk(chars[i]) {
+                    i += 1;
+                }
+                let segment: String = chars[start..i].iter().collect();
+                for word in segment.split_whitespace() {
+                    let cleaned: String = word.chars().filter(|c| c.is_alphanumeric()).collect();
+                    if !cleaned.is_empty() {
+                        tokens.push(cleaned.to_lowercase());
+                    }
+                }
+            }
+        }
+
+        tokens
+    }
+
+    /// Get images directory path
+    pub fn images_dir(&self) -> &Path {
+        &self.images_dir
+    }
+
+    /// Get pages directory path
+    pub fn pages_dir(&self) -> &Path {
+        &self.pages_dir
+    }
+
+    /// Sort text blocks by reading order and join into structured text
+    /// with heading detection, confidence filtering, and paragraph breaks
+    fn sort_and_join_text_blocks(blocks: &[TextBlock], direction: &TextDirection) -> String {
+        Self::build_structured_text(blocks, direction)
+    }
+
+    /// Sort text blocks by reading order
+    /// Vertical (Japanese): right-to-left columns, then top-to-bottom within each column
+    /// Horizontal: top-to-bottom rows, then left-to-right within each row
+    fn sort_text_blocks(blocks: &[TextBlock], direction: &TextDirection) -> Vec<TextBlock> {
+        let mut sorted = blocks.to_vec();
+
+        match direction {
+            TextDirection::Vertical => {
+                // Right-to-left, then top-to-bottom
+                sorted.sort_by(|a, b| {
+                    // Compare X in reverse (right to left)
+                    let ax = a.bbox.0;
+                    let bx = b.bbox.0;
+                    let x_cmp = bx.cmp(&ax);
+                    if x_cmp != std::cmp::Ordering::Equal {
+                        return x_cmp;
+                    }
+                    // Then top to bottom
+                    a.bbox.1.cmp(&b.bbox.1)
+                });
+            }
+            TextDirection::Horizontal | TextDirection::Mixed => {
+                // Top-to-bottom, then left-to-right
+                sorted.sort_by(|a, b| {
+                    let ay = a.bbox.1;
+                    let by = b.bbox.1;
+                    let y_cmp = ay.cmp(&by);
+                    if y_cmp != std::cmp::Ordering::Equal {
+                        return y_cmp;
+                    }
+                    a.bbox.0.cmp(&b.bbox.0)
+                });
+            }
+        }
+
+        sorted
     }
 
     /// Format a group of text blocks with heading detection and paragraph breaks
@@ -635,9 +1476,96 @@ impl MarkdownGenerator {
         // Sort by reading order
         let sorted = Self::sort_text_blocks(&filtered, direction);
 
-        // Calculate metrics for heading detection and paragraph gaps
-        let median_size = Self::median_font_size(&sorted);
-        let median_height = Self::median_line_height(&sorted);
+        Self::render_sorted_blocks(&sorted)
+    }
+
+    /// Same as [`MarkdownGenerator::build_structured_text`], except a
+    /// furigana block (short, all-kana per [`MarkdownGenerator::is_furigana_line`],
+    /// smaller `font_size` than, and horizontally overlapping, the preceding
+    /// block) is folded into that preceding block as a ruby annotation
+    /// instead of becoming its own line. Blocks with no qualifying
+    /// predecessor are left untouched and fall through to the usual
+    /// line-based stripping in [`MarkdownGenerator::normalize_markdown_with_ruby`].
+    fn build_structured_text_with_ruby(
+        blocks: &[TextBlock],
+        direction: &TextDirection,
+        format: RubyPairingFormat,
+    ) -> String {
+        if blocks.is_empty() {
+            return String::new();
+        }
+
+        let filtered = Self::filter_low_confidence(blocks);
+        if filtered.is_empty() {
+            return String::new();
+        }
+
+        let sorted = Self::sort_text_blocks(&filtered, direction);
+        let paired = Self::pair_furigana_blocks_by_bbox(&sorted, format);
+
+        Self::render_sorted_blocks(&paired)
+    }
+
+    /// Fold furigana candidate blocks into the preceding block's text as
+    /// ruby annotations. See [`MarkdownGenerator::build_structured_text_with_ruby`].
+    fn pair_furigana_blocks_by_bbox(
+        sorted: &[TextBlock],
+        format: RubyPairingFormat,
+    ) -> Vec<TextBlock> {
+        let mut out: Vec<TextBlock> = Vec::with_capacity(sorted.len());
+
+        for block in sorted {
+            if let Some(prev) = out.last_mut() {
+                if Self::is_furigana_block_candidate(prev, block) {
+                    let reading: String = block
+                        .text
+                        .chars()
+                        .filter(|&c| c != ' ' && c != '\u{3000}')
+                        .collect();
+                    if !reading.is_empty() {
+                        prev.text = Self::render_ruby_pair(prev.text.trim(), &reading, format);
+                        continue;
+                    }
+                }
+            }
+            out.push(block.clone());
+        }
+
+        out
+    }
+
+    /// Whether `candidate` looks like a furigana reading for `prev`: short
+    /// all-kana text, a smaller font size than `prev`, and a bbox that
+    /// roughly overlaps `prev` horizontally
+    fn is_furigana_block_candidate(prev: &TextBlock, candidate: &TextBlock) -> bool {
+        if !Self::is_furigana_line(candidate.text.trim()) {
+            return false;
+        }
+
+        let (Some(prev_size), Some(candidate_size)) = (prev.font_size, candidate.font_size) else {
+            return false;
+        };
+        if candidate_size >= prev_size {
+            return false;
+        }
+
+        let prev_left = prev.bbox.0;
+        let prev_right = prev.bbox.0 + prev.bbox.2;
+        let candidate_left = candidate.bbox.0;
+        let candidate_right = candidate.bbox.0 + candidate.bbox.2;
+
+        let overlap_left = prev_left.max(candidate_left);
+        let overlap_right = prev_right.min(candidate_right);
+        overlap_right > overlap_left
+    }
+
+    /// Render already-filtered, sorted, (optionally ruby-paired) blocks into
+    /// the heading/paragraph-break-aware body text shared by
+    /// [`MarkdownGenerator::build_structured_text`] and
+    /// [`MarkdownGenerator::build_structured_text_with_ruby`]
+    fn render_sorted_blocks(sorted: &[TextBlock]) -> String {
+        let median_size = Self::median_font_size(sorted);
+        let median_height = Self::median_line_height(sorted);
 
         let mut result = String::new();
 
@@ -690,6 +1618,7 @@ impl MarkdownGenerator {
     /// Post-process markdown: normalize spacing, remove duplicates, collapse blank lines,
     /// remove stray page numbers, convert +heading to ## heading, skip furigana lines
     fn normalize_markdown(md: &str) -> String {
+        let md = normalize_line_endings(strip_bom(md));
         let mut result = String::with_capacity(md.len());
         let mut blank_count = 0u32;
         let mut prev_line: Option<String> = None;
@@ -792,78 +1721,632 @@ impl MarkdownGenerator {
         result
     }
 
-    /// Check if a line is a stray page number (1-4 digits, possibly with leading zeros)
-    fn is_page_number_line(text: &str) -> bool {
-        let trimmed = text.trim();
-        let char_count = trimmed.chars().count();
-        // 1-4 digit string, e.g., "028", "1", "300"
-        (1..=4).contains(&char_count) && trimmed.chars().all(|c| c.is_ascii_digit())
-    }
+    /// Same as [`MarkdownGenerator::normalize_markdown`], except a furigana
+    /// line is paired with the content line immediately before it (the
+    /// kanji-bearing line OCR emits first) instead of being dropped, when
+    /// that line looks like a plausible pairing: it must contain at least
+    /// one CJK ideograph, and the furigana's length must be within
+    /// [`FURIGANA_LENGTH_RATIO_MAX`]x of it in either direction. Otherwise
+    /// falls back to today's skip behavior.
+    fn normalize_markdown_with_ruby(md: &str, format: RubyPairingFormat) -> String {
+        let md = normalize_line_endings(strip_bom(md));
+        let mut result = String::with_capacity(md.len());
+        let mut blank_count = 0u32;
+        let mut prev_line: Option<String> = None;
+        let mut prev_line_range: Option<std::ops::Range<usize>> = None;
+        let mut is_first_content_line = true;
 
-    /// Check if a line is likely furigana (ruby text above kanji)
-    /// Furigana lines are typically: short, all hiragana/katakana, with spaces
-    fn is_furigana_line(text: &str) -> bool {
-        let trimmed = text.trim();
-        let char_count = trimmed.chars().count();
-        // Must be short (furigana for a name is usually < 15 chars)
-        if !(2..=15).contains(&char_count) {
-            return false;
-        }
-        // Must contain a space (furigana for multiple words)
-        if !trimmed.contains(' ') {
-            return false;
-        }
-        // All characters must be hiragana, katakana, or space
-        trimmed.chars().all(|c| {
-            c == ' '
-                || c == '\u{3000}' // full-width space
-                || ('\u{3040}'..='\u{309F}').contains(&c) // hiragana
-                || ('\u{30A0}'..='\u{30FF}').contains(&c) // katakana
-        })
-    }
+        for line in md.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                blank_count += 1;
+                if blank_count <= 1 {
+                    result.push('\n');
+                }
+                continue;
+            }
 
-    /// Get image path relative to the output directory for markdown references
-    fn relative_image_path(&self, abs_path: &Path) -> String {
-        if let Ok(rel) = abs_path.strip_prefix(&self.output_dir) {
-            rel.to_string_lossy().to_string()
-        } else {
-            abs_path.to_string_lossy().to_string()
-        }
-    }
-}
+            if is_first_content_line && Self::is_page_number_line(trimmed) {
+                continue;
+            }
 
-/// Sanitize a string for use as a filename
-fn sanitize_filename(name: &str) -> String {
-    name.chars()
-        .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-            _ => c,
-        })
-        .collect()
-}
+            if Self::is_furigana_line(trimmed) {
+                if let (Some(kanji_line), Some(range)) = (&prev_line, prev_line_range.clone()) {
+                    if let Some(paired) = Self::try_pair_furigana(kanji_line, trimmed, format) {
+                        result.replace_range(range, &paired);
+                        continue;
+                    }
+                }
+                // No suitable kanji line adjacent: fall back to dropping it.
+                continue;
+            }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            let line_to_write = if let Some(heading_text) = trimmed.strip_prefix('+') {
+                let heading_text = heading_text.trim();
+                if !heading_text.is_empty() && heading_text.len() > 1 {
+                    if !result.is_empty() && !result.ends_with("\n\n") {
+                        if !result.ends_with('\n') {
+                            result.push('\n');
+                        }
+                        result.push('\n');
+                    }
+                    format!("## {}", heading_text)
+                } else {
+                    trimmed.to_string()
+                }
+            } else {
+                trimmed.to_string()
+            };
+            let trimmed = line_to_write.trim();
 
-    #[test]
-    fn test_sanitize_filename() {
-        assert_eq!(sanitize_filename("hello/world"), "hello_world");
-        assert_eq!(sanitize_filename("test:file"), "test_file");
-        assert_eq!(sanitize_filename("normal_file"), "normal_file");
-        assert_eq!(sanitize_filename("日本語テスト"), "日本語テスト");
-    }
+            if let Some(ref prev) = prev_line {
+                if prev == trimmed {
+                    continue;
+                }
+                if prev.len() >= 5
+                    && trimmed.len() >= 5
+                    && (prev.starts_with(trimmed) || trimmed.starts_with(prev.as_str()))
+                {
+                    continue;
+                }
+            }
 
-    #[test]
-    fn test_sort_text_blocks_vertical() {
-        let blocks = vec![
-            TextBlock {
-                text: "左列".into(),
-                bbox: (100, 0, 50, 500),
-                confidence: 0.9,
-                direction: TextDirection::Vertical,
-                font_size: Some(12.0),
-            },
+            let char_count = trimmed.chars().count();
+            if char_count <= 3
+                && !trimmed.starts_with('#')
+                && !trimmed.starts_with('-')
+                && !trimmed.starts_with('!')
+            {
+                let is_meaningful = trimmed.chars().all(|c| c.is_ascii_digit())
+                    || (trimmed.len() >= 2 && trimmed.chars().all(|c| c.is_ascii_alphanumeric()));
+                if !is_meaningful {
+                    continue;
+                }
+            }
+
+            if char_count <= 5
+                && trimmed.chars().all(|c| c.is_ascii_uppercase())
+                && !trimmed.starts_with('#')
+            {
+                continue;
+            }
+
+            blank_count = 0;
+            is_first_content_line = false;
+            if !result.is_empty() && !result.ends_with('\n') {
+                result.push('\n');
+            }
+            let start = result.len();
+            result.push_str(trimmed);
+            prev_line_range = Some(start..result.len());
+            prev_line = Some(trimmed.to_string());
+        }
+
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+
+        result
+    }
+
+    /// Try to pair a detected furigana line with its preceding kanji line,
+    /// rendering the pair as ruby under `format`. Returns `None` when the
+    /// kanji line isn't a plausible match (no CJK ideographs, or the two
+    /// lines' lengths are too far apart to plausibly be a reading pair).
+    fn try_pair_furigana(kanji_line: &str, furigana_line: &str, format: RubyPairingFormat) -> Option<String> {
+        if !kanji_line.chars().any(Self::is_kanji_char) {
+            return None;
+        }
+
+        let reading: String = furigana_line
+            .chars()
+            .filter(|&c| c != ' ' && c != '\u{3000}')
+            .collect();
+        if reading.is_empty() {
+            return None;
+        }
+
+        let kanji_len = kanji_line.chars().count() as f32;
+        let reading_len = reading.chars().count() as f32;
+        let ratio = reading_len.max(kanji_len) / reading_len.min(kanji_len);
+        if ratio > FURIGANA_LENGTH_RATIO_MAX {
+            return None;
+        }
+
+        Some(Self::render_ruby_pair(kanji_line, &reading, format))
+    }
+
+    /// Render a base/reading pair as ruby, splitting the reading across
+    /// individual base characters only when `base` is pure kanji and the
+    /// reading has exactly as many characters as `base` (the one case where
+    /// per-character alignment is unambiguous); otherwise the whole reading attaches to
+    /// the whole base span as a single ruby group.
+    fn render_ruby_pair(base: &str, reading: &str, format: RubyPairingFormat) -> String {
+        let base_chars: Vec<char> = base.chars().collect();
+        let reading_chars: Vec<char> = reading.chars().collect();
+        let all_kanji = !base_chars.is_empty() && base_chars.iter().all(|&c| Self::is_kanji_char(c));
+
+        if all_kanji && base_chars.len() == reading_chars.len() {
+            base_chars
+                .iter()
+                .zip(reading_chars.iter())
+                .map(|(b, r)| Self::render_one_ruby(&b.to_string(), &r.to_string(), format))
+                .collect()
+        } else {
+            Self::render_one_ruby(base, reading, format)
+        }
+    }
+
+    fn render_one_ruby(base: &str, reading: &str, format: RubyPairingFormat) -> String {
+        match format {
+            RubyPairingFormat::Html => format!("<ruby>{}<rt>{}</rt></ruby>", base, reading),
+            RubyPairingFormat::Novel => format!("{}《{}》", base, reading),
+        }
+    }
+
+    fn is_kanji_char(c: char) -> bool {
+        ('\u{4E00}'..='\u{9FFF}').contains(&c)
+    }
+
+    /// Parse `markdown` into a flat sequence of [`AstBlock`]s, the same
+    /// blank-line-delimited splitting [`Self::normalize_markdown`] and
+    /// `render_page_content` already use, so a round-trip through this
+    /// parser and [`Self::serialize_commonmark_ast`] can't silently
+    /// reshuffle content that was already block-structured correctly.
+    fn parse_commonmark_ast(markdown: &str) -> Vec<AstBlock> {
+        let mut blocks = Vec::new();
+
+        for raw_block in markdown.split("\n\n") {
+            let trimmed = raw_block.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if Self::is_thematic_break(trimmed) {
+                blocks.push(AstBlock::ThematicBreak);
+                continue;
+            }
+
+            let mut lines = trimmed.splitn(2, '\n');
+            let first = lines.next().unwrap_or("");
+            if let Some((level, text)) = Self::parse_atx_heading(first) {
+                blocks.push(AstBlock::Heading { level, text });
+                if let Some(rest) = lines.next() {
+                    let rest = rest.trim();
+                    if !rest.is_empty() {
+                        let (text, had_accidental_markup) = Self::escape_paragraph(rest);
+                        blocks.push(AstBlock::Paragraph {
+                            text,
+                            had_accidental_markup,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let (text, had_accidental_markup) = Self::escape_paragraph(trimmed);
+            blocks.push(AstBlock::Paragraph {
+                text,
+                had_accidental_markup,
+            });
+        }
+
+        blocks
+    }
+
+    /// Re-serialize an [`AstBlock`] sequence, guaranteeing a blank line
+    /// surrounds every heading and thematic break (every block is joined by
+    /// `"\n\n"`, CommonMark's own block separator)
+    fn serialize_commonmark_ast(blocks: &[AstBlock]) -> String {
+        let mut rendered = Vec::with_capacity(blocks.len());
+
+        for block in blocks {
+            let mut text = String::new();
+            match block {
+                AstBlock::Heading { level, text: title } => {
+                    writeln!(text, "{} {}", "#".repeat(*level as usize), title).ok();
+                }
+                AstBlock::ThematicBreak => {
+                    writeln!(text, "---").ok();
+                }
+                AstBlock::Paragraph { text: body, .. } => {
+                    writeln!(text, "{}", body).ok();
+                }
+            }
+            rendered.push(text.trim_end_matches('\n').to_string());
+        }
+
+        if rendered.is_empty() {
+            return String::new();
+        }
+
+        let mut out = rendered.join("\n\n");
+        out.push('\n');
+        out
+    }
+
+    /// Whether `line` (already trimmed) is a thematic break: three or more
+    /// `-`, `*`, or `_` characters, optionally interspersed with spaces
+    fn is_thematic_break(line: &str) -> bool {
+        let Some(marker) = line.chars().next() else {
+            return false;
+        };
+        if marker != '-' && marker != '*' && marker != '_' {
+            return false;
+        }
+        let marker_count = line.chars().filter(|&c| c == marker).count();
+        marker_count >= 3 && line.chars().all(|c| c == marker || c == ' ')
+    }
+
+    /// Parse an ATX heading line (`"# "` through `"###### "`) into its level
+    /// and title text, or `None` if `line` isn't one
+    fn parse_atx_heading(line: &str) -> Option<(u8, String)> {
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        if hashes == 0 || hashes > 6 {
+            return None;
+        }
+        let rest = &line[hashes..];
+        if rest.is_empty() {
+            return Some((hashes as u8, String::new()));
+        }
+        let rest = rest.strip_prefix(' ')?;
+        Some((hashes as u8, rest.trim_end().to_string()))
+    }
+
+    /// Escape literal markup inside a paragraph so re-parsing it can't split
+    /// it into extra blocks or misread inline emphasis: a leading ATX
+    /// heading/thematic-break/list marker on any line but the first (the
+    /// first line already went through the heading check in
+    /// [`Self::parse_commonmark_ast`]) gets its leading character escaped,
+    /// and every literal `*`, `_`, `` ` `` is backslash-escaped throughout.
+    /// Bare `#` characters that aren't line-initial need no escaping, since
+    /// CommonMark only treats a line-initial run of `#` as a heading.
+    /// Returns the escaped text alongside whether any line-initial markup
+    /// was found, for [`Self::normalize_via_ast_strict`] to warn about.
+    fn escape_paragraph(text: &str) -> (String, bool) {
+        let mut out = String::with_capacity(text.len());
+        let mut had_accidental_markup = false;
+
+        for (i, line) in text.lines().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+
+            let mut line = line.to_string();
+            if i > 0 && Self::starts_with_block_markup(&line) {
+                line.insert(0, '\\');
+                had_accidental_markup = true;
+            }
+
+            for c in line.chars() {
+                if c == '*' || c == '_' || c == '`' {
+                    out.push('\\');
+                }
+                out.push(c);
+            }
+        }
+
+        (out, had_accidental_markup)
+    }
+
+    /// Whether `line` starts with markup that CommonMark would read as a
+    /// new block (ATX heading, thematic break, or `-`/`*`/`+` list marker)
+    fn starts_with_block_markup(line: &str) -> bool {
+        let trimmed = line.trim_start();
+        Self::parse_atx_heading(trimmed).is_some()
+            || Self::is_thematic_break(trimmed)
+            || trimmed.starts_with("- ")
+            || trimmed.starts_with("* ")
+            || trimmed.starts_with("+ ")
+    }
+
+    /// Parse `md` into a minimal CommonMark-like AST and re-serialize it,
+    /// guaranteeing blank lines surround every heading/thematic break and
+    /// escaping literal `*`/`_`/`` ` ``/line-initial list markers in body
+    /// text. Run this as the final step after
+    /// [`Self::normalize_markdown`]/[`Self::normalize_markdown_with_ruby`]
+    /// so OCR noise that happens to look like Markdown markup can't
+    /// silently corrupt downstream rendering.
+    pub fn normalize_via_ast(md: &str) -> String {
+        Self::serialize_commonmark_ast(&Self::parse_commonmark_ast(md))
+    }
+
+    /// Same as [`Self::normalize_via_ast`], but prints a warning to stderr
+    /// for every paragraph where the round-trip had to escape line-initial
+    /// markup — i.e. where a generic CommonMark parser would have split the
+    /// paragraph into extra blocks, a sign the source OCR text contains
+    /// accidental markup worth a human look
+    pub fn normalize_via_ast_strict(md: &str) -> String {
+        let blocks = Self::parse_commonmark_ast(md);
+
+        for block in &blocks {
+            if let AstBlock::Paragraph {
+                text,
+                had_accidental_markup: true,
+            } = block
+            {
+                eprintln!(
+                    "warning: normalize_via_ast: accidental block markup escaped in paragraph: {:?}",
+                    text
+                );
+            }
+        }
+
+        Self::serialize_commonmark_ast(&blocks)
+    }
+
+    /// Check if a line is a stray page number (1-4 digits, possibly with leading zeros)
+    fn is_page_number_line(text: &str) -> bool {
+        let trimmed = text.trim();
+        let char_count = trimmed.chars().count();
+        // 1-4 digit string, e.g., "028", "1", "300"
+        (1..=4).contains(&char_count) && trimmed.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// Check if a line is likely furigana (ruby text above kanji)
+    /// Furigana lines are typically: short, all hiragana/katakana, with spaces
+    fn is_furigana_line(text: &str) -> bool {
+        let trimmed = text.trim();
+        let char_count = trimmed.chars().count();
+        // Must be short (furigana for a name is usually < 15 chars)
+        if !(2..=15).contains(&char_count) {
+            return false;
+        }
+        // Must contain a space (furigana for multiple words)
+        if !trimmed.contains(' ') {
+            return false;
+        }
+        // All characters must be hiragana, katakana, or space
+        trimmed.chars().all(|c| {
+            c == ' '
+                || c == '\u{3000}' // full-width space
+                || ('\u{3040}'..='\u{309F}').contains(&c) // hiragana
+                || ('\u{30A0}'..='\u{30FF}').contains(&c) // katakana
+        })
+    }
+
+    /// Get image path relative to the output directory for markdown references
+    fn relative_image_path(&self, abs_path: &Path) -> String {
+        relative_image_path(&self.output_dir, abs_path)
+    }
+}
+
+/// Sanitize a string for use as a filename
+pub(crate) fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Opt-in ASCII-safe variant of [`sanitize_filename`]: romanize `name` via
+/// [`crate::romaji::romaji_slug`] (kakasi-style kanji/kana-to-romaji,
+/// reusing [`BuiltinReadingDict`] the same way [`crate::romaji`]'s own
+/// callers do) before running it through the same path-hostile-character
+/// replacement. Unmapped kanji fall back to `_`, matching `romaji_slug`'s
+/// existing behavior for unknown compounds. `sanitize_filename` itself is
+/// untouched, so callers that don't opt in keep today's Unicode-preserving
+/// behavior.
+pub(crate) fn sanitize_filename_romaji(name: &str) -> String {
+    let dict = BuiltinReadingDict::new();
+    sanitize_filename(&crate::romaji::romaji_slug(name, &dict))
+}
+
+/// Strip a leading UTF-8 byte order mark, if present (common from Windows
+/// OCR toolchains that write a BOM-prefixed text file)
+fn strip_bom(text: &str) -> &str {
+    text.strip_prefix('\u{FEFF}').unwrap_or(text)
+}
+
+/// Slugify `title` the way GitHub's (and mdBook's) Markdown renderer anchors
+/// headings, disambiguating repeats within the same chapter file with a
+/// `-1`, `-2`, ... suffix. Duplicated from `DocumentTree`'s private
+/// `unique_slug` (same algorithm, different module) rather than widening
+/// its visibility for one caller.
+fn mdbook_heading_anchor(title: &str, slug_counts: &mut std::collections::HashMap<String, u32>) -> String {
+    let mut slug = String::new();
+    let mut last_was_dash = false;
+    for c in title.chars() {
+        if c.is_whitespace() {
+            if !last_was_dash && !slug.is_empty() {
+                slug.push('-');
+                last_was_dash = true;
+            }
+        } else if c.is_ascii_punctuation() {
+            continue;
+        } else {
+            slug.push(c.to_ascii_lowercase());
+            last_was_dash = false;
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+
+    let count = slug_counts.entry(slug.clone()).or_insert(0);
+    let unique = if *count == 0 {
+        slug
+    } else {
+        format!("{}-{}", slug, count)
+    };
+    *count += 1;
+    unique
+}
+
+/// Detect which line ending convention `text` uses by counting bare `CR`,
+/// bare `LF`, and `CRLF` occurrences. A file that mixes conventions (common
+/// after concatenating OCR output from different sources) reports
+/// [`DetectedLineEnding::Mixed`] with the counts of each.
+pub fn detect_line_ending(text: &str) -> DetectedLineEnding {
+    let mut cr = 0usize;
+    let mut lf = 0usize;
+    let mut crlf = 0usize;
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 2;
+            }
+            b'\r' => {
+                cr += 1;
+                i += 1;
+            }
+            b'\n' => {
+                lf += 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    let kinds_present = [cr > 0, lf > 0, crlf > 0].iter().filter(|&&k| k).count();
+    if kinds_present > 1 {
+        DetectedLineEnding::Mixed { cr, lf, crlf }
+    } else if crlf > 0 {
+        DetectedLineEnding::Crlf
+    } else if cr > 0 {
+        DetectedLineEnding::Cr
+    } else {
+        DetectedLineEnding::Lf
+    }
+}
+
+/// Normalize every line break in `text` (`\r\n` or bare `\r`) to `\n`
+fn normalize_line_endings(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\r' {
+            if chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            out.push('\n');
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Check if a character belongs to a CJK script (kanji, hiragana, or
+/// katakana). Duplicated from [`MarkdownGenerator::is_cjk`] (a private
+/// method, not reachable from this free function) rather than refactoring
+/// visibility across an unrelated change.
+fn is_cjk_char(c: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&c)
+        || ('\u{3040}'..='\u{309F}').contains(&c)
+        || ('\u{30A0}'..='\u{30FF}').contains(&c)
+}
+
+/// Convert a full-width ASCII character (`！？，：（）` and friends, U+FF01
+/// through U+FF5E) to its half-width equivalent. The full-width block is a
+/// fixed 0xFEE0 offset from half-width ASCII.
+fn fullwidth_to_halfwidth(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Whether `text[pos..]` starts an `http://` or `https://` URL token
+fn is_url_start(text: &str, pos: usize) -> bool {
+    text[pos..].starts_with("http://") || text[pos..].starts_with("https://")
+}
+
+/// Length in bytes of the URL token starting at `pos` (assumed to satisfy
+/// [`is_url_start`]): runs until whitespace or end of string
+fn url_token_len(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(text.len() - pos)
+}
+
+/// Insert spacing and normalize full-width ASCII at CJK/Latin boundaries,
+/// e.g. `衛星GPS画像` -> `衛星 GPS 画像`. Code spans (backtick-delimited) and
+/// `http(s)://` URLs are copied through untouched so identifiers and links
+/// never get a space or width conversion injected into them.
+fn normalize_cjk_latin_spacing(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_code_span = false;
+    let bytes_len = text.len();
+    let mut byte_pos = 0;
+
+    for c in text.chars() {
+        if in_code_span {
+            out.push(c);
+            if c == '`' {
+                in_code_span = false;
+            }
+            byte_pos += c.len_utf8();
+            continue;
+        }
+
+        if c == '`' {
+            in_code_span = true;
+            out.push(c);
+            byte_pos += c.len_utf8();
+            continue;
+        }
+
+        if byte_pos < bytes_len && is_url_start(text, byte_pos) {
+            let len = url_token_len(text, byte_pos);
+            out.push_str(&text[byte_pos..byte_pos + len]);
+            byte_pos += len;
+            continue;
+        }
+
+        let prev = out.chars().last();
+        let is_latin = c.is_ascii_alphanumeric();
+        let converted = if prev.is_some_and(|p| p.is_ascii_alphanumeric()) || is_latin {
+            fullwidth_to_halfwidth(c)
+        } else {
+            c
+        };
+        let converted_is_latin = converted.is_ascii_alphanumeric();
+
+        if let Some(prev_char) = prev {
+            let prev_is_cjk = is_cjk_char(prev_char);
+            let prev_is_latin = prev_char.is_ascii_alphanumeric();
+            let boundary = (prev_is_cjk && converted_is_latin) || (prev_is_latin && is_cjk_char(converted));
+            if boundary && prev_char != ' ' && converted != ' ' {
+                out.push(' ');
+            }
+        }
+
+        out.push(converted);
+        byte_pos += c.len_utf8();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("hello/world"), "hello_world");
+        assert_eq!(sanitize_filename("test:file"), "test_file");
+        assert_eq!(sanitize_filename("normal_file"), "normal_file");
+        assert_eq!(sanitize_filename("日本語テスト"), "日本語テスト");
+    }
+
+    #[test]
+    fn test_sort_text_blocks_vertical() {
+        let blocks = vec![
+            TextBlock {
+                text: "左列".into(),
+                bbox: (100, 0, 50, 500),
+                confidence: 0.9,
+                direction: TextDirection::Vertical,
+                font_size: Some(12.0),
+            },
             TextBlock {
                 text: "右列".into(),
                 bbox: (500, 0, 50, 500),
@@ -1760,4 +3243,1060 @@ mod tests {
         assert!(!result.contains("OIL"));
         assert!(result.contains("Real text"));
     }
+
+    // ============ DocumentRenderer Tests ============
+
+    #[test]
+    fn test_generate_page_markdown_renders_embedded_headings() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![
+                ContentElement::Text {
+                    content: "## 大見出し\n\n### 小見出し\n\n本文".into(),
+                    direction: TextDirection::Horizontal,
+                },
+                ContentElement::PageBreak,
+            ],
+        };
+
+        let md = gen.generate_page_markdown(&content).unwrap();
+        assert!(md.contains("## 大見出し"));
+        assert!(md.contains("### 小見出し"));
+        assert!(md.contains("本文"));
+    }
+
+    /// A trivial second `DocumentRenderer` impl (like an HTML/EPUB backend
+    /// would be), proving `render_page_content` doesn't hard-code Markdown
+    struct PlainTextRenderer;
+
+    impl DocumentRenderer for PlainTextRenderer {
+        fn emit_heading(&self, level: u8, text: &str) -> String {
+            format!("H{}:{}\n", level, text)
+        }
+
+        fn emit_paragraph(&self, text: &str, _direction: TextDirection) -> String {
+            format!("P:{}\n", text)
+        }
+
+        fn emit_figure(&self, image_path: &Path, caption: Option<&str>) -> String {
+            format!(
+                "FIG:{}:{}\n",
+                image_path.display(),
+                caption.unwrap_or_default()
+            )
+        }
+
+        fn emit_full_page_image(&self, image_path: &Path) -> String {
+            format!("IMG:{}\n", image_path.display())
+        }
+
+        fn emit_page_break(&self) -> String {
+            "BREAK\n".to_string()
+        }
+    }
+
+    #[test]
+    fn test_merge_pages_with_toc_prefixes_contents_list() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let pages = vec![
+            PageContent {
+                page_index: 0,
+                elements: vec![
+                    ContentElement::Text {
+                        content: "## 第一章\n\n本文です".into(),
+                        direction: TextDirection::Horizontal,
+                    },
+                    ContentElement::PageBreak,
+                ],
+            },
+            PageContent {
+                page_index: 1,
+                elements: vec![
+                    ContentElement::Text {
+                        content: "## 第二章\n\n続きの本文".into(),
+                        direction: TextDirection::Horizontal,
+                    },
+                    ContentElement::PageBreak,
+                ],
+            },
+        ];
+
+        let merged_path = gen.merge_pages_with_toc("テストブック", &pages).unwrap();
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+
+        assert!(content.contains("# テストブック"));
+        assert!(content.contains("# Contents"));
+        assert!(content.contains("[第一章](#第一章)"));
+        assert!(content.contains("[第二章](#第二章)"));
+        assert!(content.contains("## 第一章"));
+        assert!(content.contains("本文です"));
+        assert!(content.contains("続きの本文"));
+    }
+
+    #[test]
+    fn test_render_page_content_is_generic_over_renderer() {
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![
+                ContentElement::Text {
+                    content: "## 見出し\n\n本文".into(),
+                    direction: TextDirection::Horizontal,
+                },
+                ContentElement::Figure {
+                    image_path: PathBuf::from("images/fig.png"),
+                    caption: Some("図".into()),
+                },
+                ContentElement::PageBreak,
+            ],
+        };
+
+        let out = render_page_content(&PlainTextRenderer, &content);
+        assert!(out.contains("H2:見出し"));
+        assert!(out.contains("P:本文"));
+        assert!(out.contains("FIG:images/fig.png:図"));
+        assert!(out.contains("BREAK"));
+    }
+
+    // ============ Furigana Annotation Tests ============
+
+    #[test]
+    fn test_generate_page_markdown_without_furigana_is_unchanged() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "漢字の本文".into(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+
+        let md = gen.generate_page_markdown(&content).unwrap();
+        assert!(md.contains("漢字の本文"));
+        assert!(!md.contains('{'));
+    }
+
+    #[test]
+    fn test_generate_page_markdown_with_furigana_annotates_kanji() {
+        use crate::furigana::{BuiltinReadingDict, RubyFormat};
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_furigana_annotation(Box::new(BuiltinReadingDict::new()), RubyFormat::Brackets);
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "漢字の本文".into(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+
+        let md = gen.generate_page_markdown(&content).unwrap();
+        assert!(md.contains("{漢字|かんじ}の本文"));
+    }
+
+    // ============ Search Index Tests ============
+
+    #[test]
+    fn test_tokenize_cjk_produces_bigrams() {
+        let tokens = MarkdownGenerator::tokenize("東京駅");
+        assert_eq!(tokens, vec!["東京", "京駅"]);
+    }
+
+    #[test]
+    fn test_tokenize_latin_splits_on_whitespace_and_lowercases() {
+        let tokens = MarkdownGenerator::tokenize("Hello World");
+        assert_eq!(tokens, vec!["hello", "world"]);
+    }
+
+    #[test]
+    fn test_tokenize_mixed_cjk_and_latin() {
+        let tokens = MarkdownGenerator::tokenize("東京 Tokyo");
+        assert_eq!(tokens, vec!["東京", "tokyo"]);
+    }
+
+    #[test]
+    fn test_tokenize_single_cjk_char_is_its_own_token() {
+        let tokens = MarkdownGenerator::tokenize("駅");
+        assert_eq!(tokens, vec!["駅"]);
+    }
+
+    #[test]
+    fn test_build_search_index_indexes_retained_blocks() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![TextBlock {
+                text: "東京駅".into(),
+                bbox: (10, 20, 100, 30),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(12.0),
+            }],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let index = gen.build_search_index(&[(0, ocr)]);
+        let postings = index.terms.get("東京").expect("term should be indexed");
+        assert_eq!(postings.len(), 1);
+        assert_eq!(postings[0].page, 0);
+        assert_eq!(postings[0].bbox, (10, 20, 100, 30));
+        assert_eq!(postings[0].weight, 1.0);
+    }
+
+    #[test]
+    fn test_build_search_index_weights_headings_higher() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![
+                TextBlock {
+                    text: "見出し".into(),
+                    bbox: (0, 0, 100, 30),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(30.0),
+                },
+                TextBlock {
+                    text: "本文".into(),
+                    bbox: (0, 40, 100, 30),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(12.0),
+                },
+            ],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let index = gen.build_search_index(&[(0, ocr)]);
+        let heading_postings = index.terms.get("見出").unwrap();
+        let body_postings = index.terms.get("本文").unwrap();
+        assert_eq!(heading_postings[0].weight, HEADING_TERM_WEIGHT);
+        assert_eq!(body_postings[0].weight, 1.0);
+    }
+
+    #[test]
+    fn test_build_search_index_skips_noise_blocks() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![TextBlock {
+                text: "0000000000000000".into(), // long digit run -> noise
+                bbox: (0, 0, 100, 30),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(12.0),
+            }],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let index = gen.build_search_index(&[(0, ocr)]);
+        assert!(index.terms.is_empty());
+    }
+
+    #[test]
+    fn test_write_search_index_creates_json_file() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![TextBlock {
+                text: "本文".into(),
+                bbox: (0, 0, 100, 30),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(12.0),
+            }],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let path = gen.write_search_index("テスト", &[(0, ocr)]).unwrap();
+        assert!(path.exists());
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("本文"));
+    }
+
+    // ============ S-Expression Dump Tests ============
+
+    #[test]
+    fn test_dump_sexp_cover_page() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![],
+            confidence: 0.0,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let dump = gen.dump_sexp(0, &ocr, &PageClassification::Cover, &[]);
+        assert!(dump.starts_with("(page 0"));
+        assert!(dump.contains("(cover)"));
+        assert!(dump.contains("(page-break))"));
+    }
+
+    #[test]
+    fn test_dump_sexp_text_only_reports_heading_and_paragraph() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![
+                TextBlock {
+                    text: "見出し".into(),
+                    bbox: (0, 0, 100, 30),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(30.0),
+                },
+                TextBlock {
+                    text: "本文です".into(),
+                    bbox: (0, 40, 100, 30),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(12.0),
+                },
+            ],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let dump = gen.dump_sexp(1, &ocr, &PageClassification::TextOnly, &[]);
+        assert!(dump.contains("(heading 2 \"見出し\""));
+        assert!(dump.contains("(paragraph :dir horizontal \"本文です\""));
+        assert!(dump.contains(":median-font-size"));
+        assert!(dump.contains(":median-line-height"));
+    }
+
+    #[test]
+    fn test_dump_sexp_reports_dropped_noise_blocks() {
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![TextBlock {
+                text: "0000000000000000".into(),
+                bbox: (0, 0, 100, 30),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(12.0),
+            }],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let dump = gen.dump_sexp(0, &ocr, &PageClassification::TextOnly, &[]);
+        assert!(dump.contains("(dropped :reason noise"));
+    }
+
+    #[test]
+    fn test_dump_sexp_mixed_interleaves_figure() {
+        use crate::figure_detect::RegionType;
+        use std::time::Duration;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        let ocr = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![TextBlock {
+                text: "本文".into(),
+                bbox: (0, 300, 100, 30),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(12.0),
+            }],
+            confidence: 0.9,
+            processing_time: Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let fig = FigureRegion {
+            bbox: (0, 0, 200, 100),
+            area: 20000,
+            region_type: RegionType::Figure,
+        };
+        let fig_path = tmpdir.path().join("images").join("figure_001.png");
+        let figures = vec![fig.clone()];
+        let figure_images = vec![(fig, fig_path)];
+
+        let dump = gen.dump_sexp(
+            0,
+            &ocr,
+            &PageClassification::Mixed { figures },
+            &figure_images,
+        );
+        let figure_pos = dump.find("(figure").unwrap();
+        let paragraph_pos = dump.find("(paragraph").unwrap();
+        assert!(figure_pos < paragraph_pos, "figure above text should be emitted first");
+    }
+
+    #[test]
+    fn test_normalize_with_ruby_pairs_kanji_line_html() {
+        let input = "里見祐介\nさとみ ゆうすけ\nSome text\n";
+        let result = MarkdownGenerator::normalize_markdown_with_ruby(input, RubyPairingFormat::Html);
+        assert!(!result.contains("さとみ ゆうすけ"));
+        assert!(result.contains("<ruby>"));
+        assert!(result.contains("<rt>"));
+        assert!(result.contains("Some text"));
+    }
+
+    #[test]
+    fn test_normalize_with_ruby_pairs_kanji_line_novel() {
+        let input = "里見祐介\nさとみ ゆうすけ\n";
+        let result = MarkdownGenerator::normalize_markdown_with_ruby(input, RubyPairingFormat::Novel);
+        assert!(result.contains('《'));
+        assert!(result.contains('》'));
+        assert!(!result.contains("さとみ ゆうすけ"));
+    }
+
+    #[test]
+    fn test_normalize_with_ruby_falls_back_without_kanji_line() {
+        let input = "さとみ ゆうすけ\nSome text\n";
+        let result = MarkdownGenerator::normalize_markdown_with_ruby(input, RubyPairingFormat::Html);
+        assert!(!result.contains("さとみ ゆうすけ"));
+        assert!(!result.contains("<ruby>"));
+        assert!(result.contains("Some text"));
+    }
+
+    #[test]
+    fn test_normalize_with_ruby_rejects_mismatched_lengths() {
+        // "東京駅前" (4 kanji chars) vs an unrelated 13-kana furigana reading:
+        // ratio 3.25 is over FURIGANA_LENGTH_RATIO_MAX
+        let input = "東京駅前\nあいうえお かきくけこさしす\n";
+        let result = MarkdownGenerator::normalize_markdown_with_ruby(input, RubyPairingFormat::Html);
+        assert!(!result.contains("<ruby>"));
+        assert!(!result.contains("あいうえお かきくけこさしす"));
+    }
+
+    #[test]
+    fn test_render_ruby_pair_splits_per_character_on_equal_length() {
+        let result = MarkdownGenerator::render_ruby_pair("小野", "おの", RubyPairingFormat::Html);
+        assert_eq!(result, "<ruby>小<rt>お</rt></ruby><ruby>野<rt>の</rt></ruby>");
+    }
+
+    #[test]
+    fn test_render_ruby_pair_whole_span_on_mismatched_length() {
+        let result = MarkdownGenerator::render_ruby_pair("日本", "にっぽん", RubyPairingFormat::Html);
+        assert_eq!(result, "<ruby>日本<rt>にっぽん</rt></ruby>");
+    }
+
+    #[test]
+    fn test_is_kanji_char_boundaries() {
+        assert!(MarkdownGenerator::is_kanji_char('漢'));
+        assert!(!MarkdownGenerator::is_kanji_char('あ'));
+        assert!(!MarkdownGenerator::is_kanji_char('ア'));
+        assert!(!MarkdownGenerator::is_kanji_char('A'));
+    }
+
+    #[test]
+    fn test_generate_page_markdown_with_ruby_preservation_enabled() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_furigana_ruby_preservation(RubyPairingFormat::Html);
+
+        let content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "里見祐介\nさとみ ゆうすけ".into(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+        let md = gen.generate_page_markdown(&content).unwrap();
+        assert!(md.contains("<ruby>"));
+        assert!(!md.contains("さとみ ゆうすけ"));
+    }
+
+    #[test]
+    fn test_merge_pages_preserves_unicode_title_by_default() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        let output_path = gen.merge_pages("日本語", 0).unwrap();
+        assert_eq!(output_path.file_name().unwrap(), "日本語.md");
+    }
+
+    #[test]
+    fn test_merge_pages_with_romaji_slug_style() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_filename_style(FilenameStyle::RomajiSlug);
+        let output_path = gen.merge_pages("日本語のタイトル", 0).unwrap();
+        assert_eq!(output_path.file_name().unwrap(), "nihongo_no_taitoru.md");
+    }
+
+    #[test]
+    fn test_merge_pages_with_toc_with_romaji_slug_style() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_filename_style(FilenameStyle::RomajiSlug);
+        let output_path = gen.merge_pages_with_toc("日本", &[]).unwrap();
+        assert_eq!(output_path.file_name().unwrap(), "nihon.md");
+    }
+
+    #[test]
+    fn test_strip_bom_removes_leading_marker() {
+        assert_eq!(strip_bom("\u{FEFF}hello"), "hello");
+        assert_eq!(strip_bom("hello"), "hello");
+    }
+
+    #[test]
+    fn test_detect_line_ending_lf_only() {
+        assert_eq!(detect_line_ending("a\nb\nc\n"), DetectedLineEnding::Lf);
+    }
+
+    #[test]
+    fn test_detect_line_ending_crlf_only() {
+        assert_eq!(detect_line_ending("a\r\nb\r\n"), DetectedLineEnding::Crlf);
+    }
+
+    #[test]
+    fn test_detect_line_ending_cr_only() {
+        assert_eq!(detect_line_ending("a\rb\rc"), DetectedLineEnding::Cr);
+    }
+
+    #[test]
+    fn test_detect_line_ending_mixed() {
+        let result = detect_line_ending("a\nb\r\nc\rd");
+        assert_eq!(
+            result,
+            DetectedLineEnding::Mixed {
+                cr: 1,
+                lf: 1,
+                crlf: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_detect_line_ending_empty_defaults_to_lf() {
+        assert_eq!(detect_line_ending(""), DetectedLineEnding::Lf);
+    }
+
+    #[test]
+    fn test_normalize_line_endings_collapses_crlf_and_cr() {
+        assert_eq!(normalize_line_endings("a\r\nb\rc\n"), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn test_normalize_markdown_strips_bom_and_crlf() {
+        let input = "\u{FEFF}Line one\r\nLine two\r\n";
+        let result = MarkdownGenerator::normalize_markdown(input);
+        assert!(!result.contains('\u{FEFF}'));
+        assert!(!result.contains('\r'));
+        assert!(result.contains("Line one"));
+        assert!(result.contains("Line two"));
+    }
+
+    #[test]
+    fn test_save_page_markdown_with_crlf_line_ending() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_line_ending(LineEnding::CrLf);
+        let page_path = gen.save_page_markdown(0, "line one\nline two\n").unwrap();
+        let content = std::fs::read_to_string(&page_path).unwrap();
+        assert_eq!(content, "line one\r\nline two\r\n");
+    }
+
+    #[test]
+    fn test_merge_pages_with_crlf_line_ending() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_line_ending(LineEnding::CrLf);
+        gen.save_page_markdown(0, "page one\n").unwrap();
+
+        let merged_path = gen.merge_pages("タイトル", 1).unwrap();
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(content.contains("page one\r\n"));
+        assert!(!content.contains("\r\r"));
+    }
+
+    #[test]
+    fn test_merge_pages_defaults_to_lf_line_ending() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+        gen.save_page_markdown(0, "page one\n").unwrap();
+
+        let merged_path = gen.merge_pages("タイトル", 1).unwrap();
+        let content = std::fs::read_to_string(&merged_path).unwrap();
+        assert!(!content.contains('\r'));
+    }
+
+    #[test]
+    fn test_is_furigana_block_candidate_qualifies() {
+        let kanji = TextBlock {
+            text: "里見祐介".into(),
+            bbox: (100, 100, 200, 40),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(24.0),
+        };
+        let furigana = TextBlock {
+            text: "さとみ ゆうすけ".into(),
+            bbox: (110, 70, 150, 15),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(10.0),
+        };
+        assert!(MarkdownGenerator::is_furigana_block_candidate(&kanji, &furigana));
+    }
+
+    #[test]
+    fn test_is_furigana_block_candidate_rejects_same_font_size() {
+        let kanji = TextBlock {
+            text: "里見祐介".into(),
+            bbox: (100, 100, 200, 40),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(24.0),
+        };
+        let not_furigana = TextBlock {
+            text: "さとみ ゆうすけ".into(),
+            bbox: (110, 70, 150, 15),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(24.0),
+        };
+        assert!(!MarkdownGenerator::is_furigana_block_candidate(
+            &kanji,
+            &not_furigana
+        ));
+    }
+
+    #[test]
+    fn test_is_furigana_block_candidate_rejects_no_horizontal_overlap() {
+        let kanji = TextBlock {
+            text: "里見祐介".into(),
+            bbox: (100, 100, 200, 40),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(24.0),
+        };
+        let far_away = TextBlock {
+            text: "さとみ ゆうすけ".into(),
+            bbox: (900, 70, 150, 15),
+            confidence: 0.9,
+            direction: TextDirection::Horizontal,
+            font_size: Some(10.0),
+        };
+        assert!(!MarkdownGenerator::is_furigana_block_candidate(
+            &kanji,
+            &far_away
+        ));
+    }
+
+    #[test]
+    fn test_pair_furigana_blocks_by_bbox_folds_into_ruby() {
+        let blocks = vec![
+            TextBlock {
+                text: "里見祐介".into(),
+                bbox: (100, 100, 200, 40),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(24.0),
+            },
+            TextBlock {
+                text: "さとみ ゆうすけ".into(),
+                bbox: (110, 70, 150, 15),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(10.0),
+            },
+        ];
+
+        let paired =
+            MarkdownGenerator::pair_furigana_blocks_by_bbox(&blocks, RubyPairingFormat::Html);
+        assert_eq!(paired.len(), 1);
+        assert!(paired[0].text.contains("<ruby>"));
+        assert!(paired[0].text.contains("<rt>"));
+    }
+
+    #[test]
+    fn test_pair_furigana_blocks_by_bbox_leaves_unrelated_blocks_untouched() {
+        let blocks = vec![
+            TextBlock {
+                text: "本文テキスト".into(),
+                bbox: (100, 100, 200, 40),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(24.0),
+            },
+            TextBlock {
+                text: "別の本文".into(),
+                bbox: (100, 200, 200, 40),
+                confidence: 0.9,
+                direction: TextDirection::Horizontal,
+                font_size: Some(24.0),
+            },
+        ];
+
+        let paired =
+            MarkdownGenerator::pair_furigana_blocks_by_bbox(&blocks, RubyPairingFormat::Html);
+        assert_eq!(paired.len(), 2);
+        assert_eq!(paired[0].text, "本文テキスト");
+        assert_eq!(paired[1].text, "別の本文");
+    }
+
+    #[test]
+    fn test_build_page_content_with_ruby_mode_merges_furigana_block() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path())
+            .unwrap()
+            .with_furigana_ruby_preservation(RubyPairingFormat::Html);
+
+        let ocr_result = OcrResult {
+            input_path: "test.png".into(),
+            text_blocks: vec![
+                TextBlock {
+                    text: "里見祐介".into(),
+                    bbox: (100, 100, 200, 40),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(24.0),
+                },
+                // Starts below the kanji block, so it follows it in reading order
+                TextBlock {
+                    text: "さとみ ゆうすけ".into(),
+                    bbox: (110, 145, 150, 15),
+                    confidence: 0.9,
+                    direction: TextDirection::Horizontal,
+                    font_size: Some(10.0),
+                },
+            ],
+            confidence: 0.9,
+            processing_time: std::time::Duration::from_millis(10),
+            text_direction: TextDirection::Horizontal,
+        };
+
+        let page_content = gen.build_page_content(
+            0,
+            &ocr_result,
+            &PageClassification::TextOnly,
+            &[],
+        );
+
+        let md = gen.generate_page_markdown(&page_content).unwrap();
+        assert!(md.contains("<ruby>"));
+        assert!(!md.contains("さとみ ゆうすけ"));
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_inserts_boundary_space() {
+        assert_eq!(normalize_cjk_latin_spacing("衛星GPS画像"), "衛星 GPS 画像");
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_no_double_space() {
+        assert_eq!(normalize_cjk_latin_spacing("衛星 GPS 画像"), "衛星 GPS 画像");
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_converts_fullwidth_punctuation_next_to_latin() {
+        assert_eq!(normalize_cjk_latin_spacing("Part1！"), "Part1!");
+        assert_eq!(normalize_cjk_latin_spacing("GPS画像ー"), "GPS 画像ー");
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_leaves_pure_cjk_fullwidth_punctuation() {
+        assert_eq!(normalize_cjk_latin_spacing("今日は！"), "今日は！");
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_protects_code_span() {
+        assert_eq!(normalize_cjk_latin_spacing("説明`衛星GPS画像`です"), "説明`衛星GPS画像`です");
+    }
+
+    #[test]
+    fn test_normalize_cjk_latin_spacing_protects_url() {
+        let input = "詳細はhttp://example.com/衛星GPSを参照";
+        assert_eq!(normalize_cjk_latin_spacing(input), input);
+    }
+
+    #[test]
+    fn test_generate_page_markdown_default_preserves_spacing() {
+        let dir = std::env::temp_dir().join(format!("md_gen_test_{}", std::process::id()));
+        let gen = MarkdownGenerator::new(&dir).unwrap();
+
+        let page_content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "衛星GPS画像".to_string(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+
+        let md = gen.generate_page_markdown(&page_content).unwrap();
+        assert!(md.contains("衛星GPS画像"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_generate_page_markdown_with_cjk_latin_spacing_enabled() {
+        let dir = std::env::temp_dir().join(format!("md_gen_test_{}", std::process::id() + 1));
+        let gen = MarkdownGenerator::new(&dir)
+            .unwrap()
+            .with_cjk_latin_spacing(CjkLatinSpacing::Normalize);
+
+        let page_content = PageContent {
+            page_index: 0,
+            elements: vec![ContentElement::Text {
+                content: "衛星GPS画像".to_string(),
+                direction: TextDirection::Horizontal,
+            }],
+        };
+
+        let md = gen.generate_page_markdown(&page_content).unwrap();
+        assert!(md.contains("衛星 GPS 画像"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_split_into_mdbook_chapters_by_heading() {
+        let chapters = MarkdownGenerator::split_into_mdbook_chapters(
+            "## 第一章\n\n本文1\n\n## 第二章\n\n本文2",
+        );
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "第一章");
+        assert!(chapters[0].body.contains("本文1"));
+        assert_eq!(chapters[1].title, "第二章");
+        assert!(chapters[1].body.contains("本文2"));
+    }
+
+    #[test]
+    fn test_split_into_mdbook_chapters_untitled_before_first_heading() {
+        let chapters =
+            MarkdownGenerator::split_into_mdbook_chapters("前書き本文\n\n## 本編");
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "Untitled");
+        assert!(chapters[0].body.contains("前書き本文"));
+        assert_eq!(chapters[1].title, "本編");
+    }
+
+    #[test]
+    fn test_split_into_mdbook_chapters_collects_subsection_titles() {
+        let chapters = MarkdownGenerator::split_into_mdbook_chapters(
+            "## 第一章\n\n### 第一節\n\n本文\n\n### 第二節\n\n本文2",
+        );
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].subsection_titles, vec!["第一節", "第二節"]);
+    }
+
+    #[test]
+    fn test_split_into_mdbook_chapters_page_break_does_not_start_chapter() {
+        let chapters = MarkdownGenerator::split_into_mdbook_chapters(
+            "## 第一章\n\n本文1\n\n---\n\n本文2",
+        );
+        assert_eq!(chapters.len(), 1);
+        assert!(chapters[0].body.contains("---"));
+        assert!(chapters[0].body.contains("本文2"));
+    }
+
+    #[test]
+    fn test_mdbook_heading_anchor_disambiguates_repeats() {
+        let mut counts = std::collections::HashMap::new();
+        assert_eq!(mdbook_heading_anchor("Intro", &mut counts), "intro");
+        assert_eq!(mdbook_heading_anchor("Intro", &mut counts), "intro-1");
+    }
+
+    #[test]
+    fn test_export_mdbook_writes_book_toml_summary_and_chapters() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        gen.save_page_markdown(0, "## 第一章\n\n本文1\n\n### 第一節\n\n詳細\n\n")
+            .unwrap();
+        gen.save_page_markdown(1, "## 第二章\n\n本文2\n\n").unwrap();
+
+        let book_dir = gen.export_mdbook("テスト本", 2).unwrap();
+
+        let book_toml = std::fs::read_to_string(book_dir.join("book.toml")).unwrap();
+        assert!(book_toml.contains("title = \"テスト本\""));
+        assert!(book_toml.contains("src = \"src\""));
+
+        let summary = std::fs::read_to_string(book_dir.join("src/SUMMARY.md")).unwrap();
+        assert!(summary.starts_with("# Summary\n"));
+        assert!(summary.contains("- [第一章](chapter_01.md)"));
+        assert!(summary.contains("  - [第一節](chapter_01.md#"));
+        assert!(summary.contains("- [第二章](chapter_02.md)"));
+
+        let chapter1 = std::fs::read_to_string(book_dir.join("src/chapter_01.md")).unwrap();
+        assert!(chapter1.starts_with("# 第一章\n"));
+        assert!(chapter1.contains("本文1"));
+        assert!(chapter1.contains("### 第一節"));
+
+        let chapter2 = std::fs::read_to_string(book_dir.join("src/chapter_02.md")).unwrap();
+        assert!(chapter2.starts_with("# 第二章\n"));
+        assert!(chapter2.contains("本文2"));
+    }
+
+    #[test]
+    fn test_export_mdbook_skips_missing_page() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let gen = MarkdownGenerator::new(tmpdir.path()).unwrap();
+
+        // Save only page 0 and 2, skip page 1 (mirrors test_merge_pages_missing_page)
+        gen.save_page_markdown(0, "## 第一章\n\n本文1\n\n").unwrap();
+        gen.save_page_markdown(2, "本文3\n\n").unwrap();
+
+        let book_dir = gen.export_mdbook("テスト", 3).unwrap();
+        let chapter1 = std::fs::read_to_string(book_dir.join("src/chapter_01.md")).unwrap();
+        assert!(chapter1.contains("本文1"));
+        assert!(chapter1.contains("本文3"));
+    }
+
+    #[test]
+    fn test_sanitize_filename_romaji_transliterates_kanji_compound() {
+        // 日本 -> にほん -> "nihon"
+        assert_eq!(sanitize_filename_romaji("日本"), "nihon");
+    }
+
+    #[test]
+    fn test_sanitize_filename_romaji_mixed_kanji_and_kana() {
+        assert_eq!(sanitize_filename_romaji("日本語のタイトル"), "nihongo_no_taitoru");
+    }
+
+    #[test]
+    fn test_sanitize_filename_romaji_passes_ascii_through() {
+        assert_eq!(sanitize_filename_romaji("Report 2024"), "report_2024");
+    }
+
+    #[test]
+    fn test_sanitize_filename_romaji_unmapped_kanji_falls_back_to_underscore() {
+        // No dictionary entry for any of these: each collapses into the
+        // underscore separator, same as romaji_slug's own fallback, and the
+        // final trim of leading/trailing `_` leaves an empty slug.
+        assert_eq!(sanitize_filename_romaji("未知語"), "");
+    }
+
+    #[test]
+    fn test_sanitize_filename_romaji_result_has_no_path_hostile_characters() {
+        let result = sanitize_filename_romaji("日本語/テスト");
+        assert!(!result.contains('/'));
+    }
+
+    #[test]
+    fn test_sanitize_filename_default_behavior_unchanged() {
+        // sanitize_filename itself keeps preserving Unicode verbatim
+        assert_eq!(sanitize_filename("日本語テスト"), "日本語テスト");
+    }
+
+    #[test]
+    fn test_normalize_via_ast_ensures_blank_line_around_heading() {
+        let result = MarkdownGenerator::normalize_via_ast("本文1\n\n## 見出し\n\n本文2");
+        assert_eq!(result, "本文1\n\n## 見出し\n\n本文2\n");
+    }
+
+    #[test]
+    fn test_normalize_via_ast_escapes_mid_text_emphasis_characters() {
+        let result = MarkdownGenerator::normalize_via_ast("値は*重要*で_ある_と`言う`");
+        assert_eq!(result, "値は\\*重要\\*で\\_ある\\_と\\`言う\\`\n");
+    }
+
+    #[test]
+    fn test_normalize_via_ast_escapes_stray_list_marker_mid_paragraph() {
+        let result = MarkdownGenerator::normalize_via_ast("本文1行目\n+ 本文2行目");
+        assert_eq!(result, "本文1行目\n\\+ 本文2行目\n");
+    }
+
+    #[test]
+    fn test_normalize_via_ast_preserves_thematic_break() {
+        let result = MarkdownGenerator::normalize_via_ast("本文1\n\n---\n\n本文2");
+        assert_eq!(result, "本文1\n\n---\n\n本文2\n");
+    }
+
+    #[test]
+    fn test_normalize_via_ast_preserves_heading_levels() {
+        let blocks = MarkdownGenerator::parse_commonmark_ast("### 小見出し");
+        assert_eq!(
+            blocks,
+            vec![AstBlock::Heading {
+                level: 3,
+                text: "小見出し".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_normalize_via_ast_strict_flags_accidental_markup() {
+        let blocks = MarkdownGenerator::parse_commonmark_ast("本文1行目\n- 本文2行目");
+        assert!(matches!(
+            blocks[0],
+            AstBlock::Paragraph {
+                had_accidental_markup: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_normalize_via_ast_strict_no_flag_for_clean_paragraph() {
+        let blocks = MarkdownGenerator::parse_commonmark_ast("きれいな本文だけの段落");
+        assert!(matches!(
+            blocks[0],
+            AstBlock::Paragraph {
+                had_accidental_markup: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_normalize_via_ast_strict_round_trips_same_as_non_strict_for_clean_input() {
+        let clean = "本文1\n\n## 見出し\n\n本文2";
+        assert_eq!(
+            MarkdownGenerator::normalize_via_ast(clean),
+            MarkdownGenerator::normalize_via_ast_strict(clean)
+        );
+    }
+
+    #[test]
+    fn test_is_thematic_break_requires_three_or_more_markers() {
+        assert!(MarkdownGenerator::is_thematic_break("---"));
+        assert!(MarkdownGenerator::is_thematic_break("- - -"));
+        assert!(!MarkdownGenerator::is_thematic_break("--"));
+        assert!(!MarkdownGenerator::is_thematic_break("本文"));
+    }
 }
@@ -0,0 +1,285 @@
+//! Markdown → PDF module
+//!
+//! Closes the loop the rest of the crate opens: `MarkdownGenerator` turns a
+//! scanned PDF into Markdown, and `MarkdownToPdfConverter` turns Markdown back
+//! into a styled PDF, so a PDF → Markdown → edit → PDF round trip stays
+//! entirely inside this crate instead of shelling out to an external
+//! HTML-to-PDF tool. Conversion goes through an HTML intermediate: a
+//! CommonMark/GFM parser produces the body, an embedded (and overridable) CSS
+//! stylesheet supplies the heading scale, table borders and vertical-writing
+//! support for CJK, and `libwkhtmltox` rasterizes the result in-process via
+//! the `wkhtmltopdf` crate's FFI binding rather than spawning its CLI.
+//!
+//! Unlike the PDF → Markdown direction, this one has no pipeline of its own
+//! to hang off of: it's a standalone library entry point meant to be called
+//! from a future `markdown-to-pdf` CLI subcommand (`crate::cli`, alongside
+//! [`crate::markdown_pipeline::MarkdownPipeline::from_args`]'s
+//! `MarkdownArgs`), not from [`crate::markdown_pipeline::MarkdownPipeline`]
+//! itself.
+
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use pulldown_cmark::{html, Options as CmarkOptions, Parser};
+use wkhtmltopdf::{PdfApplication, Size};
+
+/// Error type for Markdown -> PDF conversion
+#[derive(Debug, Error)]
+pub enum MarkdownToPdfError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("HTML-to-PDF rendering failed: {0}")]
+    RenderError(String),
+}
+
+/// Paper size presets for the rendered PDF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    A4,
+    B5,
+    Letter,
+}
+
+impl PaperSize {
+    /// Width/height in millimeters, portrait orientation
+    fn dimensions_mm(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::B5 => (176.0, 250.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+/// Page margins in millimeters
+#[derive(Debug, Clone, Copy)]
+pub struct Margins {
+    pub top_mm: f32,
+    pub right_mm: f32,
+    pub bottom_mm: f32,
+    pub left_mm: f32,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self {
+            top_mm: 20.0,
+            right_mm: 18.0,
+            bottom_mm: 20.0,
+            left_mm: 18.0,
+        }
+    }
+}
+
+/// Default stylesheet: heading scale, table borders, and a `.tategaki` class
+/// for vertical Japanese text. [`MarkdownToPdfConverter::with_custom_css`]
+/// content is appended after this, so it overrides these rules on conflict.
+const DEFAULT_CSS: &str = r#"
+body { font-family: "Noto Sans CJK JP", sans-serif; line-height: 1.7; }
+h1 { font-size: 1.8em; border-bottom: 2px solid #333; padding-bottom: 0.2em; }
+h2 { font-size: 1.4em; border-bottom: 1px solid #999; padding-bottom: 0.15em; }
+h3 { font-size: 1.15em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #666; padding: 0.3em 0.6em; }
+.tategaki { writing-mode: vertical-rl; -epub-writing-mode: vertical-rl; }
+"#;
+
+/// Converts Markdown (this crate's own output, or any CommonMark/GFM document)
+/// to a styled PDF.
+pub struct MarkdownToPdfConverter {
+    paper_size: PaperSize,
+    margins: Margins,
+    font_files: Vec<PathBuf>,
+    custom_css: Option<String>,
+    vertical_writing: bool,
+}
+
+impl MarkdownToPdfConverter {
+    /// Create a converter with A4 paper, the default margins, and no
+    /// embedded fonts or custom CSS
+    pub fn new() -> Self {
+        Self {
+            paper_size: PaperSize::A4,
+            margins: Margins::default(),
+            font_files: Vec::new(),
+            custom_css: None,
+            vertical_writing: false,
+        }
+    }
+
+    /// Override the paper size (default [`PaperSize::A4`])
+    pub fn with_paper_size(mut self, paper_size: PaperSize) -> Self {
+        self.paper_size = paper_size;
+        self
+    }
+
+    /// Override the page margins (default [`Margins::default`])
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    /// Register a font file to embed via `@font-face`, guaranteeing glyph
+    /// coverage (e.g. Japanese) independent of what's installed on the
+    /// rendering machine. May be called more than once.
+    pub fn with_font_file(mut self, font_path: &Path) -> Self {
+        self.font_files.push(font_path.to_path_buf());
+        self
+    }
+
+    /// Append `css` after [`DEFAULT_CSS`], so its rules win on conflict
+    pub fn with_custom_css(mut self, css: &str) -> Self {
+        self.custom_css = Some(css.to_string());
+        self
+    }
+
+    /// Render the document body in vertical (tategaki) writing mode
+    pub fn with_vertical_writing(mut self, vertical: bool) -> Self {
+        self.vertical_writing = vertical;
+        self
+    }
+
+    /// Parse `markdown` (CommonMark plus GFM tables, strikethrough and
+    /// footnotes) to an HTML fragment
+    pub fn markdown_to_html(&self, markdown: &str) -> String {
+        let mut options = CmarkOptions::empty();
+        options.insert(CmarkOptions::ENABLE_TABLES);
+        options.insert(CmarkOptions::ENABLE_STRIKETHROUGH);
+        options.insert(CmarkOptions::ENABLE_FOOTNOTES);
+
+        let parser = Parser::new_ext(markdown, options);
+        let mut html_fragment = String::new();
+        html::push_html(&mut html_fragment, parser);
+        html_fragment
+    }
+
+    /// Wrap `body_html` in a full document: the embedded stylesheet, a
+    /// `@font-face` rule per registered font file, then `custom_css` last
+    fn wrap_html_document(&self, title: &str, body_html: &str) -> String {
+        let mut style = String::from(DEFAULT_CSS);
+
+        for (i, font_path) in self.font_files.iter().enumerate() {
+            let _ = write!(
+                style,
+                "\n@font-face {{ font-family: \"embedded-{i}\"; src: url(\"file://{}\"); }}",
+                font_path.display()
+            );
+        }
+
+        if let Some(custom) = &self.custom_css {
+            style.push('\n');
+            style.push_str(custom);
+        }
+
+        let body_class = if self.vertical_writing {
+            " class=\"tategaki\""
+        } else {
+            ""
+        };
+
+        format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n\
+             <style>{style}</style>\n</head><body{body_class}>\n{body_html}\n</body></html>"
+        )
+    }
+
+    /// Render `markdown` to a PDF at `output_path`: [`Self::markdown_to_html`]
+    /// plus [`Self::wrap_html_document`] produce the HTML, then `libwkhtmltox`
+    /// rasterizes it in-process
+    pub fn convert(
+        &self,
+        markdown: &str,
+        title: &str,
+        output_path: &Path,
+    ) -> Result<(), MarkdownToPdfError> {
+        let body_html = self.markdown_to_html(markdown);
+        let document = self.wrap_html_document(title, &body_html);
+        self.render_html_to_pdf(&document, output_path)
+    }
+
+    fn render_html_to_pdf(&self, html: &str, output_path: &Path) -> Result<(), MarkdownToPdfError> {
+        let (width_mm, height_mm) = self.paper_size.dimensions_mm();
+
+        let pdf_app =
+            PdfApplication::new().map_err(|e| MarkdownToPdfError::RenderError(e.to_string()))?;
+        let mut builder = pdf_app.builder();
+        builder
+            .page_size(width_mm as u32, height_mm as u32)
+            .margin_top(Size::Millimeters(self.margins.top_mm as u32))
+            .margin_right(Size::Millimeters(self.margins.right_mm as u32))
+            .margin_bottom(Size::Millimeters(self.margins.bottom_mm as u32))
+            .margin_left(Size::Millimeters(self.margins.left_mm as u32));
+
+        let mut pdf_out = builder
+            .build_from_html(html)
+            .map_err(|e| MarkdownToPdfError::RenderError(e.to_string()))?;
+
+        pdf_out
+            .save(output_path)
+            .map_err(|e| MarkdownToPdfError::RenderError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl Default for MarkdownToPdfConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html_renders_heading() {
+        let converter = MarkdownToPdfConverter::new();
+        let html = converter.markdown_to_html("## 見出し\n\n本文");
+        assert!(html.contains("<h2>見出し</h2>"));
+        assert!(html.contains("<p>本文</p>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_supports_gfm_tables() {
+        let converter = MarkdownToPdfConverter::new();
+        let html = converter.markdown_to_html("| a | b |\n|---|---|\n| 1 | 2 |\n");
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<td>1</td>"));
+    }
+
+    #[test]
+    fn test_wrap_html_document_appends_custom_css_after_default() {
+        let converter = MarkdownToPdfConverter::new().with_custom_css("h1 { color: red; }");
+        let document = converter.wrap_html_document("タイトル", "<p>本文</p>");
+        let default_pos = document.find("h1 { font-size").unwrap();
+        let custom_pos = document.find("h1 { color: red; }").unwrap();
+        assert!(custom_pos > default_pos);
+    }
+
+    #[test]
+    fn test_wrap_html_document_embeds_font_face_for_each_font_file() {
+        let converter = MarkdownToPdfConverter::new()
+            .with_font_file(Path::new("/fonts/NotoSansJP.otf"))
+            .with_font_file(Path::new("/fonts/IPAMincho.ttf"));
+        let document = converter.wrap_html_document("タイトル", "<p>本文</p>");
+        assert!(document.contains("embedded-0"));
+        assert!(document.contains("embedded-1"));
+        assert!(document.contains("file:///fonts/NotoSansJP.otf"));
+    }
+
+    #[test]
+    fn test_wrap_html_document_adds_tategaki_class_when_vertical() {
+        let converter = MarkdownToPdfConverter::new().with_vertical_writing(true);
+        let document = converter.wrap_html_document("タイトル", "<p>本文</p>");
+        assert!(document.contains("<body class=\"tategaki\">"));
+    }
+
+    #[test]
+    fn test_paper_size_dimensions_mm() {
+        assert_eq!(PaperSize::A4.dimensions_mm(), (210.0, 297.0));
+        assert_eq!(PaperSize::Letter.dimensions_mm(), (215.9, 279.4));
+    }
+}
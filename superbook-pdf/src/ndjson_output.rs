@@ -0,0 +1,132 @@
+//! Structured per-page OCR output (NDJSON and pretty-JSON)
+//!
+//! `search_index.rs` flattens a page down to one searchable `text` field;
+//! this module keeps the raw per-block data (bbox, confidence, reading
+//! direction) so downstream tooling can stream-parse results page by page
+//! (NDJSON) or inspect a whole document at once for human review
+//! (pretty-JSON), without waiting for the full Markdown render.
+
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+
+use serde::{Deserialize, Serialize};
+
+/// Error type for structured page output
+#[derive(Debug, Error)]
+pub enum NdjsonOutputError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// One OCR text block, as it appears in a [`PageRecord`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextBlockRecord {
+    pub text: String,
+    /// `(x, y, width, height)` in page pixel coordinates
+    pub bbox: (u32, u32, u32, u32),
+    pub confidence: f32,
+}
+
+/// One page's structured OCR output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRecord {
+    pub page_index: usize,
+    pub input_path: String,
+    pub confidence: f32,
+    pub processing_time_ms: u128,
+    /// `"horizontal"`, `"vertical"` or `"mixed"`
+    pub text_direction: String,
+    pub text_blocks: Vec<TextBlockRecord>,
+}
+
+/// Combined document written by [`write_pretty_json`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrettyJsonDocument {
+    pub title: String,
+    pub pages: Vec<PageRecord>,
+}
+
+/// Write `records` to `path` as newline-delimited JSON, one object per page
+pub fn write_ndjson(path: &Path, records: &[PageRecord]) -> Result<(), NdjsonOutputError> {
+    let mut file = std::fs::File::create(path)?;
+    for record in records {
+        let line = serde_json::to_string(record)?;
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write `records` to `path` as a single pretty-printed combined document
+pub fn write_pretty_json(
+    path: &Path,
+    title: &str,
+    records: Vec<PageRecord>,
+) -> Result<(), NdjsonOutputError> {
+    let document = PrettyJsonDocument {
+        title: title.to_string(),
+        pages: records,
+    };
+    let json = serde_json::to_string_pretty(&document)?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_record(page_index: usize) -> PageRecord {
+        PageRecord {
+            page_index,
+            input_path: format!("page_{:03}.png", page_index + 1),
+            confidence: 0.9,
+            processing_time_ms: 10,
+            text_direction: "vertical".to_string(),
+            text_blocks: vec![TextBlockRecord {
+                text: "テスト".to_string(),
+                bbox: (0, 0, 100, 20),
+                confidence: 0.9,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_write_ndjson_one_line_per_record() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.ndjson");
+        write_ndjson(&path, &[sample_record(0), sample_record(1)]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let first: PageRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first.page_index, 0);
+        let second: PageRecord = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second.page_index, 1);
+    }
+
+    #[test]
+    fn test_write_ndjson_empty_list_writes_empty_file() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.ndjson");
+        write_ndjson(&path, &[]).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn test_write_pretty_json_wraps_title_and_pages() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.json");
+        write_pretty_json(&path, "テスト本", vec![sample_record(0)]).unwrap();
+
+        let loaded: PrettyJsonDocument =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded.title, "テスト本");
+        assert_eq!(loaded.pages.len(), 1);
+    }
+}
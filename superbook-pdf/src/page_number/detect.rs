@@ -1,24 +1,261 @@
 //! Page Number Detection Implementation
 //!
 //! Tesseract-based page number detection with 4-stage fallback matching.
-
+//!
+//! OCR backend: the `tesseract-ffi` feature switches [`TesseractPageDetector`] from
+//! spawning a `tesseract` CLI process per page to an in-process libtesseract engine
+//! reused per rayon worker thread. The feature is opt-in because it requires
+//! libtesseract headers at build time; without it the portable subprocess path is used.
+//!
+//! `detect_single` recognizes per-word candidates (real bounding boxes + confidences,
+//! via `GetTSVText`/the `tsv` CLI output) and hands them to
+//! [`find_page_number_with_fallback`] rather than trusting a single whole-region guess.
+//!
+//! When [`PageNumberOptions::auto_rotate`] is set, `detect_single` first runs
+//! Tesseract OSD (`--psm 0`) and rotates the page upright before searching, so a
+//! page number search region computed from `search_region_percent` still lands on
+//! the right edge of the image for pages fed in rotated 90/180/270 degrees.
+//!
+//! Labeled page numbers ("Page 12", "- 12 -", "12 / 340") are recognized by joining
+//! each OCR line's words and matching it against [`PageNumberOptions::label_patterns`]
+//! (see [`super::types::extract_label_number`]), producing an extra candidate
+//! alongside the individual word candidates.
+//!
+//! Stage 2 similarity matching scores candidates with [`ocr_aware_similarity`], a
+//! Levenshtein distance that charges a reduced substitution cost for commonly-confused
+//! OCR character pairs (`0`/`O`, `1`/`l`/`I`, ...) instead of the usual flat cost, so a
+//! misread digit doesn't sink an otherwise correct match. The confusion table is
+//! configurable per [`PageNumberOptions::confusion_pairs`]. Acceptance is gated by
+//! [`allowed_edit_cost`], a length-adaptive typo budget ([`DEFAULT_LENGTH_TYPO_BUDGET`])
+//! rather than a single flat threshold, since one wrong digit is fatal for a 1-digit
+//! page number but tolerable for a 4-digit one; [`FallbackMatchStats::relaxed_budget_matches`]
+//! tracks how many matches relied on the relaxed end of that budget.
+//!
+//! `analyze_batch` runs two correction passes over the detected numbers before
+//! computing missing/duplicate pages: first `correct_via_arithmetic_progression`
+//! fixes isolated low-confidence misreads, then [`sequence::analyze_sequence`] fits
+//! one or two robust line segments across the whole batch (RANSAC + least squares)
+//! and overwrites any remaining outlier, populating
+//! [`PageNumberAnalysis::interpolated_pages`] with which pages were corrected this way.
+//!
+//! The fallback matcher is expressed in terms of [`PageLabel`] rather than a raw
+//! `u32`, so front matter numbered in Roman numerals ("xii") or decorated styles
+//! ("A-12") matches against the scheme it was actually printed in instead of being
+//! forced through Arabic-numeral comparison. [`find_page_numbers_batch`] still takes
+//! a plain Arabic start number (its callers only ever enumerate Arabic sequences)
+//! and wraps each expected number in [`PageLabel::arabic`] internally.
+//!
+//! Each stage picks its own winner by a different yardstick (closest distance,
+//! highest similarity, ...), which makes the raw `score`/`distance` pair on a
+//! [`PageNumberMatch`] meaningless to compare across stages or pages.
+//! [`composite_score`] fixes that with a single fzf-style score - a weighted blend
+//! of normalized geometric distance, OCR confidence, confusion-weighted similarity,
+//! and a bonus for sitting in the batch's dominant [`PageNumberPosition`] - that
+//! every stage now uses internally to pick its winner and that's also recorded on
+//! the match via [`PageNumberMatch::composite_score`].
+//! [`find_page_numbers_batch_ranked`] exposes this fully: it learns the dominant
+//! position across a first pass, then returns every in-region candidate per page
+//! sorted by composite score (not just the winner), so callers can apply their own
+//! cutoff or offer alternatives instead of trusting the 4-stage winner blindly.
+
+use super::sequence;
 use super::types::{
-    DetectedPageNumber, MatchStage, OffsetCorrection, PageNumberAnalysis, PageNumberCandidate,
-    PageNumberError, PageNumberMatch, PageNumberOptions, PageNumberPosition, PageNumberRect,
-    Rectangle, Result,
+    extract_label_number, ConfusionPair, DetectedPageNumber, MatchStage, OffsetCorrection,
+    PageLabel, PageLabelStyle, PageNumberAnalysis, PageNumberCandidate, PageNumberError,
+    PageNumberMatch, PageNumberOptions, PageNumberPosition, PageNumberRect, Rectangle, Result,
+    DEFAULT_CONFUSION_PAIRS,
 };
 use image::GenericImageView;
 use rayon::prelude::*;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+// ============================================================
+// In-Process Tesseract Engine (feature = "tesseract-ffi")
+// ============================================================
+
+/// Thread-local libtesseract engine, one per rayon worker.
+///
+/// `tesseract::Tesseract` holds a raw `TessBaseAPI*` and is not `Sync`, so rather than
+/// sharing a single instance behind a mutex (which would serialize `analyze_batch`) we
+/// keep one engine per worker thread and reuse it across pages handled by that thread.
+#[cfg(feature = "tesseract-ffi")]
+mod tesseract_engine {
+    use std::cell::RefCell;
+
+    pub struct TesseractEngine {
+        inner: tesseract::Tesseract,
+    }
+
+    impl TesseractEngine {
+        fn new() -> Self {
+            let inner = tesseract::Tesseract::new(None, Some("eng"))
+                .expect("failed to initialize libtesseract")
+                .set_variable("tessedit_char_whitelist", "0123456789")
+                .expect("failed to configure libtesseract")
+                .set_page_seg_mode(tesseract::PageSegMode::PsmSingleLine);
+            Self { inner }
+        }
+
+        /// Recognize digits in `img`, returning the raw recognized text and the mean
+        /// word confidence (0-100) reported by libtesseract.
+        ///
+        /// `set_image_from_mem` consumes the underlying `Tesseract` handle and returns
+        /// a new one (the crate's binding models each TessBaseAPI call as a builder
+        /// step), so this takes `self` by value and hands back the updated engine
+        /// alongside the result for `with_engine` to store.
+        fn recognize_digits(
+            self,
+            img: &image::DynamicImage,
+        ) -> (Self, Result<(String, f32), super::PageNumberError>) {
+            let mut buf = std::io::Cursor::new(Vec::new());
+            if let Err(_e) = img.write_to(&mut buf, image::ImageFormat::Png) {
+                return (
+                    self,
+                    Err(super::PageNumberError::OcrFailed("image encode failed".into())),
+                );
+            }
+
+            let inner = match self.inner.set_image_from_mem(buf.get_ref()) {
+                Ok(inner) => inner,
+                Err(_) => {
+                    return (
+                        self,
+                        Err(super::PageNumberError::OcrFailed(
+                            "tesseract set_image failed".into(),
+                        )),
+                    )
+                }
+            };
+
+            match inner.get_text() {
+                Ok(text) => {
+                    let confidence = inner.mean_text_conf() as f32;
+                    (
+                        Self { inner },
+                        Ok((text.trim().to_string(), confidence)),
+                    )
+                }
+                Err(_) => (
+                    Self { inner },
+                    Err(super::PageNumberError::OcrFailed(
+                        "tesseract recognition failed".into(),
+                    )),
+                ),
+            }
+        }
+
+        /// Recognize `img` and return libtesseract's TSV dump (one row per word,
+        /// with per-word bounding boxes and confidences) for [`super::parse_tesseract_tsv`].
+        fn recognize_words_tsv(
+            self,
+            img: &image::DynamicImage,
+        ) -> (Self, Result<String, super::PageNumberError>) {
+            let mut buf = std::io::Cursor::new(Vec::new());
+            if let Err(_e) = img.write_to(&mut buf, image::ImageFormat::Png) {
+                return (
+                    self,
+                    Err(super::PageNumberError::OcrFailed("image encode failed".into())),
+                );
+            }
+
+            let inner = match self.inner.set_image_from_mem(buf.get_ref()) {
+                Ok(inner) => inner,
+                Err(_) => {
+                    return (
+                        self,
+                        Err(super::PageNumberError::OcrFailed(
+                            "tesseract set_image failed".into(),
+                        )),
+                    )
+                }
+            };
+
+            match inner.get_tsv_text(0) {
+                Ok(tsv) => (Self { inner }, Ok(tsv)),
+                Err(_) => (
+                    Self { inner },
+                    Err(super::PageNumberError::OcrFailed(
+                        "tesseract tsv recognition failed".into(),
+                    )),
+                ),
+            }
+        }
+    }
+
+    thread_local! {
+        static ENGINE: RefCell<Option<TesseractEngine>> = const { RefCell::new(None) };
+    }
+
+    /// Recognize digits via the thread-local engine, threading the rebuilt handle back in.
+    pub fn recognize_digits_on_thread(
+        img: &image::DynamicImage,
+    ) -> Result<(String, f32), super::PageNumberError> {
+        ENGINE.with(|cell| {
+            let mut slot = cell.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(TesseractEngine::new());
+            }
+            let engine = slot.take().expect("engine just initialized");
+            let (engine, result) = engine.recognize_digits(img);
+            *slot = Some(engine);
+            result
+        })
+    }
+
+    /// Recognize per-word candidates via the thread-local engine's TSV output.
+    ///
+    /// Reuses `TessBaseAPI::GetTSVText` rather than a bespoke word-box API so both
+    /// the FFI and subprocess backends share one TSV parser ([`super::parse_tesseract_tsv`]).
+    pub fn recognize_words_on_thread(
+        img: &image::DynamicImage,
+        region_y_offset: u32,
+        label_patterns: &[String],
+    ) -> Vec<super::PageNumberCandidate> {
+        ENGINE.with(|cell| {
+            let mut slot = cell.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(TesseractEngine::new());
+            }
+            let engine = slot.take().expect("engine just initialized");
+            let (engine, result) = engine.recognize_words_tsv(img);
+            *slot = Some(engine);
+            match result {
+                Ok(tsv) => super::parse_tesseract_tsv(&tsv, region_y_offset, label_patterns),
+                Err(_) => Vec::new(),
+            }
+        })
+    }
+}
+
 // ============================================================
 // Fallback Matching Constants (Phase 2.1)
 // ============================================================
 
-/// Minimum Jaro-Winkler similarity for Stage 2 matching
+/// Minimum confusion-weighted similarity for Stage 2 matching
 pub const MIN_SIMILARITY_THRESHOLD: f64 = 0.7;
 
+/// Length-adaptive typo budget for Stage 2 acceptance: `(max_len, max_edit_cost)`
+/// pairs, checked in order against the rendered expected label's character count -
+/// the first entry whose `max_len` covers it wins. A single wrong digit is fatal
+/// for a 1-digit page number but tolerable for a 4+ digit one, so longer labels get
+/// a larger allowed edit-cost budget (see [`allowed_edit_cost`]).
+pub const DEFAULT_LENGTH_TYPO_BUDGET: &[(usize, f64)] = &[(1, 0.0), (3, 1.0), (usize::MAX, 2.0)];
+
+/// Weight applied to normalized geometric distance in [`composite_score`]; closer
+/// candidates score higher.
+pub const COMPOSITE_WEIGHT_DISTANCE: f64 = 0.35;
+
+/// Weight applied to OCR confidence in [`composite_score`].
+pub const COMPOSITE_WEIGHT_CONFIDENCE: f64 = 0.25;
+
+/// Weight applied to confusion-weighted text similarity in [`composite_score`].
+pub const COMPOSITE_WEIGHT_SIMILARITY: f64 = 0.3;
+
+/// Weight applied to the dominant-position bonus in [`composite_score`] (see
+/// [`estimate_dominant_position`]).
+pub const COMPOSITE_WEIGHT_POSITION: f64 = 0.1;
+
 /// Margin percentage for expanding search region (3% as per spec)
 pub const SEARCH_REGION_MARGIN_PERCENT: f32 = 3.0;
 
@@ -26,196 +263,483 @@ pub const SEARCH_REGION_MARGIN_PERCENT: f32 = 3.0;
 #[allow(dead_code)]
 pub const DEFAULT_REFERENCE_Y_RATIO: f32 = 0.95;
 
+/// Substitution cost for replacing `a` with `b`, per `confusion_pairs` (checked in
+/// either direction), defaulting to the standard Levenshtein cost of `1.0`.
+fn confusion_cost(confusion_pairs: &[ConfusionPair], a: char, b: char) -> f64 {
+    confusion_pairs
+        .iter()
+        .find(|&&(x, y, _)| (x == a && y == b) || (x == b && y == a))
+        .map_or(1.0, |&(_, _, cost)| cost)
+}
+
+/// Confusion-weighted Levenshtein edit cost between `expected` and `candidate`.
+///
+/// Instead of a flat substitution cost of `1.0`, looks up each substituted
+/// character pair in `confusion_pairs` and charges the reduced cost there
+/// (insertions/deletions stay at `1.0`), so e.g. OCR reading "l23" for "123" costs
+/// much less than an unrelated substitution would. See [`ocr_aware_similarity`] for
+/// the normalized `[0, 1]` score derived from this, and [`allowed_edit_cost`] for
+/// the length-adaptive acceptance budget Stage 2 checks this cost against.
+fn ocr_aware_edit_cost(expected: &str, candidate: &str, confusion_pairs: &[ConfusionPair]) -> f64 {
+    let a: Vec<char> = expected.chars().collect();
+    let b: Vec<char> = candidate.chars().collect();
+    let (len_a, len_b) = (a.len(), b.len());
+
+    if len_a == 0 && len_b == 0 {
+        return 0.0;
+    }
+
+    let mut dp = vec![vec![0.0f64; len_b + 1]; len_a + 1];
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i as f64;
+    }
+    for j in 0..=len_b {
+        dp[0][j] = j as f64;
+    }
+
+    for i in 1..=len_a {
+        for j in 1..=len_b {
+            let sub_cost = if a[i - 1] == b[j - 1] {
+                0.0
+            } else {
+                confusion_cost(confusion_pairs, a[i - 1], b[j - 1])
+            };
+            dp[i][j] = (dp[i - 1][j] + 1.0)
+                .min(dp[i][j - 1] + 1.0)
+                .min(dp[i - 1][j - 1] + sub_cost);
+        }
+    }
+
+    dp[len_a][len_b]
+}
+
+/// Confusion-weighted string similarity for Stage 2 matching, normalized to `[0,
+/// 1]` via `1 - cost / max(len_a, len_b)` from [`ocr_aware_edit_cost`].
+fn ocr_aware_similarity(expected: &str, candidate: &str, confusion_pairs: &[ConfusionPair]) -> f64 {
+    let max_len = expected.chars().count().max(candidate.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    let cost = ocr_aware_edit_cost(expected, candidate, confusion_pairs);
+    (1.0 - cost / max_len as f64).max(0.0)
+}
+
+/// Maximum total edit cost tolerated for a Stage 2 match against an expected label
+/// rendered as `len` characters, per [`DEFAULT_LENGTH_TYPO_BUDGET`]. Falls back to
+/// the table's last (largest) budget if `len` somehow exceeds every entry.
+fn allowed_edit_cost(len: usize) -> f64 {
+    DEFAULT_LENGTH_TYPO_BUDGET
+        .iter()
+        .find(|&&(max_len, _)| len <= max_len)
+        .map_or(2.0, |&(_, budget)| budget)
+}
+
+/// fzf-style composite ranking score for a single candidate: a weighted blend of
+/// normalized geometric distance, OCR confidence, confusion-weighted text
+/// similarity, and a bonus for sitting in the batch's dominant
+/// [`PageNumberPosition`] (see [`estimate_dominant_position`]). Each component is
+/// normalized to `[0.0, 1.0]` before weighting, so the result is directly
+/// comparable across stages and pages - unlike the stage-specific `score`/`distance`
+/// pair, which only means something within a single stage.
+fn composite_score(
+    candidate: &PageNumberCandidate,
+    expected_str: &str,
+    distance: f64,
+    confusion_pairs: &[ConfusionPair],
+    region: &Rectangle,
+    dominant_position: Option<PageNumberPosition>,
+) -> f64 {
+    let normalized_distance = (1.0 - distance / 1000.0).clamp(0.0, 1.0);
+    let similarity = ocr_aware_similarity(expected_str, candidate.text.trim(), confusion_pairs);
+    let positional_bonus = match dominant_position {
+        Some(position) if matches_dominant_position(candidate, region, position) => 1.0,
+        _ => 0.0,
+    };
+
+    COMPOSITE_WEIGHT_DISTANCE * normalized_distance
+        + COMPOSITE_WEIGHT_CONFIDENCE * f64::from(candidate.confidence)
+        + COMPOSITE_WEIGHT_SIMILARITY * similarity
+        + COMPOSITE_WEIGHT_POSITION * positional_bonus
+}
+
+/// Whether `candidate` sits in the half of `region` (top vs. bottom) that
+/// `position` refers to. Only the top/bottom axis is checked -
+/// [`PageNumberPosition`]'s outside/inside/center variants aren't distinguishable
+/// from a single bounding box without knowing the page's odd/even-side convention,
+/// so e.g. `BottomOutside` and `BottomInside` both collapse to "bottom" here.
+fn matches_dominant_position(
+    candidate: &PageNumberCandidate,
+    region: &Rectangle,
+    position: PageNumberPosition,
+) -> bool {
+    let (_, cy) = candidate.bbox.center();
+    let midpoint = region.y + region.height as i32 / 2;
+    let in_top_half = cy < midpoint;
+    let position_is_top = matches!(
+        position,
+        PageNumberPosition::TopCenter | PageNumberPosition::TopOutside
+    );
+    in_top_half == position_is_top
+}
+
+/// Learn which half (top vs. bottom) of each page's search region its winning
+/// match actually sits in, across a whole batch, by majority vote. A single page's
+/// winner is too small a sample to trust as a positional hint, but the pattern
+/// across a scanned book's pages usually isn't - page numbers don't typically
+/// alternate between header and footer page-to-page. Returns `None` if no page in
+/// the batch matched at all.
+fn estimate_dominant_position(
+    matches: &[Option<PageNumberMatch>],
+    regions: &[Rectangle],
+) -> Option<PageNumberPosition> {
+    let mut top_votes = 0usize;
+    let mut bottom_votes = 0usize;
+
+    for (m, region) in matches.iter().zip(regions.iter()) {
+        let Some(m) = m else { continue };
+        let (_, cy) = m.candidate.bbox.center();
+        let midpoint = region.y + region.height as i32 / 2;
+        if cy < midpoint {
+            top_votes += 1;
+        } else {
+            bottom_votes += 1;
+        }
+    }
+
+    if top_votes == 0 && bottom_votes == 0 {
+        None
+    } else if top_votes >= bottom_votes {
+        Some(PageNumberPosition::TopCenter)
+    } else {
+        Some(PageNumberPosition::BottomCenter)
+    }
+}
+
 // ============================================================
 // 4-Stage Fallback Matching (Phase 2.1)
 // ============================================================
 
-/// Find page number with 4-stage fallback matching
+/// Find page number with 4-stage fallback matching, using [`DEFAULT_CONFUSION_PAIRS`]
+/// for Stage 2's confusion-weighted similarity. See
+/// [`find_page_number_with_fallback_with_confusion`] to supply a custom confusion table
+/// (e.g. [`PageNumberOptions::confusion_pairs`]).
 ///
 /// # Stages
 /// 1. **ExactMatch**: Exact number match + within region + minimum distance
-/// 2. **SimilarityMatch**: Maximum similarity (Jaro-Winkler) + within region
+/// 2. **SimilarityMatch**: Within the length-adaptive typo budget + within region, best composite score
 /// 3. **OcrSuccessMatch**: OCR success region + minimum distance
 /// 4. **FallbackMatch**: All detected regions + minimum distance
 ///
 /// # Arguments
 /// * `candidates` - OCR detection candidates
-/// * `expected_number` - The page number we're looking for
+/// * `expected` - The page label we're looking for (Arabic, Roman, or decorated)
 /// * `search_region` - The region to prioritize (with 3% margin expansion)
 ///
 /// # Returns
 /// The best matching candidate, or None if no candidates available
 pub fn find_page_number_with_fallback(
     candidates: &[PageNumberCandidate],
-    expected_number: u32,
+    expected: &PageLabel,
     search_region: &Rectangle,
+) -> Option<PageNumberMatch> {
+    find_page_number_with_fallback_with_confusion(
+        candidates,
+        expected,
+        search_region,
+        DEFAULT_CONFUSION_PAIRS,
+    )
+}
+
+/// Same as [`find_page_number_with_fallback`], but with a caller-supplied confusion
+/// table for Stage 2's similarity scoring (see [`ocr_aware_similarity`]).
+pub fn find_page_number_with_fallback_with_confusion(
+    candidates: &[PageNumberCandidate],
+    expected: &PageLabel,
+    search_region: &Rectangle,
+    confusion_pairs: &[ConfusionPair],
+) -> Option<PageNumberMatch> {
+    find_page_number_with_fallback_ranked(candidates, expected, search_region, confusion_pairs, None)
+}
+
+/// Same as [`find_page_number_with_fallback_with_confusion`], but additionally
+/// taking a `dominant_position` hint (see [`estimate_dominant_position`]) for the
+/// composite score's positional bonus. Single-page callers that don't know the
+/// batch's dominant position (e.g. [`TesseractPageDetector::detect_single`]) should
+/// pass `None`; [`find_page_numbers_batch_ranked`] learns it across a first pass.
+pub fn find_page_number_with_fallback_ranked(
+    candidates: &[PageNumberCandidate],
+    expected: &PageLabel,
+    search_region: &Rectangle,
+    confusion_pairs: &[ConfusionPair],
+    dominant_position: Option<PageNumberPosition>,
 ) -> Option<PageNumberMatch> {
     if candidates.is_empty() {
         return None;
     }
 
-    let expected_str = expected_number.to_string();
+    let expected_str = expected.render();
     let (ref_x, ref_y) = search_region.center();
 
     // Expand search region by 3% margin
     let expanded_region = search_region.expand(SEARCH_REGION_MARGIN_PERCENT);
 
     // Stage 1: Exact match + within region + minimum distance
-    if let Some(m) = stage1_exact_match(candidates, expected_number, &expanded_region, ref_x, ref_y)
-    {
+    if let Some(m) = stage1_exact_match(
+        candidates,
+        expected,
+        &expected_str,
+        &expanded_region,
+        ref_x,
+        ref_y,
+        confusion_pairs,
+        dominant_position,
+    ) {
         return Some(m);
     }
 
-    // Stage 2: Maximum similarity (Jaro-Winkler) + within region
-    if let Some(m) =
-        stage2_similarity_match(candidates, &expected_str, &expanded_region, ref_x, ref_y)
-    {
+    // Stage 2: Within the length-adaptive typo budget + within region, best composite score
+    if let Some(m) = stage2_similarity_match(
+        candidates,
+        expected,
+        &expected_str,
+        &expanded_region,
+        ref_x,
+        ref_y,
+        confusion_pairs,
+        dominant_position,
+    ) {
         return Some(m);
     }
 
     // Stage 3: OCR success region + minimum distance
-    if let Some(m) = stage3_ocr_success_match(candidates, expected_number, ref_x, ref_y) {
+    if let Some(m) = stage3_ocr_success_match(
+        candidates,
+        expected,
+        &expected_str,
+        &expanded_region,
+        ref_x,
+        ref_y,
+        confusion_pairs,
+        dominant_position,
+    ) {
         return Some(m);
     }
 
     // Stage 4: All detected regions + minimum distance (fallback)
-    stage4_fallback_match(candidates, expected_number, ref_x, ref_y)
+    stage4_fallback_match(
+        candidates,
+        expected,
+        &expected_str,
+        &expanded_region,
+        ref_x,
+        ref_y,
+        confusion_pairs,
+        dominant_position,
+    )
 }
 
-/// Stage 1: Exact match + within region + minimum distance
+/// Stage 1: Exact match + within region, ranked by [`composite_score`]
 fn stage1_exact_match(
     candidates: &[PageNumberCandidate],
-    expected_number: u32,
+    expected: &PageLabel,
+    expected_str: &str,
     region: &Rectangle,
     ref_x: i32,
     ref_y: i32,
+    confusion_pairs: &[ConfusionPair],
+    dominant_position: Option<PageNumberPosition>,
 ) -> Option<PageNumberMatch> {
-    let mut best: Option<(PageNumberCandidate, f64)> = None;
+    let mut best: Option<(PageNumberCandidate, f64, f64)> = None; // (candidate, distance, composite)
 
     for candidate in candidates {
-        // Check for exact number match
-        if candidate.number == Some(expected_number) {
+        // Check for exact label match (same scheme and ordinal, not just digits)
+        if candidate.label.as_ref() == Some(expected) {
             let (cx, cy) = candidate.bbox.center();
             // Check if within expanded region
             if region.contains(cx, cy) {
                 let distance = candidate.distance_to(ref_x, ref_y);
-                if best.as_ref().is_none_or(|(_, d)| distance < *d) {
-                    best = Some((candidate.clone(), distance));
+                let composite = composite_score(
+                    candidate,
+                    expected_str,
+                    distance,
+                    confusion_pairs,
+                    region,
+                    dominant_position,
+                );
+                if best.as_ref().is_none_or(|(_, _, c)| composite > *c) {
+                    best = Some((candidate.clone(), distance, composite));
                 }
             }
         }
     }
 
-    best.map(|(candidate, distance)| {
+    best.map(|(candidate, distance, composite)| {
         PageNumberMatch::new(
             candidate,
             MatchStage::ExactMatch,
             1.0, // Perfect score for exact match
             distance,
-            expected_number,
+            expected.clone(),
         )
+        .with_composite_score(composite)
     })
 }
 
-/// Stage 2: Maximum similarity (Jaro-Winkler) + within region
+/// Stage 2: Confusion-weighted edit cost within the length-adaptive typo
+/// budget, within region, ranked by [`composite_score`]
+///
+/// When `expected` is in a non-Arabic scheme (Roman-numeral front matter, an
+/// alpha-decorated appendix), a candidate whose own text parses as a [`PageLabel`]
+/// in a *different* scheme is excluded even if the rendered text happens to be
+/// textually close, since that's a different numbering scheme rather than a misread
+/// of this one. Arabic-expected pages skip this filter: a single confusable letter
+/// (e.g. "l" for "1") can itself parse as a spurious alpha-decorated label, and
+/// Arabic is common enough that scheme confusion there is far more likely to be an
+/// OCR misread than a deliberate renumbering. Candidates that didn't parse as a
+/// label at all (garbled OCR) are always compared - the garbled text may still
+/// fuzzy-match the expected rendering.
 fn stage2_similarity_match(
     candidates: &[PageNumberCandidate],
+    expected: &PageLabel,
     expected_str: &str,
     region: &Rectangle,
     ref_x: i32,
     ref_y: i32,
+    confusion_pairs: &[ConfusionPair],
+    dominant_position: Option<PageNumberPosition>,
 ) -> Option<PageNumberMatch> {
-    use strsim::jaro_winkler;
-
-    let mut best: Option<(PageNumberCandidate, f64, f64)> = None; // (candidate, similarity, distance)
+    let mut best: Option<(PageNumberCandidate, f64, f64, bool, f64)> = None; // (candidate, similarity, distance, relaxed_budget, composite)
+    let budget = allowed_edit_cost(expected_str.chars().count());
 
     for candidate in candidates {
+        if expected.style != PageLabelStyle::Arabic
+            && candidate
+                .label
+                .as_ref()
+                .is_some_and(|label| label.style != expected.style)
+        {
+            continue;
+        }
+
         let (cx, cy) = candidate.bbox.center();
         // Check if within expanded region
         if region.contains(cx, cy) && !candidate.text.trim().is_empty() {
-            let similarity = jaro_winkler(expected_str, candidate.text.trim());
-            if similarity >= MIN_SIMILARITY_THRESHOLD {
+            let text = candidate.text.trim();
+            let cost = ocr_aware_edit_cost(expected_str, text, confusion_pairs);
+            if cost <= budget {
+                let similarity = ocr_aware_similarity(expected_str, text, confusion_pairs);
+                let relaxed_budget = similarity < MIN_SIMILARITY_THRESHOLD;
                 let distance = candidate.distance_to(ref_x, ref_y);
-                // Prefer higher similarity, then closer distance
-                let is_better = match &best {
-                    None => true,
-                    Some((_, best_sim, best_dist)) => {
-                        similarity > *best_sim
-                            || (similarity == *best_sim && distance < *best_dist)
-                    }
-                };
+                let composite = composite_score(
+                    candidate,
+                    expected_str,
+                    distance,
+                    confusion_pairs,
+                    region,
+                    dominant_position,
+                );
+                // Prefer the higher composite score
+                let is_better = best.as_ref().is_none_or(|(_, _, _, _, c)| composite > *c);
                 if is_better {
-                    best = Some((candidate.clone(), similarity, distance));
+                    best = Some((candidate.clone(), similarity, distance, relaxed_budget, composite));
                 }
             }
         }
     }
 
-    best.map(|(candidate, similarity, distance)| {
+    best.map(|(candidate, similarity, distance, relaxed_budget, composite)| {
         PageNumberMatch::new(
             candidate,
             MatchStage::SimilarityMatch,
             similarity,
             distance,
-            expected_str.parse().unwrap_or(0),
+            expected.clone(),
         )
+        .with_relaxed_budget(relaxed_budget)
+        .with_composite_score(composite)
     })
 }
 
-/// Stage 3: OCR success region + minimum distance
+/// Stage 3: OCR success region, ranked by [`composite_score`]
 fn stage3_ocr_success_match(
     candidates: &[PageNumberCandidate],
-    expected_number: u32,
+    expected: &PageLabel,
+    expected_str: &str,
+    region: &Rectangle,
     ref_x: i32,
     ref_y: i32,
+    confusion_pairs: &[ConfusionPair],
+    dominant_position: Option<PageNumberPosition>,
 ) -> Option<PageNumberMatch> {
-    let mut best: Option<(PageNumberCandidate, f64, f32)> = None; // (candidate, distance, confidence)
+    let mut best: Option<(PageNumberCandidate, f64, f32, f64)> = None; // (candidate, distance, confidence, composite)
 
     for candidate in candidates {
         // Only consider OCR success candidates (text was successfully detected)
         if candidate.ocr_success {
             let distance = candidate.distance_to(ref_x, ref_y);
-            if best.as_ref().is_none_or(|(_, d, _)| distance < *d) {
-                best = Some((candidate.clone(), distance, candidate.confidence));
+            let composite = composite_score(
+                candidate,
+                expected_str,
+                distance,
+                confusion_pairs,
+                region,
+                dominant_position,
+            );
+            if best.as_ref().is_none_or(|(_, _, _, c)| composite > *c) {
+                best = Some((candidate.clone(), distance, candidate.confidence, composite));
             }
         }
     }
 
-    best.map(|(candidate, distance, confidence)| {
+    best.map(|(candidate, distance, confidence, composite)| {
         PageNumberMatch::new(
             candidate,
             MatchStage::OcrSuccessMatch,
             confidence as f64,
             distance,
-            expected_number,
+            expected.clone(),
         )
+        .with_composite_score(composite)
     })
 }
 
-/// Stage 4: All detected regions + minimum distance (fallback)
+/// Stage 4: All detected regions (fallback), ranked by [`composite_score`]
 fn stage4_fallback_match(
     candidates: &[PageNumberCandidate],
-    expected_number: u32,
+    expected: &PageLabel,
+    expected_str: &str,
+    region: &Rectangle,
     ref_x: i32,
     ref_y: i32,
+    confusion_pairs: &[ConfusionPair],
+    dominant_position: Option<PageNumberPosition>,
 ) -> Option<PageNumberMatch> {
-    let mut best: Option<(PageNumberCandidate, f64)> = None;
+    let mut best: Option<(PageNumberCandidate, f64, f64)> = None; // (candidate, distance, composite)
 
     for candidate in candidates {
         let distance = candidate.distance_to(ref_x, ref_y);
-        if best.as_ref().is_none_or(|(_, d)| distance < *d) {
-            best = Some((candidate.clone(), distance));
+        let composite = composite_score(
+            candidate,
+            expected_str,
+            distance,
+            confusion_pairs,
+            region,
+            dominant_position,
+        );
+        if best.as_ref().is_none_or(|(_, _, c)| composite > *c) {
+            best = Some((candidate.clone(), distance, composite));
         }
     }
 
-    best.map(|(candidate, distance)| {
+    best.map(|(candidate, distance, composite)| {
         PageNumberMatch::new(
             candidate,
             MatchStage::FallbackMatch,
             0.0, // No score for fallback
             distance,
-            expected_number,
+            expected.clone(),
         )
+        .with_composite_score(composite)
     })
 }
 
@@ -229,12 +753,97 @@ pub fn find_page_numbers_batch(
         .par_iter()
         .enumerate()
         .map(|(i, candidates)| {
-            let expected_number = start_page_number + i as u32;
+            let expected = PageLabel::arabic(start_page_number + i as u32);
             let region = search_regions.get(i).cloned().unwrap_or_else(|| {
                 // Default search region if not specified
                 Rectangle::new(0, 0, 1000, 100)
             });
-            find_page_number_with_fallback(candidates, expected_number, &region)
+            find_page_number_with_fallback(candidates, &expected, &region)
+        })
+        .collect()
+}
+
+/// One page's result from [`find_page_numbers_batch_ranked`]: the winning match (as
+/// [`find_page_numbers_batch`] would find, but re-ranked with the batch's dominant
+/// position) plus every in-region candidate sorted by [`composite_score`]
+/// descending, so a caller can apply its own cutoff or present runner-up candidates
+/// instead of trusting the 4-stage winner blindly.
+#[derive(Debug, Clone, Default)]
+pub struct RankedPageMatch {
+    pub best: Option<PageNumberMatch>,
+    /// `(candidate, composite_score)` pairs within the expanded search region,
+    /// sorted by composite score descending
+    pub ranked_candidates: Vec<(PageNumberCandidate, f64)>,
+}
+
+/// Like [`find_page_numbers_batch`], but additionally ranks every in-region
+/// candidate on each page by [`composite_score`] and folds in a batch-wide dominant
+/// [`PageNumberPosition`] learned from a first pass (see
+/// [`estimate_dominant_position`]) as that score's positional bonus. The dominant
+/// position can't be known before any page has a winner, so this runs two passes:
+/// the first with no positional bonus to find provisional winners, the second
+/// re-ranking (and re-selecting) using the position learned from those winners.
+pub fn find_page_numbers_batch_ranked(
+    page_candidates: &[Vec<PageNumberCandidate>],
+    start_page_number: u32,
+    search_regions: &[Rectangle],
+) -> Vec<RankedPageMatch> {
+    let regions: Vec<Rectangle> = (0..page_candidates.len())
+        .map(|i| {
+            search_regions
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| Rectangle::new(0, 0, 1000, 100))
+        })
+        .collect();
+
+    let first_pass = find_page_numbers_batch(page_candidates, start_page_number, search_regions);
+    let dominant_position = estimate_dominant_position(&first_pass, &regions);
+
+    page_candidates
+        .par_iter()
+        .zip(regions.par_iter())
+        .enumerate()
+        .map(|(i, (candidates, region))| {
+            let expected = PageLabel::arabic(start_page_number + i as u32);
+            let expected_str = expected.render();
+            let expanded_region = region.expand(SEARCH_REGION_MARGIN_PERCENT);
+            let (ref_x, ref_y) = region.center();
+
+            let best = find_page_number_with_fallback_ranked(
+                candidates,
+                &expected,
+                region,
+                DEFAULT_CONFUSION_PAIRS,
+                dominant_position,
+            );
+
+            let mut ranked_candidates: Vec<(PageNumberCandidate, f64)> = candidates
+                .iter()
+                .filter(|candidate| {
+                    let (cx, cy) = candidate.bbox.center();
+                    expanded_region.contains(cx, cy)
+                })
+                .map(|candidate| {
+                    let distance = candidate.distance_to(ref_x, ref_y);
+                    let score = composite_score(
+                        candidate,
+                        &expected_str,
+                        distance,
+                        DEFAULT_CONFUSION_PAIRS,
+                        &expanded_region,
+                        dominant_position,
+                    );
+                    (candidate.clone(), score)
+                })
+                .collect();
+            ranked_candidates
+                .sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            RankedPageMatch {
+                best,
+                ranked_candidates,
+            }
         })
         .collect()
 }
@@ -248,6 +857,11 @@ pub struct FallbackMatchStats {
     pub stage3_ocr_success: usize,
     pub stage4_fallback: usize,
     pub not_found: usize,
+    /// Stage 2 matches that only passed because of the length-adaptive typo budget
+    /// (see [`DEFAULT_LENGTH_TYPO_BUDGET`]) rather than the stricter legacy
+    /// [`MIN_SIMILARITY_THRESHOLD`], for auditing how much the relaxed budget is
+    /// actually being relied on.
+    pub relaxed_budget_matches: usize,
 }
 
 impl FallbackMatchStats {
@@ -265,6 +879,9 @@ impl FallbackMatchStats {
                 MatchStage::OcrSuccessMatch => stats.stage3_ocr_success += 1,
                 MatchStage::FallbackMatch => stats.stage4_fallback += 1,
             }
+            if m.relaxed_budget {
+                stats.relaxed_budget_matches += 1;
+            }
         }
         stats.not_found = matches.iter().filter(|m| m.is_none()).count();
 
@@ -288,6 +905,114 @@ impl FallbackMatchStats {
     }
 }
 
+/// Parse the `Rotate: <deg>` line out of `tesseract --psm 0` OSD output.
+fn parse_osd_rotation(osd_output: &str) -> Option<u32> {
+    for line in osd_output.lines() {
+        if let Some(value) = line.strip_prefix("Rotate: ") {
+            if let Ok(degrees) = value.trim().parse::<u32>() {
+                return Some(degrees % 360);
+            }
+        }
+    }
+    None
+}
+
+/// Smallest rectangle containing both `a` and `b`.
+fn union_rect(a: &Rectangle, b: &Rectangle) -> Rectangle {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let right = (a.x + a.width as i32).max(b.x + b.width as i32);
+    let bottom = (a.y + a.height as i32).max(b.y + b.height as i32);
+    Rectangle::new(x, y, (right - x) as u32, (bottom - y) as u32)
+}
+
+/// Parse `tesseract --psm 7 tsv` output into per-word candidates, plus one extra
+/// candidate per OCR line whose joined text matches a labeled-page-number pattern
+/// (see [`DEFAULT_LABEL_PATTERNS`]), so e.g. "Page 12" or "- 12 -" is recognized as
+/// a single `12` candidate spanning the whole label rather than being left as
+/// unparseable word fragments ("Page", "12").
+///
+/// The TSV format (one header row, then one row per detected element) has columns
+/// `level page_num block_num par_num line_num word_num left top width height conf text`;
+/// we only care about word-level rows (`level == 5`) with non-empty text.
+#[cfg_attr(feature = "tesseract-ffi", allow(dead_code))]
+fn parse_tesseract_tsv(
+    tsv: &str,
+    region_y_offset: u32,
+    label_patterns: &[String],
+) -> Vec<PageNumberCandidate> {
+    let mut candidates = Vec::new();
+    let mut current_line: Option<(u32, u32, Rectangle, Vec<&str>, f32, u32)> = None;
+
+    let flush_line = |line: Option<(u32, u32, Rectangle, Vec<&str>, f32, u32)>,
+                       out: &mut Vec<PageNumberCandidate>| {
+        let Some((_, _, bbox, words, conf_sum, count)) = line else {
+            return;
+        };
+        if count == 0 {
+            return;
+        }
+        let joined = words.join(" ");
+        if let Some(number) = extract_label_number(&joined, label_patterns) {
+            let confidence = (conf_sum / count as f32).clamp(0.0, 100.0) / 100.0;
+            let mut candidate = PageNumberCandidate::new(number.to_string(), bbox, confidence);
+            candidate.text = joined;
+            out.push(candidate);
+        }
+    };
+
+    for line in tsv.lines().skip(1) {
+        let cols: Vec<&str> = line.split('\t').collect();
+        if cols.len() < 12 {
+            continue;
+        }
+        if cols[0] != "5" {
+            // Not a word-level row (page/block/paragraph/line rows are skipped)
+            continue;
+        }
+
+        let text = cols[11].trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let (Ok(block_num), Ok(line_num), Ok(left), Ok(top), Ok(width), Ok(height), Ok(conf)) = (
+            cols[2].parse::<u32>(),
+            cols[4].parse::<u32>(),
+            cols[6].parse::<i32>(),
+            cols[7].parse::<i32>(),
+            cols[8].parse::<u32>(),
+            cols[9].parse::<u32>(),
+            cols[10].parse::<f32>(),
+        ) else {
+            continue;
+        };
+
+        let bbox = Rectangle::new(left, top + region_y_offset as i32, width, height);
+        // Tesseract reports -1 confidence for non-text rows; clamp into 0-100.
+        let confidence = conf.clamp(0.0, 100.0) / 100.0;
+        candidates.push(PageNumberCandidate::new(text.to_string(), bbox, confidence));
+
+        match &mut current_line {
+            Some((cur_block, cur_line, line_bbox, words, conf_sum, count))
+                if *cur_block == block_num && *cur_line == line_num =>
+            {
+                *line_bbox = union_rect(line_bbox, &bbox);
+                words.push(text);
+                *conf_sum += conf;
+                *count += 1;
+            }
+            _ => {
+                flush_line(current_line.take(), &mut candidates);
+                current_line = Some((block_num, line_num, bbox, vec![text], conf, 1));
+            }
+        }
+    }
+    flush_line(current_line.take(), &mut candidates);
+
+    candidates
+}
+
 /// Tesseract-based page number detector
 pub struct TesseractPageDetector;
 
@@ -305,6 +1030,15 @@ impl TesseractPageDetector {
         let img = image::open(image_path)
             .map_err(|_| PageNumberError::ImageNotFound(image_path.to_path_buf()))?;
 
+        // Orientation/script detection: scanned pages are sometimes fed in rotated by
+        // a multiple of 90 degrees (e.g. a landscape scan of a portrait book page),
+        // which would put the page number search region on the wrong edge entirely.
+        let img = if options.auto_rotate {
+            Self::correct_orientation(img)
+        } else {
+            img
+        };
+
         let (width, height) = img.dimensions();
 
         // Determine search region based on position hint
@@ -322,10 +1056,30 @@ impl TesseractPageDetector {
         // Crop search region
         let search_region = img.crop_imm(0, search_y, width, search_height);
 
-        // For now, use simple image analysis instead of Tesseract
-        // In a full implementation, this would call tesseract OCR
-        let (number, raw_text, confidence) =
-            Self::analyze_region_for_numbers(&search_region, options);
+        // Run word-level OCR and let the 4-stage fallback matcher pick the best
+        // candidate, rather than trusting a single whole-region recognition result.
+        let candidates = Self::extract_word_candidates(&search_region, search_y, options);
+        let expected_label = PageLabel::arabic((page_index as u32) + 1);
+        let search_rect = Rectangle::new(0, search_y as i32, width, search_height);
+
+        let (number, raw_text, confidence) = match find_page_number_with_fallback_with_confusion(
+            &candidates,
+            &expected_label,
+            &search_rect,
+            &options.confusion_pairs,
+        ) {
+            Some(m) => (
+                m.candidate.number.map(|n| n as i32),
+                m.candidate.text.clone(),
+                m.candidate.confidence * 100.0,
+            ),
+            None => {
+                // No word-level candidates at all (e.g. blank region, OCR unavailable):
+                // fall back to the whole-region heuristic used before word-level
+                // extraction existed.
+                Self::analyze_region_for_numbers(&search_region, options)
+            }
+        };
 
         Ok(DetectedPageNumber {
             page_index,
@@ -342,13 +1096,158 @@ impl TesseractPageDetector {
             },
             confidence: confidence / 100.0,
             raw_text,
+            label: None,
         })
     }
 
+    /// Detect page orientation via Tesseract OSD (`--psm 0`) and rotate `img` upright.
+    ///
+    /// Only corrects multiples of 90 degrees (the angles OSD reports); skew within a
+    /// quadrant is out of scope here and is handled upstream by the deskew step.
+    fn correct_orientation(img: image::DynamicImage) -> image::DynamicImage {
+        match Self::detect_osd_rotation(&img) {
+            Some(90) => img.rotate90(),
+            Some(180) => img.rotate180(),
+            Some(270) => img.rotate270(),
+            _ => img,
+        }
+    }
+
+    /// Run Tesseract OSD and return the clockwise rotation (0/90/180/270) needed to
+    /// make the page upright, or `None` if OSD could not determine an orientation.
+    fn detect_osd_rotation(img: &image::DynamicImage) -> Option<u32> {
+        let temp_dir = std::env::temp_dir();
+        let temp_path = temp_dir.join(format!("page_num_osd_{}.png", std::process::id()));
+
+        if img.save(&temp_path).is_err() {
+            return None;
+        }
+
+        let output = std::process::Command::new("tesseract")
+            .arg(&temp_path)
+            .arg("stdout")
+            .arg("--psm")
+            .arg("0")
+            .output();
+
+        let _ = std::fs::remove_file(&temp_path);
+
+        let result = output.ok()?;
+        if !result.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        parse_osd_rotation(&stdout)
+    }
+
+    /// Run word-level OCR over `region` and return one [`PageNumberCandidate`] per
+    /// recognized word, with real per-word bounding boxes and confidences so the
+    /// 4-stage fallback matcher has more than a single whole-region guess to choose
+    /// from. `region_y_offset` is added to word `y` coordinates to translate them
+    /// back into full-page coordinates (the caller already cropped the region).
+    fn extract_word_candidates(
+        region: &image::DynamicImage,
+        region_y_offset: u32,
+        options: &PageNumberOptions,
+    ) -> Vec<PageNumberCandidate> {
+        #[cfg(feature = "tesseract-ffi")]
+        {
+            tesseract_engine::recognize_words_on_thread(
+                region,
+                region_y_offset,
+                &options.label_patterns,
+            )
+        }
+        #[cfg(not(feature = "tesseract-ffi"))]
+        {
+            Self::extract_word_candidates_subprocess(region, region_y_offset, options)
+        }
+    }
+
+    /// Portable fallback: parse `tesseract --psm 7 tsv` output for per-word boxes.
+    #[cfg_attr(feature = "tesseract-ffi", allow(dead_code))]
+    fn extract_word_candidates_subprocess(
+        region: &image::DynamicImage,
+        region_y_offset: u32,
+        options: &PageNumberOptions,
+    ) -> Vec<PageNumberCandidate> {
+        let temp_dir = std::env::temp_dir();
+        let temp_path = temp_dir.join(format!("page_num_words_{}.png", std::process::id()));
+
+        if region.save(&temp_path).is_err() {
+            return Vec::new();
+        }
+
+        let output = std::process::Command::new("tesseract")
+            .arg(&temp_path)
+            .arg("stdout")
+            .arg("--psm")
+            .arg("7")
+            .arg("tsv")
+            .output();
+
+        let _ = std::fs::remove_file(&temp_path);
+
+        let Ok(result) = output else {
+            return Vec::new();
+        };
+        if !result.status.success() {
+            return Vec::new();
+        }
+
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        parse_tesseract_tsv(&stdout, region_y_offset, &options.label_patterns)
+    }
+
     /// Analyze image region for numbers using Tesseract OCR
+    ///
+    /// Uses the in-process `tesseract-ffi` backend when the `tesseract-ffi` feature
+    /// is enabled (no per-page process spawn, shared engine init). Falls back to the
+    /// `tesseract` CLI subprocess otherwise, which is the portable default and does
+    /// not require libtesseract headers at build time.
     fn analyze_region_for_numbers(
         img: &image::DynamicImage,
         _options: &PageNumberOptions,
+    ) -> (Option<i32>, String, f32) {
+        #[cfg(feature = "tesseract-ffi")]
+        {
+            Self::analyze_region_for_numbers_ffi(img)
+        }
+        #[cfg(not(feature = "tesseract-ffi"))]
+        {
+            Self::analyze_region_for_numbers_subprocess(img)
+        }
+    }
+
+    /// In-process libtesseract backend (feature = "tesseract-ffi")
+    ///
+    /// Reuses one `TessApi` per rayon worker thread (see [`tesseract_engine`]) instead
+    /// of spawning a `tesseract` process per page, which removes most of the per-page
+    /// overhead when analyzing large batches.
+    #[cfg(feature = "tesseract-ffi")]
+    fn analyze_region_for_numbers_ffi(img: &image::DynamicImage) -> (Option<i32>, String, f32) {
+        match tesseract_engine::recognize_digits_on_thread(img) {
+            Ok((raw_text, mean_confidence)) => {
+                let digits: String = raw_text.chars().filter(|c| c.is_ascii_digit()).collect();
+
+                if digits.is_empty() {
+                    return (None, raw_text, 0.0);
+                }
+
+                match digits.parse::<i32>() {
+                    Ok(num) if num > 0 && num < 10000 => (Some(num), raw_text, mean_confidence),
+                    _ => (None, raw_text, 30.0),
+                }
+            }
+            Err(_) => (None, String::new(), 0.0),
+        }
+    }
+
+    /// Portable fallback backend: shells out to the `tesseract` CLI per region
+    #[cfg_attr(feature = "tesseract-ffi", allow(dead_code))]
+    fn analyze_region_for_numbers_subprocess(
+        img: &image::DynamicImage,
     ) -> (Option<i32>, String, f32) {
         // Create temp file for the cropped region
         let temp_dir = std::env::temp_dir();
@@ -411,16 +1310,19 @@ impl TesseractPageDetector {
         images: &[PathBuf],
         options: &PageNumberOptions,
     ) -> Result<PageNumberAnalysis> {
-        let detections: Vec<DetectedPageNumber> = images
+        let mut detections: Vec<DetectedPageNumber> = images
             .par_iter()
             .enumerate()
             .map(|(i, path)| Self::detect_single(path, i, options))
             .collect::<Result<Vec<_>>>()?;
 
+        Self::correct_via_arithmetic_progression(&mut detections);
+        let sequence_analysis = sequence::analyze_sequence(&mut detections);
+
         // Analyze pattern
         let (position_pattern, odd_offset, even_offset) = Self::analyze_pattern(&detections);
 
-        // Find missing and duplicate pages
+        // Find missing and duplicate pages (using the corrected numbers)
         let detected_numbers: Vec<i32> = detections.iter().filter_map(|d| d.number).collect();
         let missing_pages = Self::find_missing_pages(&detected_numbers);
         let duplicate_pages = Self::find_duplicate_pages(&detected_numbers);
@@ -439,6 +1341,7 @@ impl TesseractPageDetector {
             overall_confidence,
             missing_pages,
             duplicate_pages,
+            interpolated_pages: sequence_analysis.corrected_pages,
         })
     }
 
@@ -483,6 +1386,65 @@ impl TesseractPageDetector {
         (position_pattern, odd_avg, even_avg)
     }
 
+    /// Correct isolated OCR misreads by fitting a robust arithmetic progression
+    /// (`number = start + step * page_index`) across the batch and overwriting any
+    /// detection whose number disagrees with the fit but whose confidence is below
+    /// [`DEFAULT_MIN_CONFIDENCE`](super::types::DEFAULT_MIN_CONFIDENCE), which is the
+    /// common case for a single misread digit surrounded by correctly read neighbors.
+    ///
+    /// `step` and `start` are estimated via the median (not the mean) of per-pair
+    /// steps and per-point offsets, so a handful of misreads can't drag the whole fit
+    /// off; full RANSAC-style inlier voting is left to higher confidence thresholds
+    /// downstream.
+    fn correct_via_arithmetic_progression(detections: &mut [DetectedPageNumber]) {
+        let points: Vec<(usize, i32)> = detections
+            .iter()
+            .filter_map(|d| d.number.map(|n| (d.page_index, n)))
+            .collect();
+
+        if points.len() < 3 {
+            return;
+        }
+
+        let mut steps: Vec<f64> = points
+            .windows(2)
+            .filter_map(|w| {
+                let (idx_a, num_a) = w[0];
+                let (idx_b, num_b) = w[1];
+                let idx_delta = idx_b as i64 - idx_a as i64;
+                if idx_delta == 0 {
+                    None
+                } else {
+                    Some((num_b - num_a) as f64 / idx_delta as f64)
+                }
+            })
+            .collect();
+        if steps.is_empty() {
+            return;
+        }
+        steps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let step = steps[steps.len() / 2];
+
+        let mut offsets: Vec<f64> = points
+            .iter()
+            .map(|&(idx, num)| num as f64 - step * idx as f64)
+            .collect();
+        offsets.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let start = offsets[offsets.len() / 2];
+
+        for detection in detections.iter_mut() {
+            let Some(number) = detection.number else {
+                continue;
+            };
+            let predicted = (start + step * detection.page_index as f64).round() as i32;
+            let disagrees = number != predicted;
+            let low_confidence = detection.confidence * 100.0 < super::types::DEFAULT_MIN_CONFIDENCE;
+            if disagrees && low_confidence {
+                detection.number = Some(predicted);
+            }
+        }
+    }
+
     /// Find missing page numbers
     fn find_missing_pages(numbers: &[i32]) -> Vec<usize> {
         if numbers.is_empty() {
@@ -563,42 +1525,6 @@ impl TesseractPageDetector {
 
         Ok(true)
     }
-
-    /// Parse Roman numeral to integer
-    pub fn parse_roman_numeral(text: &str) -> Option<i32> {
-        let text = text.to_lowercase().trim().to_string();
-        let roman_map = [
-            ("m", 1000),
-            ("cm", 900),
-            ("d", 500),
-            ("cd", 400),
-            ("c", 100),
-            ("xc", 90),
-            ("l", 50),
-            ("xl", 40),
-            ("x", 10),
-            ("ix", 9),
-            ("v", 5),
-            ("iv", 4),
-            ("i", 1),
-        ];
-
-        let mut result = 0;
-        let mut remaining = text.as_str();
-
-        for (numeral, value) in &roman_map {
-            while remaining.starts_with(numeral) {
-                result += value;
-                remaining = &remaining[numeral.len()..];
-            }
-        }
-
-        if remaining.is_empty() && result > 0 {
-            Some(result)
-        } else {
-            None
-        }
-    }
 }
 
 #[cfg(test)]
@@ -613,38 +1539,6 @@ mod tests {
         assert!(matches!(result, Err(PageNumberError::ImageNotFound(_))));
     }
 
-    #[test]
-    fn test_roman_numeral_parsing() {
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("I"), Some(1));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("IV"), Some(4));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("V"), Some(5));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("IX"), Some(9));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("X"), Some(10));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("XL"), Some(40));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("L"), Some(50));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("XC"), Some(90));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("C"), Some(100));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("CD"), Some(400));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("D"), Some(500));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("CM"), Some(900));
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("M"), Some(1000));
-        assert_eq!(
-            TesseractPageDetector::parse_roman_numeral("MCMXCIX"),
-            Some(1999)
-        );
-        assert_eq!(
-            TesseractPageDetector::parse_roman_numeral("MMXXIII"),
-            Some(2023)
-        );
-    }
-
-    #[test]
-    fn test_roman_numeral_invalid() {
-        assert_eq!(TesseractPageDetector::parse_roman_numeral(""), None);
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("ABC"), None);
-        assert_eq!(TesseractPageDetector::parse_roman_numeral("123"), None);
-    }
-
     #[test]
     fn test_find_missing_pages() {
         let numbers = vec![1, 2, 4, 5, 7];
@@ -670,6 +1564,64 @@ mod tests {
         assert_eq!(result.overall_confidence, 0.0);
     }
 
+    // ============================================================
+    // Arithmetic Progression Correction Tests
+    // ============================================================
+
+    fn detection(page_index: usize, number: i32, confidence: f32) -> DetectedPageNumber {
+        DetectedPageNumber {
+            page_index,
+            number: Some(number),
+            position: PageNumberRect {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 50,
+            },
+            confidence,
+            raw_text: number.to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_correct_via_arithmetic_progression_fixes_low_confidence_misread() {
+        let mut detections = vec![
+            detection(0, 10, 0.95),
+            detection(1, 11, 0.95),
+            detection(2, 18, 0.20), // misread: should be 12, low confidence
+            detection(3, 13, 0.95),
+            detection(4, 14, 0.95),
+        ];
+
+        TesseractPageDetector::correct_via_arithmetic_progression(&mut detections);
+
+        assert_eq!(detections[2].number, Some(12));
+    }
+
+    #[test]
+    fn test_correct_via_arithmetic_progression_keeps_high_confidence_outlier() {
+        let mut detections = vec![
+            detection(0, 10, 0.95),
+            detection(1, 11, 0.95),
+            detection(2, 18, 0.95), // disagrees with fit but OCR is confident: keep it
+            detection(3, 13, 0.95),
+            detection(4, 14, 0.95),
+        ];
+
+        TesseractPageDetector::correct_via_arithmetic_progression(&mut detections);
+
+        assert_eq!(detections[2].number, Some(18));
+    }
+
+    #[test]
+    fn test_correct_via_arithmetic_progression_skips_sparse_batches() {
+        let mut detections = vec![detection(0, 10, 0.10), detection(1, 99, 0.10)];
+        TesseractPageDetector::correct_via_arithmetic_progression(&mut detections);
+        // Fewer than 3 data points: no fit attempted, values untouched
+        assert_eq!(detections[1].number, Some(99));
+    }
+
     #[test]
     fn test_validate_order_ascending() {
         let analysis = PageNumberAnalysis {
@@ -685,6 +1637,7 @@ mod tests {
                     },
                     confidence: 0.9,
                     raw_text: "1".to_string(),
+                    label: None,
                 },
                 DetectedPageNumber {
                     page_index: 1,
@@ -697,6 +1650,7 @@ mod tests {
                     },
                     confidence: 0.9,
                     raw_text: "2".to_string(),
+                    label: None,
                 },
                 DetectedPageNumber {
                     page_index: 2,
@@ -709,6 +1663,7 @@ mod tests {
                     },
                     confidence: 0.9,
                     raw_text: "3".to_string(),
+                    label: None,
                 },
             ],
             position_pattern: PageNumberPosition::BottomCenter,
@@ -717,6 +1672,7 @@ mod tests {
             overall_confidence: 0.9,
             missing_pages: vec![],
             duplicate_pages: vec![],
+            interpolated_pages: vec![],
         };
 
         assert!(TesseractPageDetector::validate_order(&analysis).unwrap());
@@ -737,6 +1693,7 @@ mod tests {
                     },
                     confidence: 0.9,
                     raw_text: "3".to_string(),
+                    label: None,
                 },
                 DetectedPageNumber {
                     page_index: 1,
@@ -749,6 +1706,7 @@ mod tests {
                     },
                     confidence: 0.9,
                     raw_text: "1".to_string(),
+                    label: None,
                 },
             ],
             position_pattern: PageNumberPosition::BottomCenter,
@@ -757,6 +1715,7 @@ mod tests {
             overall_confidence: 0.9,
             missing_pages: vec![],
             duplicate_pages: vec![],
+            interpolated_pages: vec![],
         };
 
         assert!(!TesseractPageDetector::validate_order(&analysis).unwrap());
@@ -770,7 +1729,7 @@ mod tests {
     fn test_fallback_empty_candidates() {
         let candidates: Vec<PageNumberCandidate> = vec![];
         let region = Rectangle::new(0, 900, 1000, 100);
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
         assert!(result.is_none());
     }
 
@@ -781,12 +1740,12 @@ mod tests {
             PageNumberCandidate::new("41".to_string(), Rectangle::new(100, 950, 50, 30), 0.90),
         ];
         let region = Rectangle::new(0, 900, 1000, 100);
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         assert!(result.is_some());
         let m = result.unwrap();
         assert_eq!(m.stage, MatchStage::ExactMatch);
-        assert_eq!(m.expected_number, 42);
+        assert_eq!(m.expected_label, PageLabel::arabic(42));
         assert_eq!(m.candidate.number, Some(42));
     }
 
@@ -798,7 +1757,7 @@ mod tests {
             PageNumberCandidate::new("42".to_string(), Rectangle::new(500, 950, 50, 30), 0.95),
         ];
         let region = Rectangle::new(400, 900, 200, 100); // Center at (500, 950)
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         assert!(result.is_some());
         let m = result.unwrap();
@@ -809,21 +1768,55 @@ mod tests {
 
     #[test]
     fn test_fallback_stage2_similarity_match() {
-        // No exact match, but similar text (123 is similar to 124)
+        // No exact match, but OCR misread '1' as 'l' (a known confusion pair)
         let candidates = vec![
-            PageNumberCandidate::new("124".to_string(), Rectangle::new(500, 950, 50, 30), 0.80),
+            PageNumberCandidate::new("l23".to_string(), Rectangle::new(500, 950, 50, 30), 0.80),
             PageNumberCandidate::new("abc".to_string(), Rectangle::new(100, 950, 50, 30), 0.90),
         ];
         let region = Rectangle::new(400, 900, 200, 100);
-        let result = find_page_number_with_fallback(&candidates, 123, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(123), &region);
 
         assert!(result.is_some());
         let m = result.unwrap();
         assert_eq!(m.stage, MatchStage::SimilarityMatch);
-        // "124" is similar to "123" (Jaro-Winkler ~0.93)
+        // "l23" is a single confusable substitution away from "123"
         assert!(m.score >= MIN_SIMILARITY_THRESHOLD);
     }
 
+    #[test]
+    fn test_ocr_aware_similarity_exact_match() {
+        assert_eq!(
+            ocr_aware_similarity("123", "123", DEFAULT_CONFUSION_PAIRS),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_ocr_aware_similarity_confusable_substitution_is_cheap() {
+        // "l23" for "123" is a single confusable substitution ('1' <-> 'l'),
+        // so it should score much closer to 1.0 than an unrelated substitution.
+        let confusable = ocr_aware_similarity("123", "l23", DEFAULT_CONFUSION_PAIRS);
+        let unrelated = ocr_aware_similarity("123", "a23", DEFAULT_CONFUSION_PAIRS);
+        assert!(confusable > unrelated);
+        assert!(confusable >= MIN_SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn test_ocr_aware_similarity_unrelated_substitution_uses_flat_cost() {
+        // "129" vs "123": one unrelated substitution out of 3 chars -> 1 - 1/3
+        let similarity = ocr_aware_similarity("123", "129", DEFAULT_CONFUSION_PAIRS);
+        assert!((similarity - (1.0 - 1.0 / 3.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ocr_aware_similarity_custom_confusion_table() {
+        // A caller-supplied table can charge a reduced cost for glyphs not in the
+        // default table (e.g. a language-specific confusion).
+        let custom_pairs: &[ConfusionPair] = &[('3', 'E', 0.1)];
+        let similarity = ocr_aware_similarity("123", "12E", custom_pairs);
+        assert!((similarity - (1.0 - 0.1 / 3.0)).abs() < 1e-9);
+    }
+
     #[test]
     fn test_fallback_stage3_ocr_success() {
         // No exact or similar match, but OCR success
@@ -831,7 +1824,7 @@ mod tests {
             PageNumberCandidate::new("xyz".to_string(), Rectangle::new(500, 950, 50, 30), 0.80),
         ];
         let region = Rectangle::new(0, 0, 100, 100); // Far from candidate
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         assert!(result.is_some());
         let m = result.unwrap();
@@ -849,7 +1842,7 @@ mod tests {
         candidate.ocr_success = false; // Force OCR failure
         let candidates = vec![candidate];
         let region = Rectangle::new(0, 0, 100, 100);
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         assert!(result.is_some());
         let m = result.unwrap();
@@ -864,7 +1857,7 @@ mod tests {
             PageNumberCandidate::new("42".to_string(), Rectangle::new(500, 950, 50, 30), 0.95),
         ];
         let region = Rectangle::new(0, 900, 1000, 100);
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         // Should match Stage 1 (exact) even though Stage 3 would also match
         assert!(result.is_some());
@@ -934,9 +1927,358 @@ mod tests {
         let candidates = vec![candidate];
         // Region center at (500, 950), with expansion should include (503, 953)
         let region = Rectangle::new(400, 900, 200, 100);
-        let result = find_page_number_with_fallback(&candidates, 42, &region);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(42), &region);
 
         assert!(result.is_some());
         assert_eq!(result.unwrap().stage, MatchStage::ExactMatch);
     }
+
+    // ============================================================
+    // PageLabel-Aware Matching Tests
+    // ============================================================
+
+    #[test]
+    fn test_fallback_matches_roman_numeral_exact() {
+        let candidates = vec![PageNumberCandidate::new(
+            "xii".to_string(),
+            Rectangle::new(500, 950, 50, 30),
+            0.95,
+        )];
+        let expected = PageLabel {
+            style: PageLabelStyle::RomanLower,
+            ordinal: 12,
+            prefix: None,
+        };
+        let region = Rectangle::new(0, 900, 1000, 100);
+        let result = find_page_number_with_fallback(&candidates, &expected, &region);
+
+        assert!(result.is_some());
+        let m = result.unwrap();
+        assert_eq!(m.stage, MatchStage::ExactMatch);
+        assert_eq!(m.expected_label, expected);
+    }
+
+    #[test]
+    fn test_fallback_does_not_cross_match_arabic_against_roman_scheme() {
+        // An Arabic "12" candidate shouldn't exact- or similarity-match a Roman
+        // numeral page ("xii") even though Stage 3/4 would still find it.
+        let candidates = vec![PageNumberCandidate::new(
+            "12".to_string(),
+            Rectangle::new(500, 950, 50, 30),
+            0.95,
+        )];
+        let expected = PageLabel {
+            style: PageLabelStyle::RomanLower,
+            ordinal: 12,
+            prefix: None,
+        };
+        let region = Rectangle::new(0, 900, 1000, 100);
+        let result = find_page_number_with_fallback(&candidates, &expected, &region);
+
+        assert!(result.is_some());
+        // Falls through to a later stage rather than treating "12" as "xii"
+        assert_ne!(result.unwrap().stage, MatchStage::ExactMatch);
+    }
+
+    #[test]
+    fn test_allowed_edit_cost_grows_with_length() {
+        assert_eq!(allowed_edit_cost(1), 0.0);
+        assert_eq!(allowed_edit_cost(3), 1.0);
+        assert_eq!(allowed_edit_cost(4), 2.0);
+        assert_eq!(allowed_edit_cost(10), 2.0);
+    }
+
+    #[test]
+    fn test_fallback_stage2_rejects_single_digit_typo() {
+        // A 1-digit expected number gets zero typo budget: any mismatch is rejected
+        // by Stage 2, even a confusable one.
+        let candidates = vec![PageNumberCandidate::new(
+            "l".to_string(),
+            Rectangle::new(500, 950, 50, 30),
+            0.80,
+        )];
+        let region = Rectangle::new(400, 900, 200, 100);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(1), &region);
+
+        assert!(result.is_some());
+        assert_ne!(result.unwrap().stage, MatchStage::SimilarityMatch);
+    }
+
+    #[test]
+    fn test_fallback_stage2_relaxed_budget_for_long_numbers() {
+        // Two unrelated-substitution edits: similarity 0.5 fails the legacy flat
+        // 0.7 threshold, but a 4-digit expected number's larger typo budget (cost
+        // <= 2.0) still accepts it, and the match records that it relied on the
+        // relaxed budget.
+        let candidates = vec![PageNumberCandidate::new(
+            "1099".to_string(),
+            Rectangle::new(500, 950, 50, 30),
+            0.80,
+        )];
+        let region = Rectangle::new(400, 900, 200, 100);
+        let result =
+            find_page_number_with_fallback(&candidates, &PageLabel::arabic(1024), &region);
+
+        assert!(result.is_some());
+        let m = result.unwrap();
+        assert_eq!(m.stage, MatchStage::SimilarityMatch);
+        assert!(m.relaxed_budget);
+
+        let stats = FallbackMatchStats::from_matches(&[Some(m)]);
+        assert_eq!(stats.relaxed_budget_matches, 1);
+    }
+
+    #[test]
+    fn test_fallback_stage2_still_matches_confusable_letter_for_arabic_page() {
+        // "l23" parses as an alpha-decorated label ("l"-23), a different scheme than
+        // the expected Arabic "123" - but since Arabic is the common case, the
+        // cross-scheme filter is skipped and the confusable-letter misread still
+        // similarity-matches as before.
+        let candidates = vec![PageNumberCandidate::new(
+            "l23".to_string(),
+            Rectangle::new(500, 950, 50, 30),
+            0.80,
+        )];
+        let region = Rectangle::new(400, 900, 200, 100);
+        let result = find_page_number_with_fallback(&candidates, &PageLabel::arabic(123), &region);
+
+        assert!(result.is_some());
+        assert_eq!(result.unwrap().stage, MatchStage::SimilarityMatch);
+    }
+
+    // ============================================================
+    // Composite Ranking Score Tests
+    // ============================================================
+
+    #[test]
+    fn test_composite_score_prefers_closer_candidate() {
+        let region = Rectangle::new(400, 900, 200, 100); // Center at (500, 950)
+        let near = PageNumberCandidate::new("42".to_string(), Rectangle::new(500, 950, 50, 30), 0.9);
+        let far = PageNumberCandidate::new("42".to_string(), Rectangle::new(100, 950, 50, 30), 0.9);
+
+        let near_score = composite_score(
+            &near,
+            "42",
+            near.distance_to(500, 950),
+            DEFAULT_CONFUSION_PAIRS,
+            &region,
+            None,
+        );
+        let far_score = composite_score(
+            &far,
+            "42",
+            far.distance_to(500, 950),
+            DEFAULT_CONFUSION_PAIRS,
+            &region,
+            None,
+        );
+
+        assert!(near_score > far_score);
+    }
+
+    #[test]
+    fn test_composite_score_positional_bonus_rewards_dominant_half() {
+        // Same candidate, same distance - only the dominant position hint differs.
+        let region = Rectangle::new(0, 900, 1000, 100); // y 900..1000, midpoint 950
+        let top_candidate =
+            PageNumberCandidate::new("42".to_string(), Rectangle::new(475, 900, 50, 10), 0.9);
+
+        let with_matching_hint = composite_score(
+            &top_candidate,
+            "42",
+            0.0,
+            DEFAULT_CONFUSION_PAIRS,
+            &region,
+            Some(PageNumberPosition::TopCenter),
+        );
+        let with_opposing_hint = composite_score(
+            &top_candidate,
+            "42",
+            0.0,
+            DEFAULT_CONFUSION_PAIRS,
+            &region,
+            Some(PageNumberPosition::BottomCenter),
+        );
+        let with_no_hint = composite_score(
+            &top_candidate,
+            "42",
+            0.0,
+            DEFAULT_CONFUSION_PAIRS,
+            &region,
+            None,
+        );
+
+        assert!(with_matching_hint > with_no_hint);
+        // No hint and an opposing hint both contribute zero positional bonus
+        assert!((with_no_hint - with_opposing_hint).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_dominant_position_majority_vote() {
+        let region = Rectangle::new(0, 900, 1000, 100); // midpoint y = 950
+        let top = PageNumberCandidate::new("1".to_string(), Rectangle::new(475, 900, 50, 10), 0.9);
+        let bottom =
+            PageNumberCandidate::new("2".to_string(), Rectangle::new(475, 980, 50, 10), 0.9);
+
+        let matches = vec![
+            Some(PageNumberMatch::new(top.clone(), MatchStage::ExactMatch, 1.0, 0.0, PageLabel::arabic(1))),
+            Some(PageNumberMatch::new(top, MatchStage::ExactMatch, 1.0, 0.0, PageLabel::arabic(2))),
+            Some(PageNumberMatch::new(bottom, MatchStage::ExactMatch, 1.0, 0.0, PageLabel::arabic(3))),
+        ];
+        let regions = vec![region; 3];
+
+        assert_eq!(
+            estimate_dominant_position(&matches, &regions),
+            Some(PageNumberPosition::TopCenter)
+        );
+    }
+
+    #[test]
+    fn test_estimate_dominant_position_none_when_batch_empty() {
+        let matches: Vec<Option<PageNumberMatch>> = vec![None, None];
+        let regions = vec![Rectangle::new(0, 900, 1000, 100); 2];
+        assert_eq!(estimate_dominant_position(&matches, &regions), None);
+    }
+
+    #[test]
+    fn test_find_page_numbers_batch_ranked_sorts_candidates_by_composite_score() {
+        // A wide region so both candidates fall within the expanded search area -
+        // the narrower regions used by the single-winner tests above would exclude
+        // one candidate entirely, leaving nothing to rank.
+        let page_candidates = vec![vec![
+            PageNumberCandidate::new("42".to_string(), Rectangle::new(100, 950, 50, 30), 0.90),
+            PageNumberCandidate::new("42".to_string(), Rectangle::new(500, 950, 50, 30), 0.95),
+        ]];
+        let regions = vec![Rectangle::new(50, 900, 500, 100)]; // Center at (300, 950)
+
+        let results = find_page_numbers_batch_ranked(&page_candidates, 42, &regions);
+
+        assert_eq!(results.len(), 1);
+        let page = &results[0];
+        assert!(page.best.is_some());
+        assert_eq!(page.ranked_candidates.len(), 2);
+        // Sorted descending by composite score
+        assert!(page.ranked_candidates[0].1 >= page.ranked_candidates[1].1);
+        // The winner heads the ranked list too
+        assert_eq!(
+            page.ranked_candidates[0].0.bbox.x,
+            page.best.as_ref().unwrap().candidate.bbox.x
+        );
+    }
+
+    // ============================================================
+    // Word-Level TSV Extraction Tests (Phase 2.1 continued)
+    // ============================================================
+
+    // ============================================================
+    // OSD Orientation Detection Tests
+    // ============================================================
+
+    #[test]
+    fn test_parse_osd_rotation_upright() {
+        let osd = "Page number: 0\nOrientation in degrees: 0\nRotate: 0\nOrientation confidence: 5.5\n";
+        assert_eq!(parse_osd_rotation(osd), Some(0));
+    }
+
+    #[test]
+    fn test_parse_osd_rotation_sideways() {
+        let osd = "Page number: 0\nOrientation in degrees: 90\nRotate: 90\nOrientation confidence: 3.2\n";
+        assert_eq!(parse_osd_rotation(osd), Some(90));
+    }
+
+    #[test]
+    fn test_parse_osd_rotation_missing() {
+        assert_eq!(parse_osd_rotation("garbage output\n"), None);
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv_word_rows() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                    1\t1\t0\t0\t0\t0\t0\t0\t1000\t100\t-1\t\n\
+                    5\t1\t1\t1\t1\t1\t500\t950\t50\t30\t95.5\t42\n";
+
+        let candidates = parse_tesseract_tsv(tsv, 0, &[]);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].text, "42");
+        assert_eq!(candidates[0].number, Some(42));
+        assert_eq!(candidates[0].bbox, Rectangle::new(500, 950, 50, 30));
+        assert!((candidates[0].confidence - 0.955).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv_applies_y_offset() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                    5\t1\t1\t1\t1\t1\t10\t20\t50\t30\t90.0\t7\n";
+
+        let candidates = parse_tesseract_tsv(tsv, 900, &[]);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].bbox.y, 920);
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv_skips_empty_and_non_word_rows() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                    2\t1\t1\t0\t0\t0\t0\t0\t1000\t100\t-1\t\n\
+                    5\t1\t1\t1\t1\t1\t10\t20\t50\t30\t90.0\t\n";
+
+        let candidates = parse_tesseract_tsv(tsv, 0, &[]);
+        assert!(candidates.is_empty());
+    }
+
+    // ============================================================
+    // Configurable Label Pattern Tests
+    // ============================================================
+
+    #[test]
+    fn test_extract_label_number_page_prefix() {
+        assert_eq!(
+            extract_label_number("Page 12", &default_label_patterns()),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_extract_label_number_dash_wrapped() {
+        assert_eq!(
+            extract_label_number("- 12 -", &default_label_patterns()),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_extract_label_number_fraction() {
+        assert_eq!(
+            extract_label_number("12 / 340", &default_label_patterns()),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_extract_label_number_no_match() {
+        assert_eq!(extract_label_number("Chapter One", &default_label_patterns()), None);
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv_groups_label_into_line_candidate() {
+        // Two words "Page" and "12" on the same OCR line should combine into one
+        // label candidate in addition to the individual word candidates.
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                    5\t1\t1\t1\t1\t1\t400\t950\t80\t30\t92.0\tPage\n\
+                    5\t1\t1\t1\t1\t2\t490\t950\t40\t30\t93.0\t12\n";
+
+        let candidates = parse_tesseract_tsv(tsv, 0, &default_label_patterns());
+        assert_eq!(candidates.len(), 3); // "Page" + "12" + merged "Page 12" label
+        let label = candidates
+            .iter()
+            .find(|c| c.text == "Page 12")
+            .expect("expected merged label candidate");
+        assert_eq!(label.number, Some(12));
+    }
+
+    fn default_label_patterns() -> Vec<String> {
+        super::super::types::DEFAULT_LABEL_PATTERNS
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
 }
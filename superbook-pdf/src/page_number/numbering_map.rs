@@ -0,0 +1,356 @@
+//! Per-segment logical page numbering
+//!
+//! [`super::offset::PageOffsetResult`] carries a single `logical_page` per
+//! physical page, derived from one shift (or, via
+//! [`super::offset::PageOffsetAnalyzer::analyze_offsets`]'s piecewise
+//! `page_number_segments`, one shift per Arabic-numbered run). Real books
+//! are rarely numbered with a single scheme end to end: front matter in
+//! lowercase Roman, a body in Arabic starting back at 1, sometimes an
+//! appendix with its own reset. Since [`DetectedPageNumber::number`] only
+//! ever carries a plain Arabic value, a Roman-numeral or lettered page
+//! comes through as `None` and is simply dropped.
+//!
+//! This module indexes [`DetectedPageNumber::label`] (a full
+//! [`PageLabel`], carrying both the numbering scheme and the ordinal
+//! within it) into a [`PageNumberingMap`]: a sorted `Vec` of non-overlapping
+//! physical-page ranges, each tagged with the scheme and logical number
+//! that applies there. [`PageNumberingMap::build`] segments runs of
+//! consistent scheme and stride from the detections, then extends each
+//! run forward to the next run's start (and the first run back to page 1)
+//! so the ranges collectively partition the whole physical-page span with
+//! no gaps and no overlap. [`PageNumberingMap::lookup`] then answers
+//! "what scheme and logical number is physical page N" via binary search
+//! over that `Vec`.
+
+use super::types::{DetectedPageNumber, PageLabel, PageLabelStyle};
+
+/// A single non-overlapping span of physical pages that share one
+/// numbering scheme and advance at a stride of one logical number per
+/// physical page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberingRange {
+    /// First physical page (1-indexed) covered by this range
+    pub physical_start: usize,
+    /// Last physical page (1-indexed, inclusive) covered by this range
+    pub physical_end: usize,
+    /// Numbering scheme in effect across this range
+    pub scheme: PageLabelStyle,
+    /// Physical page at which a detection anchored this range - the first
+    /// physical page actually observed with a label of this scheme; differs
+    /// from `physical_start` only for the first range, which is extended
+    /// backward to page 1
+    anchor_physical: usize,
+    /// Logical ordinal observed at `anchor_physical`
+    anchor_ordinal: u32,
+}
+
+impl NumberingRange {
+    /// Logical ordinal at `physical`, extrapolated from the anchor at a
+    /// stride of one logical number per physical page. Only meaningful for
+    /// `physical` inside `[physical_start, physical_end]`.
+    fn logical_at(&self, physical: usize) -> i64 {
+        self.anchor_ordinal as i64 + (physical as i64 - self.anchor_physical as i64)
+    }
+}
+
+/// Sorted, non-overlapping map from physical page to numbering scheme and
+/// logical page, covering multiple numbering schemes within one document.
+#[derive(Debug, Clone, Default)]
+pub struct PageNumberingMap {
+    ranges: Vec<NumberingRange>,
+}
+
+impl PageNumberingMap {
+    /// The empty map: every [`Self::lookup`] misses.
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// The ranges making up this map, in ascending physical-page order.
+    pub fn ranges(&self) -> &[NumberingRange] {
+        &self.ranges
+    }
+
+    /// Build a map from `detections`' [`DetectedPageNumber::label`]s.
+    ///
+    /// Detections without a label (OCR failures, or pages the fallback
+    /// matcher couldn't confidently read) carry no scheme information of
+    /// their own; they're folded into whichever neighboring range's span
+    /// is extended to cover them. Detections are otherwise grouped into
+    /// runs of consistent scheme and stride - a new run starts whenever
+    /// the scheme changes or the ordinal doesn't advance by exactly the
+    /// same amount as the physical page (e.g. the body resetting from
+    /// Roman `xii` back to Arabic `1`).
+    pub fn build(detections: &[DetectedPageNumber]) -> Self {
+        let mut labeled: Vec<(usize, PageLabel)> = detections
+            .iter()
+            .filter_map(|d| d.label.clone().map(|label| (d.page_index + 1, label)))
+            .collect();
+        labeled.sort_by_key(|(physical, _)| *physical);
+
+        if labeled.is_empty() {
+            return Self::new();
+        }
+
+        let max_physical = detections
+            .iter()
+            .map(|d| d.page_index + 1)
+            .max()
+            .unwrap_or(0);
+
+        // Step 1: split into runs of consistent scheme and stride.
+        let mut runs: Vec<Vec<(usize, PageLabel)>> = Vec::new();
+        for entry in labeled {
+            let starts_new_run = match runs.last().and_then(|run| run.last()) {
+                Some((prev_physical, prev_label)) => {
+                    let physical_delta = entry.0 as i64 - *prev_physical as i64;
+                    let ordinal_delta = entry.1.ordinal as i64 - prev_label.ordinal as i64;
+                    entry.1.style != prev_label.style || ordinal_delta != physical_delta
+                }
+                None => true,
+            };
+
+            if starts_new_run {
+                runs.push(vec![entry]);
+            } else {
+                runs.last_mut().unwrap().push(entry);
+            }
+        }
+
+        // Step 2: turn each run into a range, extending forward to the
+        // next run's start (and the first range back to page 1) so the
+        // ranges partition the full physical-page span with no gaps.
+        let mut ranges: Vec<NumberingRange> = Vec::with_capacity(runs.len());
+        for (i, run) in runs.iter().enumerate() {
+            let (anchor_physical, anchor_label) = run.first().unwrap().clone();
+            let physical_start = if i == 0 { 1 } else { anchor_physical };
+            let physical_end = match runs.get(i + 1) {
+                Some(next_run) => next_run.first().unwrap().0 - 1,
+                None => max_physical.max(physical_start),
+            };
+
+            ranges.push(NumberingRange {
+                physical_start,
+                physical_end,
+                scheme: anchor_label.style,
+                anchor_physical,
+                anchor_ordinal: anchor_label.ordinal,
+            });
+        }
+
+        Self { ranges }
+    }
+
+    /// Look up the numbering scheme and logical page for physical page
+    /// `physical` (1-indexed) via binary search over the sorted ranges.
+    ///
+    /// Returns `Ok((scheme, logical))` for the containing range, or
+    /// `Err(insertion_index)` - the index in [`Self::ranges`] at which a
+    /// new range covering `physical` would need to be inserted to keep
+    /// the list sorted - if `physical` falls in a gap (only possible for
+    /// pages outside the span [`Self::build`] was given).
+    pub fn lookup(&self, physical: usize) -> Result<(PageLabelStyle, i32), usize> {
+        self.ranges
+            .binary_search_by(|range| {
+                if physical < range.physical_start {
+                    std::cmp::Ordering::Greater
+                } else if physical > range.physical_end {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .map(|idx| {
+                let range = &self.ranges[idx];
+                (range.scheme, range.logical_at(physical) as i32)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_number::types::PageNumberRect;
+
+    fn position() -> PageNumberRect {
+        PageNumberRect {
+            x: 0,
+            y: 0,
+            width: 50,
+            height: 20,
+        }
+    }
+
+    fn labeled_detection(page_index: usize, label: PageLabel) -> DetectedPageNumber {
+        DetectedPageNumber {
+            page_index,
+            number: None,
+            position: position(),
+            confidence: 0.9,
+            raw_text: label.render(),
+            label: Some(label),
+        }
+    }
+
+    fn roman_lower(ordinal: u32) -> PageLabel {
+        PageLabel {
+            style: PageLabelStyle::RomanLower,
+            ordinal,
+            prefix: None,
+        }
+    }
+
+    #[test]
+    fn test_build_empty_detections_yields_empty_map() {
+        let map = PageNumberingMap::build(&[]);
+        assert!(map.ranges().is_empty());
+        assert_eq!(map.lookup(1), Err(0));
+    }
+
+    #[test]
+    fn test_build_unlabeled_detections_yield_empty_map() {
+        let detections = vec![DetectedPageNumber {
+            page_index: 0,
+            number: Some(1),
+            position: position(),
+            confidence: 0.9,
+            raw_text: "1".to_string(),
+            label: None,
+        }];
+
+        let map = PageNumberingMap::build(&detections);
+        assert!(map.ranges().is_empty());
+    }
+
+    #[test]
+    fn test_build_single_scheme_covers_whole_span() {
+        let detections: Vec<DetectedPageNumber> = (0..5)
+            .map(|i| labeled_detection(i, PageLabel::arabic(i as u32 + 1)))
+            .collect();
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.ranges().len(), 1);
+        assert_eq!(map.ranges()[0].physical_start, 1);
+        assert_eq!(map.ranges()[0].physical_end, 5);
+        assert_eq!(map.lookup(3), Ok((PageLabelStyle::Arabic, 3)));
+    }
+
+    #[test]
+    fn test_build_splits_roman_front_matter_from_arabic_body() {
+        // Physical pages 1-3: roman i, ii, iii. Physical pages 4-6: arabic
+        // body resetting to 1, 2, 3.
+        let mut detections: Vec<DetectedPageNumber> = (0..3)
+            .map(|i| labeled_detection(i, roman_lower(i as u32 + 1)))
+            .collect();
+        detections.extend((3..6).map(|i| labeled_detection(i, PageLabel::arabic(i as u32 - 2))));
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.ranges().len(), 2);
+        assert_eq!(map.lookup(2), Ok((PageLabelStyle::RomanLower, 2)));
+        assert_eq!(map.lookup(5), Ok((PageLabelStyle::Arabic, 2)));
+    }
+
+    #[test]
+    fn test_build_ranges_partition_the_full_physical_span_without_gaps() {
+        let mut detections: Vec<DetectedPageNumber> = (0..3)
+            .map(|i| labeled_detection(i, roman_lower(i as u32 + 1)))
+            .collect();
+        detections.extend((3..8).map(|i| labeled_detection(i, PageLabel::arabic(i as u32 - 2))));
+
+        let map = PageNumberingMap::build(&detections);
+
+        let ranges = map.ranges();
+        assert_eq!(ranges[0].physical_start, 1);
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].physical_end + 1, pair[1].physical_start);
+        }
+        assert_eq!(ranges.last().unwrap().physical_end, 8);
+    }
+
+    #[test]
+    fn test_lookup_fills_ocr_gap_from_the_surrounding_run() {
+        // Page 2's OCR failed (no label), but pages 1 and 3 agree on a
+        // stride-1 Arabic run; page 2 should still resolve to logical 2.
+        let detections = vec![
+            labeled_detection(0, PageLabel::arabic(1)),
+            DetectedPageNumber {
+                page_index: 1,
+                number: None,
+                position: position(),
+                confidence: 0.0,
+                raw_text: String::new(),
+                label: None,
+            },
+            labeled_detection(2, PageLabel::arabic(3)),
+        ];
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.lookup(2), Ok((PageLabelStyle::Arabic, 2)));
+    }
+
+    #[test]
+    fn test_lookup_beyond_built_span_reports_insertion_index() {
+        let detections: Vec<DetectedPageNumber> = (0..3)
+            .map(|i| labeled_detection(i, PageLabel::arabic(i as u32 + 1)))
+            .collect();
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.lookup(100), Err(1));
+    }
+
+    #[test]
+    fn test_lookup_on_empty_map_reports_insertion_index_zero() {
+        let map = PageNumberingMap::new();
+        assert_eq!(map.lookup(42), Err(0));
+    }
+
+    #[test]
+    fn test_build_detects_three_segments() {
+        // Roman front matter, then an Arabic body, then a lettered
+        // appendix - three distinct schemes back to back.
+        let mut detections: Vec<DetectedPageNumber> = (0..2)
+            .map(|i| labeled_detection(i, roman_lower(i as u32 + 1)))
+            .collect();
+        detections.extend((2..5).map(|i| labeled_detection(i, PageLabel::arabic(i as u32 - 1))));
+        detections.push(labeled_detection(
+            5,
+            PageLabel {
+                style: PageLabelStyle::Alpha,
+                ordinal: 1,
+                prefix: Some("a".to_string()),
+            },
+        ));
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.ranges().len(), 3);
+        assert_eq!(map.lookup(6), Ok((PageLabelStyle::Alpha, 1)));
+    }
+
+    #[test]
+    fn test_build_first_range_extends_back_to_page_one() {
+        // Front matter's first detected label is on physical page 2 (page
+        // 1's OCR failed); the range must still start at page 1.
+        let detections = vec![
+            DetectedPageNumber {
+                page_index: 0,
+                number: None,
+                position: position(),
+                confidence: 0.0,
+                raw_text: String::new(),
+                label: None,
+            },
+            labeled_detection(1, roman_lower(2)),
+            labeled_detection(2, roman_lower(3)),
+        ];
+
+        let map = PageNumberingMap::build(&detections);
+
+        assert_eq!(map.ranges()[0].physical_start, 1);
+        assert_eq!(map.lookup(1), Ok((PageLabelStyle::RomanLower, 1)));
+    }
+}
@@ -3,8 +3,11 @@
 //! Calculates alignment offsets based on detected page numbers.
 //! Implements group-based reference position determination (Phase 2.2).
 
+use super::numbering_map::PageNumberingMap;
+use super::rtree::RTree;
 use super::types::{DetectedPageNumber, PageNumberRect, Point, Rectangle};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 // ============================================================
 // Constants
@@ -16,8 +19,12 @@ const MIN_MATCH_COUNT: usize = 5;
 /// Minimum ratio of matched pages to total pages
 const MIN_MATCH_RATIO: f64 = 1.0 / 3.0;
 
-/// Maximum shift to test when finding page number offset
-const MAX_SHIFT_TEST: i32 = 300;
+/// Confidence-weighted score (detection `confidence` values are normalized
+/// to 0.0-1.0) a new segment boundary must earn back before
+/// [`PageOffsetAnalyzer::segment_shifts`] will prefer it over extending the
+/// previous segment; keeps one-off OCR misreads from fragmenting a book that
+/// really does use a single constant shift throughout.
+const SEGMENT_SPLIT_PENALTY: f64 = 2.0;
 
 /// Margin percentage to expand bounding boxes (Phase 2.2)
 const BBOX_MARGIN_PERCENT: f32 = 3.0;
@@ -28,6 +35,18 @@ const MIN_CONTAINMENT_RATIO: f64 = 0.70;
 /// Top percentage of smallest bboxes to consider (30%)
 const TOP_SMALL_BBOX_RATIO: f64 = 0.30;
 
+/// Outlier-rejection threshold for [`calc_overlap_center_robust`]: a bbox
+/// center further than this many median absolute deviations from the
+/// coordinate-wise median center is treated as an outlier (e.g. a footnote
+/// marker misread as a page number) and excluded before recomputing the
+/// reference point.
+const OUTLIER_MAD_THRESHOLD: f64 = 3.0;
+
+/// Floor on how many bboxes [`calc_overlap_center_robust`] will keep as
+/// inliers; rejection never drives the surviving set below this, so a
+/// handful of detections can't be filtered down to nothing.
+const MIN_INLIERS: usize = 2;
+
 // ============================================================
 // Group-Based Reference Position (Phase 2.2)
 // ============================================================
@@ -112,6 +131,138 @@ pub fn calc_overlap_center(bboxes: &[Rectangle]) -> Point {
     calc_intersection_center(&selected_bboxes)
 }
 
+/// Like [`calc_overlap_center`], but first iteratively rejects outlier bbox
+/// centers so a single stray detection (a footnote marker misread as a page
+/// number) can't collapse the whole reference point: compute the
+/// coordinate-wise median of all candidate centers, measure each center's
+/// Euclidean distance to that median, discard centers beyond
+/// [`OUTLIER_MAD_THRESHOLD`] median absolute deviations, then recompute the
+/// median/MAD on the survivors and repeat until the inlier set stops
+/// shrinking (or would drop below [`MIN_INLIERS`]). [`calc_overlap_center`]
+/// then runs its normal containment/intersection algorithm on the final
+/// inliers.
+///
+/// Returns the refined point together with the number of surviving
+/// inliers, so a caller that sees a large fraction of candidates rejected
+/// can lower its own confidence accordingly. With two or fewer bboxes
+/// there's nothing to robustly estimate against, so this defers straight to
+/// [`calc_overlap_center`] and reports every bbox as an inlier.
+pub fn calc_overlap_center_robust(bboxes: &[Rectangle]) -> (Point, usize) {
+    if bboxes.len() <= 2 {
+        return (calc_overlap_center(bboxes), bboxes.len());
+    }
+
+    let mut inliers: Vec<Rectangle> = bboxes.to_vec();
+
+    loop {
+        if inliers.len() <= MIN_INLIERS {
+            break;
+        }
+
+        let centers: Vec<(f64, f64)> = inliers
+            .iter()
+            .map(|b| {
+                let (x, y) = b.center();
+                (x as f64, y as f64)
+            })
+            .collect();
+
+        let median_x = median(&centers.iter().map(|(x, _)| *x).collect::<Vec<_>>());
+        let median_y = median(&centers.iter().map(|(_, y)| *y).collect::<Vec<_>>());
+
+        let distances: Vec<f64> = centers
+            .iter()
+            .map(|(x, y)| ((x - median_x).powi(2) + (y - median_y).powi(2)).sqrt())
+            .collect();
+        let mad = median(&distances);
+
+        // Every surviving center already agrees with the median: nothing
+        // left to reject.
+        if mad <= f64::EPSILON {
+            break;
+        }
+
+        let threshold = OUTLIER_MAD_THRESHOLD * mad;
+        let next: Vec<Rectangle> = inliers
+            .iter()
+            .zip(distances.iter())
+            .filter(|(_, &dist)| dist <= threshold)
+            .map(|(b, _)| *b)
+            .collect();
+
+        if next.len() == inliers.len() || next.len() < MIN_INLIERS {
+            break;
+        }
+
+        inliers = next;
+    }
+
+    let inlier_count = inliers.len();
+    (calc_overlap_center(&inliers), inlier_count)
+}
+
+/// Like [`calc_overlap_center`], but replaces its `O(n^2)` pairwise
+/// containment scan with an [`RTree`] lookup: build the tree over the
+/// margin-expanded bboxes, use [`RTree::nearest_cluster`] to find the most
+/// crowded group of mutually overlapping candidates (in place of counting
+/// containment against every other bbox), then run the same "smallest 30%
+/// by area" selection and [`calc_intersection_center`] as
+/// [`calc_overlap_center`] to get the final point. Worth reaching for once
+/// a page yields many spurious candidates, since the tree prunes subtrees
+/// that can't overlap a given query instead of visiting every candidate.
+pub fn calc_overlap_center_indexed(bboxes: &[Rectangle]) -> Point {
+    if bboxes.len() <= 2 {
+        return calc_overlap_center(bboxes);
+    }
+
+    let expanded: Vec<Rectangle> = bboxes
+        .iter()
+        .map(|b| b.expand(BBOX_MARGIN_PERCENT))
+        .collect();
+
+    let tree = RTree::build(&expanded);
+    let mut cluster_indices = tree.nearest_cluster(&expanded);
+    if cluster_indices.is_empty() {
+        cluster_indices = (0..expanded.len()).collect();
+    }
+
+    let mut area_sorted: Vec<(usize, u64)> = cluster_indices
+        .iter()
+        .map(|&idx| (idx, expanded[idx].area()))
+        .collect();
+    area_sorted.sort_by_key(|(_, area)| *area);
+
+    let take_count = ((area_sorted.len() as f64 * TOP_SMALL_BBOX_RATIO).ceil() as usize).max(1);
+    let smallest_indices: Vec<usize> = area_sorted
+        .iter()
+        .take(take_count)
+        .map(|(idx, _)| *idx)
+        .collect();
+
+    let selected_bboxes: Vec<&Rectangle> =
+        smallest_indices.iter().map(|&idx| &expanded[idx]).collect();
+    calc_intersection_center(&selected_bboxes)
+}
+
+/// Median of `values`; for an even count, the average of the two middle
+/// elements. Used by [`calc_overlap_center_robust`]'s median/MAD outlier
+/// rejection; `values` is copied rather than sorted in place so callers
+/// keep their original ordering.
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = sorted.len();
+    if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
 /// Calculate the center of the intersection of multiple rectangles
 fn calc_intersection_center(bboxes: &[&Rectangle]) -> Point {
     if bboxes.is_empty() {
@@ -127,7 +278,7 @@ fn calc_intersection_center(bboxes: &[&Rectangle]) -> Point {
 
     // Intersect with all other bboxes
     for bbox in bboxes.iter().skip(1) {
-        if let Some(new_intersection) = intersection.intersection(bbox) {
+        if let Some(new_intersection) = intersection.intersect(bbox) {
             intersection = new_intersection;
         } else {
             // No intersection found - fall back to average of centers
@@ -160,13 +311,168 @@ fn calc_average_center(bboxes: &[&Rectangle]) -> Point {
 /// # Returns
 /// The calculated reference point for this group
 pub fn calc_group_reference_position(positions: &[(usize, PageNumberRect)], is_odd: bool) -> Point {
+    calc_group_reference_position_robust(positions, is_odd).0
+}
+
+/// Like [`calc_group_reference_position`], but via
+/// [`calc_overlap_center_robust`]'s outlier rejection. Returns the refined
+/// point, the number of surviving inliers, and the total candidate count,
+/// so [`PageOffsetAnalyzer::analyze_offsets`] can fold a high rejection
+/// rate into [`BookOffsetAnalysis::confidence`].
+pub fn calc_group_reference_position_robust(
+    positions: &[(usize, PageNumberRect)],
+    is_odd: bool,
+) -> (Point, usize, usize) {
     let filtered: Vec<Rectangle> = positions
         .iter()
         .filter(|(page, _)| (*page % 2 == 1) == is_odd)
         .map(|(_, rect)| Rectangle::new(rect.x as i32, rect.y as i32, rect.width, rect.height))
         .collect();
 
-    calc_overlap_center(&filtered)
+    let total = filtered.len();
+    let (point, inliers) = calc_overlap_center_robust(&filtered);
+    (point, inliers, total)
+}
+
+// ============================================================
+// Phase 2.3: Overlapping Candidate Resolution
+// ============================================================
+
+/// Per-pixel-column bonus/penalty used by [`resolve_overlapping_candidates`]'s
+/// split-point search.
+const OVERLAP_MATCH_SCORE: f64 = 1.0;
+
+/// Per-pixel-column penalty for a digit that disagrees with the
+/// stride-expected number, for a digit position the expected number doesn't
+/// have (a candidate with more digits than the expected number), or for a
+/// candidate with no digits at all.
+const OVERLAP_MISMATCH_PENALTY: f64 = 1.0;
+
+/// Resolve two overlapping page-number candidates detected on the same page -
+/// typically the real printed number plus a bleed-through ghost from the
+/// facing leaf - by splitting their shared horizontal span instead of
+/// arbitrarily keeping whichever has higher confidence (which can just as
+/// easily keep the ghost).
+///
+/// Walks every pixel column of the overlap left to right, scoring each column
+/// [`OVERLAP_MATCH_SCORE`] if `left`'s digit at that column agrees with
+/// `expected_number`'s stride-implied digit (aligned from the right, since
+/// page-footer numbers are printed right-justified) and
+/// [`OVERLAP_MISMATCH_PENALTY`] against it otherwise, producing a running
+/// prefix sum; the mirror right-to-left scan builds `right`'s suffix sum. The
+/// split point that maximizes `prefix + suffix` is where the overlap most
+/// plausibly switches from one candidate's real ink to the other's, so each
+/// candidate's bbox is trimmed to its side of that point. A ghost that
+/// disagrees with the expected digits loses its share of the overlap instead
+/// of vetoing the real candidate outright.
+///
+/// Returns `(left, right)` unchanged if their bboxes don't actually overlap.
+pub fn resolve_overlapping_candidates(
+    left: &DetectedPageNumber,
+    right: &DetectedPageNumber,
+    expected_number: i32,
+) -> (DetectedPageNumber, DetectedPageNumber) {
+    let left_rect = Rectangle::new(
+        left.position.x as i32,
+        left.position.y as i32,
+        left.position.width,
+        left.position.height,
+    );
+    let right_rect = Rectangle::new(
+        right.position.x as i32,
+        right.position.y as i32,
+        right.position.width,
+        right.position.height,
+    );
+
+    let overlap_start = left_rect.x.max(right_rect.x);
+    let overlap_end = (left_rect.x + left_rect.width as i32).min(right_rect.x + right_rect.width as i32);
+
+    if overlap_start >= overlap_end {
+        return (left.clone(), right.clone());
+    }
+
+    // Digit strings aligned from the right, so an extra leading digit (the
+    // ghost's own page number happening to run one digit longer) doesn't
+    // shift every comparison out of phase.
+    let expected_digits: Vec<char> = expected_number.unsigned_abs().to_string().chars().rev().collect();
+
+    let width = (overlap_end - overlap_start) as usize;
+    let mut prefix = vec![0.0f64; width + 1];
+    for i in 0..width {
+        let x = overlap_start + i as i32;
+        prefix[i + 1] = prefix[i] + digit_consistency_at(&left.raw_text, &left_rect, x, &expected_digits);
+    }
+    let mut suffix = vec![0.0f64; width + 1];
+    for i in (0..width).rev() {
+        let x = overlap_start + i as i32;
+        suffix[i] = suffix[i + 1] + digit_consistency_at(&right.raw_text, &right_rect, x, &expected_digits);
+    }
+
+    let mut best_split = overlap_start;
+    let mut best_score = f64::MIN;
+    for (i, (p, s)) in prefix.iter().zip(suffix.iter()).enumerate() {
+        let score = p + s;
+        if score > best_score {
+            best_score = score;
+            best_split = overlap_start + i as i32;
+        }
+    }
+
+    (
+        trimmed_to_range(left, left_rect, left_rect.x, best_split),
+        trimmed_to_range(right, right_rect, best_split, right_rect.x + right_rect.width as i32),
+    )
+}
+
+/// Match/mismatch score for the digit at pixel column `x` within `rect`
+/// (`text`'s characters assumed evenly spaced across `rect`'s width) against
+/// `expected_digits_from_right`, indexed from the right to stay aligned with
+/// `text`'s own digits regardless of either string's length.
+fn digit_consistency_at(
+    text: &str,
+    rect: &Rectangle,
+    x: i32,
+    expected_digits_from_right: &[char],
+) -> f64 {
+    let digits: Vec<char> = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() || rect.width == 0 {
+        return -OVERLAP_MISMATCH_PENALTY;
+    }
+
+    let col_width = rect.width as f64 / digits.len() as f64;
+    let offset = (x - rect.x).max(0) as f64;
+    let char_index = ((offset / col_width) as usize).min(digits.len() - 1);
+    let index_from_right = digits.len() - 1 - char_index;
+
+    match expected_digits_from_right.get(index_from_right) {
+        Some(&expected) if expected == digits[char_index] => OVERLAP_MATCH_SCORE,
+        _ => -OVERLAP_MISMATCH_PENALTY,
+    }
+}
+
+/// Trim `det`'s bbox to `[new_start, new_end)`, clamped to its original
+/// extent; only `position` changes, so the trimmed candidate keeps its
+/// original text, number, and confidence for downstream matching.
+fn trimmed_to_range(
+    det: &DetectedPageNumber,
+    original: Rectangle,
+    new_start: i32,
+    new_end: i32,
+) -> DetectedPageNumber {
+    let clamped_start = new_start.max(original.x);
+    let clamped_end = new_end
+        .min(original.x + original.width as i32)
+        .max(clamped_start);
+
+    let mut trimmed = det.clone();
+    trimmed.position = PageNumberRect {
+        x: clamped_start as u32,
+        y: det.position.y,
+        width: (clamped_end - clamped_start) as u32,
+        height: det.position.height,
+    };
+    trimmed
 }
 
 // ============================================================
@@ -188,6 +494,14 @@ pub struct PageOffsetResult {
     pub page_number_position: Option<PageNumberRect>,
     /// Whether this is an odd page (in physical order)
     pub is_odd: bool,
+    /// Whether this result came from [`PageOffsetAnalyzer::interpolate_missing_offsets`]'s
+    /// per-group regression rather than a directly detected page number
+    pub was_interpolated: bool,
+    /// Detection confidence (0.0-1.0) backing this result's `shift_x`/`shift_y`;
+    /// `0.0` for interpolated or unmatched pages, so
+    /// [`PageOffsetAnalyzer::smooth_shifts`] only lets confident detections
+    /// anchor the spring model and leaves the rest to be pulled by neighbors.
+    pub confidence: f64,
 }
 
 impl PageOffsetResult {
@@ -200,6 +514,8 @@ impl PageOffsetResult {
             shift_y: 0,
             page_number_position: None,
             is_odd: physical_page % 2 == 1,
+            was_interpolated: false,
+            confidence: 0.0,
         }
     }
 }
@@ -210,6 +526,12 @@ pub struct BookOffsetAnalysis {
     /// Physical to logical page number shift
     /// (logical_page = physical_page - page_number_shift)
     pub page_number_shift: i32,
+    /// Piecewise shifts found by [`PageOffsetAnalyzer::segment_shifts`], one
+    /// entry per contiguous physical-page range that shares a constant
+    /// shift (e.g. Roman-numeral front matter vs. the Arabic-numbered body).
+    /// Empty when the book only has a single segment, in which case
+    /// `page_number_shift` alone describes the whole book.
+    pub page_number_segments: Vec<(Range<usize>, i32)>,
     /// Per-page offset results
     pub page_offsets: Vec<PageOffsetResult>,
     /// Average X position for odd pages
@@ -230,6 +552,7 @@ impl Default for BookOffsetAnalysis {
     fn default() -> Self {
         Self {
             page_number_shift: 0,
+            page_number_segments: Vec::new(),
             page_offsets: Vec::new(),
             odd_avg_x: None,
             even_avg_x: None,
@@ -254,6 +577,23 @@ impl BookOffsetAnalysis {
             .iter()
             .find(|p| p.physical_page == physical_page)
     }
+
+    /// Physical-to-logical shift that applies to `physical_page`: the
+    /// piecewise segment that contains it, if any, else the single global
+    /// [`Self::page_number_shift`].
+    pub fn shift_for_page(&self, physical_page: usize) -> i32 {
+        shift_for_physical_page(&self.page_number_segments, physical_page, self.page_number_shift)
+    }
+}
+
+/// Shift that applies to `physical_page`: whichever `segments` range
+/// contains it, else `fallback` (the single global shift).
+fn shift_for_physical_page(segments: &[(Range<usize>, i32)], physical_page: usize, fallback: i32) -> i32 {
+    segments
+        .iter()
+        .find(|(range, _)| range.contains(&physical_page))
+        .map(|(_, shift)| *shift)
+        .unwrap_or(fallback)
 }
 
 // ============================================================
@@ -279,6 +619,16 @@ impl PageOffsetAnalyzer {
             return BookOffsetAnalysis::default();
         }
 
+        // Step 0: Trim any same-page candidates whose bboxes overlap (the
+        // real printed number plus a facing-leaf bleed-through ghost) before
+        // they can corrupt `calc_group_reference_position`'s odd/even
+        // averages below. The stride used to judge which side of the split
+        // each candidate's digits belong to comes from a rough shift vote
+        // over the untrimmed detections themselves.
+        let (rough_shift, _, _) = Self::find_best_page_number_shift(detections);
+        let resolved_detections = Self::resolve_overlapping_detections(detections, rough_shift);
+        let detections = &resolved_detections[..];
+
         // Step 1: Find the best physical-to-logical shift
         let (best_shift, match_count, confidence) = Self::find_best_page_number_shift(detections);
 
@@ -299,11 +649,28 @@ impl PageOffsetAnalyzer {
             };
         }
 
-        // Step 2: Build matched page data with positions
+        // Step 1b: Partition into piecewise constant-shift segments (Roman
+        // front matter, Arabic body, appendices, ...). A single surviving
+        // segment carries no information beyond `best_shift`, so it's
+        // dropped in favor of the plain global-shift behavior.
+        let page_number_segments = match Self::segment_shifts(detections) {
+            segments if segments.len() > 1 => segments,
+            _ => Vec::new(),
+        };
+
+        // Step 2: Build matched page data with positions. Detections are
+        // walked in reading order rather than whatever order the caller
+        // passed them in, so `calc_group_reference_position_robust`'s
+        // area-based tie-breaks below land the same way regardless of
+        // detection order (see `DetectedPageNumber::reading_order_cmp`).
+        let mut ordered_detections: Vec<&DetectedPageNumber> = detections.iter().collect();
+        ordered_detections.sort_by(|a, b| a.reading_order_cmp(b));
+
         let mut matched_pages: Vec<(usize, PageNumberRect, bool)> = Vec::new();
-        for det in detections {
+        for det in ordered_detections {
             let physical_page = det.page_index + 1;
-            let expected_logical = physical_page as i32 - best_shift;
+            let shift = shift_for_physical_page(&page_number_segments, physical_page, best_shift);
+            let expected_logical = physical_page as i32 - shift;
 
             if expected_logical >= 1 && det.number == Some(expected_logical) {
                 matched_pages.push((physical_page, det.position, physical_page % 2 == 1));
@@ -317,9 +684,19 @@ impl PageOffsetAnalyzer {
             .map(|(page, rect, _)| (*page, *rect))
             .collect();
 
-        // Use C#-compatible overlap center algorithm for odd/even groups
-        let odd_ref = calc_group_reference_position(&positions, true);
-        let even_ref = calc_group_reference_position(&positions, false);
+        // Use C#-compatible overlap center algorithm for odd/even groups,
+        // via the robust variant so a stray misdetection doesn't collapse
+        // the reference point; a high rejection rate instead folds into
+        // `reference_inlier_ratio` below.
+        let (odd_ref, odd_inliers, odd_total) = calc_group_reference_position_robust(&positions, true);
+        let (even_ref, even_inliers, even_total) =
+            calc_group_reference_position_robust(&positions, false);
+        let total_candidates = odd_total + even_total;
+        let reference_inlier_ratio = if total_candidates > 0 {
+            (odd_inliers + even_inliers) as f64 / total_candidates as f64
+        } else {
+            1.0
+        };
 
         // Convert Point to Option<i32> for backward compatibility
         let odd_avg_x = if odd_ref.x != 0 || positions.iter().any(|(p, _)| *p % 2 == 1) {
@@ -346,55 +723,106 @@ impl PageOffsetAnalyzer {
         // Step 4: Align Y values between groups if close enough
         let (final_odd_avg_y, final_even_avg_y) = Self::align_group_y_values(odd_avg_y, even_avg_y);
 
-        // Step 5: Calculate per-page offsets
+        // Step 5: Calculate per-page offsets. Pages whose Arabic shift
+        // doesn't match (including non-Arabic pages, which never carry a
+        // `det.number`) fall back to `numbering_map`'s per-scheme logical
+        // number rather than being discarded outright.
+        let numbering_map = PageNumberingMap::build(detections);
         let page_offsets = Self::calculate_per_page_offsets(
             detections,
             best_shift,
+            &page_number_segments,
+            &numbering_map,
             odd_avg_x,
             even_avg_x,
             final_odd_avg_y,
             final_even_avg_y,
         );
 
+        // Rejecting a large fraction of reference-position candidates as
+        // outliers is itself a sign this book's detections are noisy, so it
+        // scales the shift-vote confidence down rather than standing alone.
         BookOffsetAnalysis {
             page_number_shift: best_shift,
+            page_number_segments,
             page_offsets,
             odd_avg_x,
             even_avg_x,
             odd_avg_y: final_odd_avg_y,
             even_avg_y: final_even_avg_y,
             match_count,
-            confidence,
+            confidence: confidence * reference_inlier_ratio,
         }
     }
 
+    /// Group `detections` by page and, for any page carrying exactly two
+    /// candidates (the real printed number plus a facing-leaf bleed-through
+    /// ghost), run them through [`resolve_overlapping_candidates`] so their
+    /// bboxes no longer overlap before [`Self::analyze_offsets`] averages
+    /// positions per odd/even group. `expected_shift` only needs to be
+    /// approximately right, since it's merely scoring digit consistency, not
+    /// the final shift used for matching. Pages with any other candidate
+    /// count (0, 1, or 3+) are passed through unchanged.
+    fn resolve_overlapping_detections(
+        detections: &[DetectedPageNumber],
+        expected_shift: i32,
+    ) -> Vec<DetectedPageNumber> {
+        let mut by_page: HashMap<usize, Vec<&DetectedPageNumber>> = HashMap::new();
+        for det in detections {
+            by_page.entry(det.page_index).or_default().push(det);
+        }
+
+        let mut resolved = Vec::with_capacity(detections.len());
+        for dets in by_page.values() {
+            if dets.len() == 2 {
+                let expected_number = (dets[0].page_index + 1) as i32 - expected_shift;
+                let (left, right) = resolve_overlapping_candidates(dets[0], dets[1], expected_number);
+                resolved.push(left);
+                resolved.push(right);
+            } else {
+                resolved.extend(dets.iter().map(|det| (*det).clone()));
+            }
+        }
+        resolved
+    }
+
     /// Find the best physical-to-logical page number shift
     ///
-    /// Tests shifts from -MAX_SHIFT_TEST to +MAX_SHIFT_TEST and returns
-    /// the shift that maximizes the number of matches weighted by confidence.
+    /// Rather than brute-force testing every shift in a fixed window (which
+    /// costs O(window × N) and can silently miss a legitimate offset outside
+    /// the window), each detection with a plausible `number` casts a single
+    /// confidence-weighted vote for the shift it implies
+    /// (`physical_page - number`), accumulated in a `shift -> (score, count)`
+    /// map. The shift with the highest accumulated score wins, ties broken
+    /// by `shift.abs()` exactly as before. This is O(N) and has no window to
+    /// fall outside of.
     fn find_best_page_number_shift(detections: &[DetectedPageNumber]) -> (i32, usize, f64) {
-        let mut best_shift = 0i32;
-        let mut best_score = 0.0f64;
-        let mut best_count = 0usize;
+        let mut votes: HashMap<i32, (f64, usize)> = HashMap::new();
 
-        for shift in -MAX_SHIFT_TEST..MAX_SHIFT_TEST {
-            let mut score = 0.0f64;
-            let mut count = 0usize;
+        for det in detections {
+            let Some(number) = det.number else {
+                continue;
+            };
+            if number < 1 {
+                continue;
+            }
 
-            for det in detections {
-                let physical_page = det.page_index + 1;
-                let expected_logical = physical_page as i32 - shift;
+            let physical_page = det.page_index + 1;
+            let candidate = physical_page as i32 - number;
+            let entry = votes.entry(candidate).or_insert((0.0, 0));
+            entry.0 += det.confidence as f64;
+            entry.1 += 1;
+        }
 
-                if expected_logical >= 1 && det.number == Some(expected_logical) {
-                    score += det.confidence as f64;
-                    count += 1;
-                }
-            }
+        let mut best_shift = 0i32;
+        let mut best_score = 0.0f64;
+        let mut best_count = 0usize;
 
-            if score > best_score || (score == best_score && shift.abs() < best_shift.abs()) {
-                best_score = score;
-                best_shift = shift;
-                best_count = count;
+        for (shift, (score, count)) in &votes {
+            if *score > best_score || (*score == best_score && shift.abs() < best_shift.abs()) {
+                best_score = *score;
+                best_shift = *shift;
+                best_count = *count;
             }
         }
 
@@ -409,6 +837,95 @@ impl PageOffsetAnalyzer {
         (best_shift, best_count, confidence)
     }
 
+    /// Partition detections into contiguous physical-page segments that
+    /// each share a single constant shift, via DP segmentation (the same
+    /// cost-minimization shape as line/page-breaking): sorted by physical
+    /// page, `seg_shift(l, r)` is the mode of `physical_page - number` among
+    /// segment `[l, r]`'s usable detections (those with `number >= 1`) and
+    /// `seg_score(l, r)` is the confidence-weighted count agreeing with
+    /// that mode. `dp[r] = max over l (dp[l-1] + seg_score(l, r) -
+    /// SEGMENT_SPLIT_PENALTY)`; backtracking the `l` chosen at each `r`
+    /// recovers the segment boundaries. Returns physical-page ranges (first
+    /// to last detection's physical page + 1, per segment) paired with
+    /// their shift; a single-element result means the book didn't benefit
+    /// from splitting and the caller should fall back to the global shift.
+    fn segment_shifts(detections: &[DetectedPageNumber]) -> Vec<(Range<usize>, i32)> {
+        let mut sorted: Vec<&DetectedPageNumber> = detections.iter().collect();
+        sorted.sort_by_key(|det| det.page_index);
+        let n = sorted.len();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        // dp[r+1] is the best achievable score using sorted[0..=r]; back[r+1]
+        // records the start index of the final segment that achieved it, and
+        // seg_of[r+1] the shift that segment voted for.
+        let mut dp = vec![f64::MIN; n + 1];
+        let mut back = vec![0usize; n + 1];
+        let mut seg_of = vec![0i32; n + 1];
+        dp[0] = 0.0;
+
+        for l in 0..n {
+            if dp[l] <= f64::MIN {
+                continue;
+            }
+
+            // Incrementally maintained vote tally for segment [l, r] as r
+            // grows, so the whole DP stays O(n^2) rather than O(n^3).
+            let mut votes: HashMap<i32, (f64, usize)> = HashMap::new();
+            let mut best_shift = 0i32;
+            let mut best_count = 0usize;
+            let mut best_score = 0.0f64;
+
+            for (r, det) in sorted.iter().enumerate().skip(l) {
+                let physical_page = det.page_index + 1;
+                if let Some(number) = det.number.filter(|&n| n >= 1) {
+                    let candidate = physical_page as i32 - number;
+                    let entry = votes.entry(candidate).or_insert((0.0, 0));
+                    entry.0 += det.confidence as f64;
+                    entry.1 += 1;
+                    let (score, count) = *entry;
+
+                    if candidate == best_shift {
+                        best_count = count;
+                        best_score = score;
+                    } else if count > best_count
+                        || (count == best_count && candidate.abs() < best_shift.abs())
+                    {
+                        best_shift = candidate;
+                        best_count = count;
+                        best_score = score;
+                    }
+                }
+
+                let candidate_dp = dp[l] + best_score - SEGMENT_SPLIT_PENALTY;
+                if candidate_dp > dp[r + 1] {
+                    dp[r + 1] = candidate_dp;
+                    back[r + 1] = l;
+                    seg_of[r + 1] = best_shift;
+                }
+            }
+        }
+
+        let mut bounds: Vec<(usize, usize, i32)> = Vec::new();
+        let mut r = n;
+        while r > 0 {
+            let l = back[r];
+            bounds.push((l, r, seg_of[r]));
+            r = l;
+        }
+        bounds.reverse();
+
+        bounds
+            .into_iter()
+            .map(|(l, r, shift)| {
+                let first_physical = sorted[l].page_index + 1;
+                let last_physical = sorted[r - 1].page_index + 1;
+                (first_physical..(last_physical + 1), shift)
+            })
+            .collect()
+    }
+
     /// Align Y values between odd and even groups if they're close
     fn align_group_y_values(
         odd_avg_y: Option<i32>,
@@ -430,10 +947,19 @@ impl PageOffsetAnalyzer {
         }
     }
 
-    /// Calculate per-page offsets based on averages
+    /// Calculate per-page offsets based on averages. `segments`, if
+    /// non-empty, overrides `shift` for any physical page it covers, so a
+    /// book with piecewise numbering (Roman front matter, then Arabic body)
+    /// matches each page against the shift for its own section rather than
+    /// a single global one. A page whose Arabic shift doesn't match (most
+    /// often because it's outside the Arabic scheme entirely) falls back
+    /// to `numbering_map`, so Roman-numeral or lettered pages still get a
+    /// correct `logical_page` instead of being dropped to [`PageOffsetResult::no_offset`].
     fn calculate_per_page_offsets(
         detections: &[DetectedPageNumber],
         shift: i32,
+        segments: &[(Range<usize>, i32)],
+        numbering_map: &PageNumberingMap,
         odd_avg_x: Option<i32>,
         even_avg_x: Option<i32>,
         odd_avg_y: Option<i32>,
@@ -444,6 +970,7 @@ impl PageOffsetAnalyzer {
             .map(|det| {
                 let physical_page = det.page_index + 1;
                 let is_odd = physical_page % 2 == 1;
+                let shift = shift_for_physical_page(segments, physical_page, shift);
                 let expected_logical = physical_page as i32 - shift;
 
                 // Check if this page's detected number matches the expected
@@ -468,6 +995,13 @@ impl PageOffsetAnalyzer {
                         shift_y,
                         page_number_position: Some(det.position),
                         is_odd,
+                        was_interpolated: false,
+                        confidence: det.confidence as f64,
+                    }
+                } else if let Ok((_, logical)) = numbering_map.lookup(physical_page) {
+                    PageOffsetResult {
+                        logical_page: Some(logical),
+                        ..PageOffsetResult::no_offset(physical_page)
                     }
                 } else {
                     PageOffsetResult::no_offset(physical_page)
@@ -476,8 +1010,14 @@ impl PageOffsetAnalyzer {
             .collect()
     }
 
-    /// Create offset results for pages without page number detection
-    /// using group averages for alignment
+    /// Create offset results for pages without page number detection by
+    /// fitting a least-squares line `shift = a*page + b` to each parity
+    /// group's matched `(physical_page, shift)` samples, separately for the
+    /// X and Y axes, and evaluating it at the missing page (clamped to the
+    /// observed sample range so a page far outside the matched pages isn't
+    /// wildly extrapolated). Groups with fewer than two matched samples
+    /// fall back to that lone sample's shift (or leave the page unaligned
+    /// when the group has no matches at all, same as before this existed).
     pub fn interpolate_missing_offsets(analysis: &mut BookOffsetAnalysis, total_pages: usize) {
         // Find pages that don't have offsets
         let existing: HashSet<usize> = analysis
@@ -486,18 +1026,221 @@ impl PageOffsetAnalyzer {
             .map(|p| p.physical_page)
             .collect();
 
+        let mut odd_x: Vec<(f64, f64)> = Vec::new();
+        let mut odd_y: Vec<(f64, f64)> = Vec::new();
+        let mut even_x: Vec<(f64, f64)> = Vec::new();
+        let mut even_y: Vec<(f64, f64)> = Vec::new();
+
+        for result in &analysis.page_offsets {
+            if result.page_number_position.is_none() {
+                continue;
+            }
+            let page = result.physical_page as f64;
+            let (samples_x, samples_y) = if result.is_odd {
+                (&mut odd_x, &mut odd_y)
+            } else {
+                (&mut even_x, &mut even_y)
+            };
+            samples_x.push((page, result.shift_x as f64));
+            samples_y.push((page, result.shift_y as f64));
+        }
+
         for page in 1..=total_pages {
-            if !existing.contains(&page) {
-                // Add a no-offset entry for missing pages
-                analysis
-                    .page_offsets
-                    .push(PageOffsetResult::no_offset(page));
+            if existing.contains(&page) {
+                continue;
             }
+
+            let is_odd = page % 2 == 1;
+            let (samples_x, samples_y) = if is_odd { (&odd_x, &odd_y) } else { (&even_x, &even_y) };
+            let page_f = page as f64;
+
+            let result = match (
+                Self::predict_shift(samples_x, page_f),
+                Self::predict_shift(samples_y, page_f),
+            ) {
+                (Some(shift_x), Some(shift_y)) => PageOffsetResult {
+                    physical_page: page,
+                    logical_page: Some(page as i32 - analysis.shift_for_page(page)),
+                    shift_x,
+                    shift_y,
+                    page_number_position: None,
+                    is_odd,
+                    was_interpolated: true,
+                    confidence: 0.0,
+                },
+                _ => PageOffsetResult::no_offset(page),
+            };
+
+            analysis.page_offsets.push(result);
         }
 
         // Sort by physical page
         analysis.page_offsets.sort_by_key(|p| p.physical_page);
     }
+
+    /// Predict the shift at `page` from `samples`' `(physical_page, shift)`
+    /// pairs: a least-squares line fit evaluated at `page` clamped to the
+    /// samples' own page range, the lone sample's shift when there's only
+    /// one, or `None` when there are no samples at all.
+    fn predict_shift(samples: &[(f64, f64)], page: f64) -> Option<i32> {
+        if samples.is_empty() {
+            return None;
+        }
+        if samples.len() == 1 {
+            return Some(samples[0].1.round() as i32);
+        }
+
+        let min_page = samples.iter().map(|(x, _)| *x).fold(f64::INFINITY, f64::min);
+        let max_page = samples.iter().map(|(x, _)| *x).fold(f64::NEG_INFINITY, f64::max);
+        let clamped_page = page.clamp(min_page, max_page);
+
+        let predicted = match Self::fit_line(samples) {
+            Some((a, b)) => a * clamped_page + b,
+            // Degenerate fit (e.g. duplicate page samples): fall back to
+            // the plain mean, same as the single-sample case.
+            None => samples.iter().map(|(_, y)| y).sum::<f64>() / samples.len() as f64,
+        };
+        Some(predicted.round() as i32)
+    }
+
+    /// Least-squares slope/intercept for `shift = a*page + b` over
+    /// `samples`, or `None` when there are fewer than two distinct pages to
+    /// fit a line through.
+    fn fit_line(samples: &[(f64, f64)]) -> Option<(f64, f64)> {
+        let n = samples.len() as f64;
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let sum_x: f64 = samples.iter().map(|(x, _)| x).sum();
+        let sum_y: f64 = samples.iter().map(|(_, y)| y).sum();
+        let sum_xy: f64 = samples.iter().map(|(x, y)| x * y).sum();
+        let sum_xx: f64 = samples.iter().map(|(x, _)| x * x).sum();
+
+        let denom = n * sum_xx - sum_x * sum_x;
+        if denom.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let a = (n * sum_xy - sum_x * sum_y) / denom;
+        let b = (sum_y - a * sum_x) / n;
+        Some((a, b))
+    }
+
+    /// Smooth `analysis.page_offsets`' `shift_x`/`shift_y` to damp per-page
+    /// OCR jitter, modeled as a spring/elastic system: each page `i` is
+    /// pulled toward its own measured shift `mᵢ` with stiffness `wᵢ` (its
+    /// [`PageOffsetResult::confidence`], `0.0` for interpolated/unmatched
+    /// pages so they're only pulled by their neighbors) and toward its
+    /// immediate neighbors with stiffness `lambda`. Minimizing
+    /// `Σ wᵢ(xᵢ − mᵢ)² + λ Σ(xᵢ − xᵢ₋₁)²` and differentiating gives the
+    /// symmetric tridiagonal system solved by [`Self::solve_spring_system`].
+    /// Run independently per axis and per odd/even parity group, since those
+    /// groups' page-number positions are already analyzed separately
+    /// throughout this module. An optional pass: call it after
+    /// [`Self::interpolate_missing_offsets`] to tighten alignment across a
+    /// chapter, or skip it to keep today's per-page shifts as-is.
+    pub fn smooth_shifts(analysis: &mut BookOffsetAnalysis, lambda: f64) {
+        Self::smooth_group(&mut analysis.page_offsets, true, lambda);
+        Self::smooth_group(&mut analysis.page_offsets, false, lambda);
+    }
+
+    /// Smooth the `shift_x`/`shift_y` of `page_offsets` entries with
+    /// `is_odd == is_odd_group`, in physical-page order, writing the
+    /// smoothed values back in place.
+    fn smooth_group(page_offsets: &mut [PageOffsetResult], is_odd_group: bool, lambda: f64) {
+        let mut indices: Vec<usize> = page_offsets
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.is_odd == is_odd_group)
+            .map(|(i, _)| i)
+            .collect();
+        indices.sort_by_key(|&i| page_offsets[i].physical_page);
+
+        if indices.len() < 2 {
+            return;
+        }
+
+        let weights: Vec<f64> = indices.iter().map(|&i| page_offsets[i].confidence).collect();
+        let xs: Vec<f64> = indices
+            .iter()
+            .map(|&i| page_offsets[i].shift_x as f64)
+            .collect();
+        let ys: Vec<f64> = indices
+            .iter()
+            .map(|&i| page_offsets[i].shift_y as f64)
+            .collect();
+
+        let smoothed_x = Self::solve_spring_system(&xs, &weights, lambda);
+        let smoothed_y = Self::solve_spring_system(&ys, &weights, lambda);
+
+        for (pos, &i) in indices.iter().enumerate() {
+            page_offsets[i].shift_x = smoothed_x[pos].round() as i32;
+            page_offsets[i].shift_y = smoothed_y[pos].round() as i32;
+        }
+    }
+
+    /// Solve the spring-smoothing tridiagonal system for one axis: page `i`'s
+    /// smoothed value `xᵢ` satisfies
+    /// `(wᵢ + λ·degᵢ)xᵢ − λxᵢ₋₁ − λxᵢ₊₁ = wᵢmᵢ`, where `degᵢ` (the number of
+    /// existing neighbors) is 1 at the ends of the sequence and 2 in the
+    /// middle. Solved in O(n) via the Thomas algorithm, which is exact and
+    /// stable here because the matrix is symmetric and diagonally dominant
+    /// whenever any `wᵢ > 0`.
+    fn solve_spring_system(measurements: &[f64], weights: &[f64], lambda: f64) -> Vec<f64> {
+        let n = measurements.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        if n == 1 {
+            return vec![measurements[0]];
+        }
+
+        let mut sub = vec![0.0; n];
+        let mut diag = vec![0.0; n];
+        let mut sup = vec![0.0; n];
+        let mut rhs = vec![0.0; n];
+
+        for i in 0..n {
+            let degree = if i == 0 || i == n - 1 { 1.0 } else { 2.0 };
+            diag[i] = weights[i] + lambda * degree;
+            rhs[i] = weights[i] * measurements[i];
+            if i > 0 {
+                sub[i] = -lambda;
+            }
+            if i < n - 1 {
+                sup[i] = -lambda;
+            }
+        }
+
+        Self::thomas_algorithm(&sub, &diag, &sup, &rhs)
+    }
+
+    /// Classic Thomas algorithm (forward elimination then back-substitution)
+    /// for a symmetric tridiagonal system: `sub[i]`/`sup[i]` are the
+    /// off-diagonal coefficients multiplying `x[i-1]`/`x[i+1]`, `diag[i]`
+    /// the coefficient multiplying `x[i]`, and `rhs[i]` the right-hand side.
+    fn thomas_algorithm(sub: &[f64], diag: &[f64], sup: &[f64], rhs: &[f64]) -> Vec<f64> {
+        let n = diag.len();
+        let mut c_prime = vec![0.0; n];
+        let mut d_prime = vec![0.0; n];
+
+        c_prime[0] = sup[0] / diag[0];
+        d_prime[0] = rhs[0] / diag[0];
+
+        for i in 1..n {
+            let denom = diag[i] - sub[i] * c_prime[i - 1];
+            c_prime[i] = if i < n - 1 { sup[i] / denom } else { 0.0 };
+            d_prime[i] = (rhs[i] - sub[i] * d_prime[i - 1]) / denom;
+        }
+
+        let mut x = vec![0.0; n];
+        x[n - 1] = d_prime[n - 1];
+        for i in (0..n - 1).rev() {
+            x[i] = d_prime[i] - c_prime[i] * x[i + 1];
+        }
+        x
+    }
 }
 
 #[cfg(test)]
@@ -619,6 +1362,7 @@ mod tests {
                 },
                 confidence: 0.9,
                 raw_text: "1".to_string(),
+                label: None,
             },
             DetectedPageNumber {
                 page_index: 1,
@@ -631,6 +1375,7 @@ mod tests {
                 },
                 confidence: 0.9,
                 raw_text: "2".to_string(),
+                label: None,
             },
             DetectedPageNumber {
                 page_index: 2,
@@ -643,6 +1388,7 @@ mod tests {
                 },
                 confidence: 0.9,
                 raw_text: "3".to_string(),
+                label: None,
             },
         ];
 
@@ -673,6 +1419,8 @@ mod tests {
                         height: 20,
                     }),
                     is_odd: true,
+                    was_interpolated: false,
+                    confidence: 0.9,
                 },
                 // Page 2 is missing
                 PageOffsetResult {
@@ -687,6 +1435,8 @@ mod tests {
                         height: 20,
                     }),
                     is_odd: true,
+                    was_interpolated: false,
+                    confidence: 0.9,
                 },
             ],
             page_number_shift: 0,
@@ -696,11 +1446,13 @@ mod tests {
             even_avg_y: Some(50),
             match_count: 2,
             confidence: 0.8,
+            ..Default::default()
         };
 
         PageOffsetAnalyzer::interpolate_missing_offsets(&mut analysis, 3);
 
-        // After interpolation, page 2 should be present
+        // After interpolation, page 2 should be present. It is an even page
+        // and both matched samples are odd pages, so it is left unaligned.
         assert_eq!(analysis.page_offsets.len(), 3);
         let page2 = analysis.get_offset(2);
         assert!(page2.is_some());
@@ -717,6 +1469,8 @@ mod tests {
             shift_y: 0,
             page_number_position: None,
             is_odd: true,
+            was_interpolated: false,
+            confidence: 0.0,
         };
 
         assert_eq!(result.physical_page, 5);
@@ -734,6 +1488,8 @@ mod tests {
             shift_y: 0,
             page_number_position: None,
             is_odd: true,
+            was_interpolated: false,
+            confidence: 0.0,
         };
 
         assert!(result.logical_page.is_none());
@@ -762,6 +1518,7 @@ mod tests {
                 }, // Odd: left
                 confidence: 0.9,
                 raw_text: "1".to_string(),
+                label: None,
             },
             DetectedPageNumber {
                 page_index: 1,
@@ -774,6 +1531,7 @@ mod tests {
                 }, // Even: right
                 confidence: 0.9,
                 raw_text: "2".to_string(),
+                label: None,
             },
             DetectedPageNumber {
                 page_index: 2,
@@ -786,6 +1544,7 @@ mod tests {
                 }, // Odd: left
                 confidence: 0.9,
                 raw_text: "3".to_string(),
+                label: None,
             },
             DetectedPageNumber {
                 page_index: 3,
@@ -798,6 +1557,7 @@ mod tests {
                 }, // Even: right
                 confidence: 0.9,
                 raw_text: "4".to_string(),
+                label: None,
             },
         ];
 
@@ -809,6 +1569,48 @@ mod tests {
         // The actual test verifies the structure supports this
     }
 
+    #[test]
+    fn test_tc_pagenum_005_odd_even_separate_offsets_is_order_independent() {
+        use crate::page_number::types::{DetectedPageNumber, PageNumberRect};
+
+        // Same detections as TC-PAGENUM-005, but fed in reverse order. The
+        // equal-area bboxes within each odd/even group previously let
+        // `calc_group_reference_position_robust`'s area-sort tie-break
+        // depend on whatever order the caller happened to supply detections
+        // in; sorting by `reading_order_cmp` first makes the result the same
+        // either way.
+        let detection = |page_index, number, x, y| DetectedPageNumber {
+            page_index,
+            number: Some(number),
+            position: PageNumberRect {
+                x,
+                y,
+                width: 50,
+                height: 20,
+            },
+            confidence: 0.9,
+            raw_text: number.to_string(),
+            label: None,
+        };
+
+        let forward = vec![
+            detection(0, 1, 100, 50),
+            detection(1, 2, 900, 50),
+            detection(2, 3, 105, 52),
+            detection(3, 4, 895, 48),
+        ];
+        let mut reversed = forward.clone();
+        reversed.reverse();
+
+        let forward_analysis = PageOffsetAnalyzer::analyze_offsets(&forward, 1000);
+        let reversed_analysis = PageOffsetAnalyzer::analyze_offsets(&reversed, 1000);
+
+        assert_eq!(forward_analysis.odd_avg_x, reversed_analysis.odd_avg_x);
+        assert_eq!(forward_analysis.odd_avg_y, reversed_analysis.odd_avg_y);
+        assert_eq!(forward_analysis.even_avg_x, reversed_analysis.even_avg_x);
+        assert_eq!(forward_analysis.even_avg_y, reversed_analysis.even_avg_y);
+    }
+
     // ============================================================
     // Phase 2.2: Group-Based Reference Position Tests
     // ============================================================
@@ -876,6 +1678,93 @@ mod tests {
         assert!(center.y >= 0 && center.y <= 50);
     }
 
+    #[test]
+    fn test_calc_overlap_center_robust_rejects_single_outlier() {
+        // Five tightly-clustered bboxes plus one wild outlier (e.g. a
+        // footnote marker misread as a page number); the robust estimator
+        // should reject the outlier and land on the cluster's center.
+        let mut bboxes: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new(500 + i, 100 + i, 50, 20))
+            .collect();
+        bboxes.push(Rectangle::new(2000, 2000, 50, 20));
+
+        let (center, inliers) = calc_overlap_center_robust(&bboxes);
+
+        assert_eq!(inliers, 5);
+        assert!(center.x < 1000, "outlier should be rejected, got x={}", center.x);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_robust_no_outliers_matches_plain_version() {
+        let bboxes: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new(500 + i, 100 + i, 50, 20))
+            .collect();
+
+        let (robust_center, inliers) = calc_overlap_center_robust(&bboxes);
+        let plain_center = calc_overlap_center(&bboxes);
+
+        assert_eq!(inliers, 5);
+        assert_eq!(robust_center, plain_center);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_robust_never_drops_below_two_inliers() {
+        // Every point is mutually "far" from the others once there are only
+        // a few of them; the floor of two inliers must still hold.
+        let bboxes = vec![
+            Rectangle::new(0, 0, 10, 10),
+            Rectangle::new(1000, 0, 10, 10),
+            Rectangle::new(0, 1000, 10, 10),
+        ];
+
+        let (_, inliers) = calc_overlap_center_robust(&bboxes);
+        assert!(inliers >= MIN_INLIERS);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_robust_two_or_fewer_bboxes_unchanged() {
+        let bboxes = vec![Rectangle::new(0, 0, 10, 10), Rectangle::new(1000, 1000, 10, 10)];
+
+        let (robust_center, inliers) = calc_overlap_center_robust(&bboxes);
+        let plain_center = calc_overlap_center(&bboxes);
+
+        assert_eq!(inliers, 2);
+        assert_eq!(robust_center, plain_center);
+    }
+
+    #[test]
+    fn test_calc_group_reference_position_robust_reports_rejected_outlier() {
+        let mut positions: Vec<(usize, PageNumberRect)> = (0..5)
+            .map(|i| {
+                (
+                    2 * i + 1,
+                    PageNumberRect {
+                        x: 100 + i as u32,
+                        y: 900 + i as u32,
+                        width: 50,
+                        height: 30,
+                    },
+                )
+            })
+            .collect();
+        // A wild outlier on an odd page.
+        positions.push((
+            11,
+            PageNumberRect {
+                x: 4000,
+                y: 4000,
+                width: 50,
+                height: 30,
+            },
+        ));
+
+        let (point, inliers, total) = calc_group_reference_position_robust(&positions, true);
+
+        assert_eq!(total, 6);
+        assert_eq!(inliers, 5);
+        assert!(point.x < 1000);
+    }
+
     #[test]
     fn test_calc_group_reference_odd_pages() {
         let positions = vec![
@@ -997,4 +1886,561 @@ mod tests {
         assert!((center.x - expected_x).abs() <= 20, "X deviation too large");
         assert!((center.y - expected_y).abs() <= 20, "Y deviation too large");
     }
+
+    // The brute-force search used to only test shifts in -300..300; the
+    // confidence-weighted vote has no such window, so a front-matter-heavy
+    // book with a shift well outside that range must still be detected.
+    #[test]
+    fn test_find_best_page_number_shift_beyond_old_window() {
+        use crate::page_number::types::{DetectedPageNumber, PageNumberRect};
+
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        let detections: Vec<DetectedPageNumber> = (0..6)
+            .map(|i| DetectedPageNumber {
+                page_index: 400 + i,
+                number: Some((i + 1) as i32),
+                position,
+                confidence: 0.9,
+                raw_text: (i + 1).to_string(),
+                label: None,
+            })
+            .collect();
+
+        let analysis = PageOffsetAnalyzer::analyze_offsets(&detections, 1000);
+
+        assert_eq!(analysis.page_number_shift, 400);
+        assert_eq!(analysis.match_count, 6);
+    }
+
+    #[test]
+    fn test_find_best_page_number_shift_ties_prefer_smaller_magnitude() {
+        use crate::page_number::types::{DetectedPageNumber, PageNumberRect};
+
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        // Two equally-confident single-vote candidates: shift 0 (page 1 ==
+        // number 1) and shift 2 (page 3 == number 1). The tie must resolve
+        // to the smaller-magnitude shift, exactly as the old loop did.
+        let detections = vec![
+            DetectedPageNumber {
+                page_index: 0,
+                number: Some(1),
+                position,
+                confidence: 0.9,
+                raw_text: "1".to_string(),
+                label: None,
+            },
+            DetectedPageNumber {
+                page_index: 2,
+                number: Some(1),
+                position,
+                confidence: 0.9,
+                raw_text: "1".to_string(),
+                label: None,
+            },
+        ];
+
+        let (shift, count, _) = PageOffsetAnalyzer::find_best_page_number_shift(&detections);
+        assert_eq!(shift, 0);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_find_best_page_number_shift_ignores_non_positive_logical_numbers() {
+        use crate::page_number::types::{DetectedPageNumber, PageNumberRect};
+
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        let detections = vec![DetectedPageNumber {
+            page_index: 0,
+            number: Some(0),
+            position,
+            confidence: 0.9,
+            raw_text: "0".to_string(),
+            label: None,
+        }];
+
+        let (shift, count, confidence) =
+            PageOffsetAnalyzer::find_best_page_number_shift(&detections);
+        assert_eq!(shift, 0);
+        assert_eq!(count, 0);
+        assert_eq!(confidence, 0.0);
+    }
+
+    // ============================================================
+    // Phase 2.3: Overlapping Candidate Resolution Tests
+    // ============================================================
+
+    fn overlap_test_detection(x: u32, width: u32, raw_text: &str) -> DetectedPageNumber {
+        DetectedPageNumber {
+            page_index: 0,
+            number: raw_text.parse().ok(),
+            position: PageNumberRect {
+                x,
+                y: 900,
+                width,
+                height: 20,
+            },
+            confidence: 0.9,
+            raw_text: raw_text.to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_overlapping_candidates_no_overlap_returns_unchanged() {
+        let left = overlap_test_detection(100, 40, "42");
+        let right = overlap_test_detection(200, 40, "42");
+
+        let (resolved_left, resolved_right) = resolve_overlapping_candidates(&left, &right, 42);
+
+        assert_eq!(resolved_left.position.x, left.position.x);
+        assert_eq!(resolved_left.position.width, left.position.width);
+        assert_eq!(resolved_right.position.x, right.position.x);
+        assert_eq!(resolved_right.position.width, right.position.width);
+    }
+
+    #[test]
+    fn test_resolve_overlapping_candidates_favors_side_matching_expected_digits() {
+        // "42" (matches the expected page number) bleeds into a "99" ghost
+        // from the facing leaf; their boxes overlap by 20 pixels.
+        let left = overlap_test_detection(100, 50, "99");
+        let right = overlap_test_detection(130, 50, "42");
+
+        let (resolved_left, resolved_right) = resolve_overlapping_candidates(&left, &right, 42);
+
+        // The real "42" should win the entire overlap, so the ghost's trimmed
+        // width shrinks while "42"'s stays at its full original span.
+        let left_end = resolved_left.position.x + resolved_left.position.width;
+        assert!(left_end <= right.position.x);
+        assert_eq!(resolved_right.position.x, right.position.x);
+        assert_eq!(resolved_right.position.width, right.position.width);
+    }
+
+    #[test]
+    fn test_resolve_overlapping_candidates_never_widens_a_candidate() {
+        let left = overlap_test_detection(100, 60, "7");
+        let right = overlap_test_detection(140, 60, "8");
+
+        let (resolved_left, resolved_right) = resolve_overlapping_candidates(&left, &right, 7);
+
+        assert!(resolved_left.position.width <= left.position.width);
+        assert!(resolved_right.position.width <= right.position.width);
+        assert!(resolved_left.position.x >= left.position.x);
+    }
+
+    #[test]
+    fn test_resolve_overlapping_candidates_preserves_text_and_confidence() {
+        let left = overlap_test_detection(100, 50, "12");
+        let right = overlap_test_detection(130, 50, "99");
+
+        let (resolved_left, resolved_right) = resolve_overlapping_candidates(&left, &right, 12);
+
+        assert_eq!(resolved_left.raw_text, "12");
+        assert_eq!(resolved_left.confidence, left.confidence);
+        assert_eq!(resolved_right.raw_text, "99");
+        assert_eq!(resolved_right.confidence, right.confidence);
+    }
+
+    // ============================================================
+    // Piecewise (segmented) shift tests
+    // ============================================================
+
+    fn segment_test_detection(
+        page_index: usize,
+        number: i32,
+        position: PageNumberRect,
+    ) -> crate::page_number::types::DetectedPageNumber {
+        crate::page_number::types::DetectedPageNumber {
+            page_index,
+            number: Some(number),
+            position,
+            confidence: 0.9,
+            raw_text: number.to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_segment_shifts_splits_front_matter_from_body() {
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+
+        // Physical pages 1-5: front matter numbered 1-5 with shift 0.
+        // Physical pages 6-10: body restarts at 1, so shift 5.
+        let mut detections: Vec<_> = (0..5)
+            .map(|i| segment_test_detection(i, (i + 1) as i32, position))
+            .collect();
+        detections.extend((5..10).map(|i| segment_test_detection(i, (i - 4) as i32, position)));
+
+        let segments = PageOffsetAnalyzer::segment_shifts(&detections);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0], (1..6, 0));
+        assert_eq!(segments[1], (6..11, 5));
+    }
+
+    #[test]
+    fn test_segment_shifts_single_segment_for_sequential_numbering() {
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        let detections: Vec<_> = (0..10)
+            .map(|i| segment_test_detection(i, (i + 1) as i32, position))
+            .collect();
+
+        let segments = PageOffsetAnalyzer::segment_shifts(&detections);
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0], (1..11, 0));
+    }
+
+    #[test]
+    fn test_analyze_offsets_falls_back_to_global_shift_for_single_segment() {
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        let detections: Vec<_> = (0..10)
+            .map(|i| segment_test_detection(i, (i + 1) as i32, position))
+            .collect();
+
+        let analysis = PageOffsetAnalyzer::analyze_offsets(&detections, 1000);
+
+        assert!(analysis.page_number_segments.is_empty());
+        assert_eq!(analysis.page_number_shift, 0);
+        assert_eq!(analysis.shift_for_page(3), 0);
+    }
+
+    #[test]
+    fn test_analyze_offsets_applies_piecewise_shift_to_each_section() {
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+        let mut detections: Vec<_> = (0..6)
+            .map(|i| segment_test_detection(i, (i + 1) as i32, position))
+            .collect();
+        detections.extend((6..12).map(|i| segment_test_detection(i, (i - 5) as i32, position)));
+
+        let analysis = PageOffsetAnalyzer::analyze_offsets(&detections, 1000);
+
+        assert_eq!(analysis.page_number_segments.len(), 2);
+        // Front matter: physical page 3 (number 3) -> logical 3 under shift 0.
+        let front = analysis.get_offset(3).unwrap();
+        assert_eq!(front.logical_page, Some(3));
+        // Body: physical page 9 (number 3, restarted) -> logical 3 under shift 6.
+        let body = analysis.get_offset(9).unwrap();
+        assert_eq!(body.logical_page, Some(3));
+        assert_eq!(analysis.shift_for_page(9), 6);
+    }
+
+    #[test]
+    fn test_analyze_offsets_labels_roman_front_matter_via_numbering_map() {
+        use crate::page_number::types::{PageLabel, PageLabelStyle};
+
+        let position = PageNumberRect {
+            x: 500,
+            y: 100,
+            width: 50,
+            height: 20,
+        };
+
+        // Roman-numeral front matter (pages 1-3, "i".."iii") has no
+        // `det.number` at all, so the Arabic shift vote never matches it;
+        // it must still get a logical page from `PageNumberingMap` instead
+        // of being dropped. The Arabic body (pages 4-9, numbers 1-6) drives
+        // the shift vote as usual.
+        let mut detections: Vec<_> = (0..3)
+            .map(|i| crate::page_number::types::DetectedPageNumber {
+                page_index: i,
+                number: None,
+                position,
+                confidence: 0.9,
+                raw_text: format!("roman-{}", i + 1),
+                label: Some(PageLabel {
+                    style: PageLabelStyle::RomanLower,
+                    ordinal: i as u32 + 1,
+                    prefix: None,
+                }),
+            })
+            .collect();
+        detections.extend((3..9).map(|i| segment_test_detection(i, (i - 2) as i32, position)));
+
+        let analysis = PageOffsetAnalyzer::analyze_offsets(&detections, 1000);
+
+        let front = analysis.get_offset(2).unwrap();
+        assert_eq!(front.logical_page, Some(2));
+        let body = analysis.get_offset(5).unwrap();
+        assert_eq!(body.logical_page, Some(2));
+    }
+
+    // ============================================================
+    // Regression-based interpolation tests
+    // ============================================================
+
+    fn matched_offset_result(physical_page: usize, shift_x: i32, shift_y: i32) -> PageOffsetResult {
+        PageOffsetResult {
+            physical_page,
+            logical_page: Some(physical_page as i32),
+            shift_x,
+            shift_y,
+            page_number_position: Some(PageNumberRect {
+                x: 100,
+                y: 50,
+                width: 30,
+                height: 20,
+            }),
+            is_odd: physical_page % 2 == 1,
+            was_interpolated: false,
+            confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_interpolate_missing_offsets_fits_line_through_matched_samples() {
+        // Odd pages 1, 3, 5, 7, 9 drift by +2 shift_x per page; page 5 is missing.
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![
+                matched_offset_result(1, 10, 0),
+                matched_offset_result(3, 14, 0),
+                matched_offset_result(7, 22, 0),
+                matched_offset_result(9, 26, 0),
+            ],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::interpolate_missing_offsets(&mut analysis, 9);
+
+        let page5 = analysis.get_offset(5).unwrap();
+        assert_eq!(page5.shift_x, 18);
+        assert!(page5.was_interpolated);
+        assert_eq!(page5.logical_page, Some(5));
+    }
+
+    #[test]
+    fn test_interpolate_missing_offsets_clamps_to_observed_range() {
+        // Matched samples only span pages 1-5; page 9 is far outside that
+        // range and must be clamped to the fit at page 5, not extrapolated.
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![matched_offset_result(1, 10, 0), matched_offset_result(5, 50, 0)],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::interpolate_missing_offsets(&mut analysis, 9);
+
+        let page9 = analysis.get_offset(9).unwrap();
+        assert_eq!(page9.shift_x, 50);
+    }
+
+    #[test]
+    fn test_interpolate_missing_offsets_single_sample_group_uses_that_shift() {
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![matched_offset_result(1, 12, 4)],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::interpolate_missing_offsets(&mut analysis, 3);
+
+        let page3 = analysis.get_offset(3).unwrap();
+        assert_eq!(page3.shift_x, 12);
+        assert_eq!(page3.shift_y, 4);
+        assert!(page3.was_interpolated);
+    }
+
+    #[test]
+    fn test_interpolate_missing_offsets_no_samples_leaves_page_unaligned() {
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::interpolate_missing_offsets(&mut analysis, 2);
+
+        let page1 = analysis.get_offset(1).unwrap();
+        assert_eq!(page1.shift_x, 0);
+        assert_eq!(page1.shift_y, 0);
+        assert!(!page1.was_interpolated);
+    }
+
+    // ============================================================
+    // Spring-smoothing tests
+    // ============================================================
+
+    fn jittery_offset_result(physical_page: usize, shift_x: i32, confidence: f64) -> PageOffsetResult {
+        PageOffsetResult {
+            physical_page,
+            logical_page: Some(physical_page as i32),
+            shift_x,
+            shift_y: 0,
+            page_number_position: Some(PageNumberRect {
+                x: 100,
+                y: 50,
+                width: 30,
+                height: 20,
+            }),
+            is_odd: physical_page % 2 == 1,
+            was_interpolated: false,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_smooth_shifts_pulls_outlier_toward_neighbors() {
+        // Odd pages 1, 3, 5, 7, 9 all measured at shift 20 except page 5,
+        // a noisy outlier at 80; smoothing should pull it back down without
+        // moving it all the way back to 20 (lambda isn't infinite).
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![
+                jittery_offset_result(1, 20, 0.9),
+                jittery_offset_result(3, 20, 0.9),
+                jittery_offset_result(5, 80, 0.9),
+                jittery_offset_result(7, 20, 0.9),
+                jittery_offset_result(9, 20, 0.9),
+            ],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::smooth_shifts(&mut analysis, 2.0);
+
+        let page5 = analysis.get_offset(5).unwrap().shift_x;
+        assert!(page5 < 80, "outlier should move toward its neighbors, got {}", page5);
+        assert!(page5 > 20, "smoothing shouldn't fully erase a confident measurement, got {}", page5);
+    }
+
+    #[test]
+    fn test_smooth_shifts_zero_lambda_leaves_shifts_unchanged() {
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![
+                jittery_offset_result(1, 10, 0.9),
+                jittery_offset_result(3, 90, 0.9),
+            ],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::smooth_shifts(&mut analysis, 0.0);
+
+        assert_eq!(analysis.get_offset(1).unwrap().shift_x, 10);
+        assert_eq!(analysis.get_offset(3).unwrap().shift_x, 90);
+    }
+
+    #[test]
+    fn test_smooth_shifts_fills_unconfident_page_from_neighbors() {
+        // Page 3 is interpolated (confidence 0.0, so it's pulled only by
+        // its neighbors) sitting between two confident pages at shift 40.
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![
+                jittery_offset_result(1, 40, 0.9),
+                jittery_offset_result(3, 0, 0.0),
+                jittery_offset_result(5, 40, 0.9),
+            ],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::smooth_shifts(&mut analysis, 2.0);
+
+        let page3 = analysis.get_offset(3).unwrap().shift_x;
+        assert!(
+            (page3 - 40).abs() <= 5,
+            "unconfident page should be pulled close to its confident neighbors, got {}",
+            page3
+        );
+    }
+
+    #[test]
+    fn test_smooth_shifts_odd_and_even_groups_are_independent() {
+        // Odd pages jitter around 10, even pages around 90; smoothing one
+        // group must not leak into the other.
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![
+                jittery_offset_result(1, 10, 0.9),
+                jittery_offset_result(2, 90, 0.9),
+                jittery_offset_result(3, 10, 0.9),
+                jittery_offset_result(4, 90, 0.9),
+            ],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::smooth_shifts(&mut analysis, 2.0);
+
+        assert!(analysis.get_offset(1).unwrap().shift_x < 50);
+        assert!(analysis.get_offset(2).unwrap().shift_x > 50);
+    }
+
+    #[test]
+    fn test_smooth_shifts_single_page_group_is_a_no_op() {
+        let mut analysis = BookOffsetAnalysis {
+            page_offsets: vec![jittery_offset_result(1, 42, 0.9)],
+            ..Default::default()
+        };
+
+        PageOffsetAnalyzer::smooth_shifts(&mut analysis, 2.0);
+
+        assert_eq!(analysis.get_offset(1).unwrap().shift_x, 42);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_indexed_matches_plain_version_without_outliers() {
+        let bboxes: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new(500 + i, 100 + i, 50, 20))
+            .collect();
+
+        let indexed_center = calc_overlap_center_indexed(&bboxes);
+        let plain_center = calc_overlap_center(&bboxes);
+
+        assert_eq!(indexed_center, plain_center);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_indexed_finds_densest_cluster() {
+        // A tight cluster near (500, 100) plus a single outlier far away;
+        // the R-tree's nearest-cluster search should settle on the cluster.
+        let mut bboxes: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new(500 + i, 100 + i, 50, 20))
+            .collect();
+        bboxes.push(Rectangle::new(3000, 3000, 50, 20));
+
+        let center = calc_overlap_center_indexed(&bboxes);
+        assert!(center.x < 1000, "outlier should not pull the center, got x={}", center.x);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_indexed_two_or_fewer_bboxes_defers_to_plain_version() {
+        let bboxes = vec![Rectangle::new(0, 0, 10, 10), Rectangle::new(1000, 1000, 10, 10)];
+
+        let indexed_center = calc_overlap_center_indexed(&bboxes);
+        let plain_center = calc_overlap_center(&bboxes);
+
+        assert_eq!(indexed_center, plain_center);
+    }
+
+    #[test]
+    fn test_calc_overlap_center_indexed_empty_is_default_point() {
+        let center = calc_overlap_center_indexed(&[]);
+        assert_eq!(center, Point::default());
+    }
 }
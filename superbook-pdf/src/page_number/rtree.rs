@@ -0,0 +1,509 @@
+//! R-tree spatial index for page-number candidate clustering
+//!
+//! [`super::offset::calc_overlap_center`]'s containment step scans every
+//! candidate bounding box against every other one to find the densest
+//! overlap region, which is `O(n^2)` and degrades once a noisy scan
+//! produces many spurious page-number candidates per page across a large
+//! book. This module implements a minimal in-memory R-tree (Guttman 1984):
+//!
+//! 1. [`RTree::insert`] descends to the leaf needing the least bounding-box
+//!    area enlargement to hold the new entry (ties broken by the smaller
+//!    existing area), so lookups that follow only have to visit subtrees
+//!    whose bounding box could plausibly contain the query - `O(log n)`
+//!    rather than a full scan.
+//! 2. A node that overflows [`MAX_ENTRIES`] is split via Guttman's
+//!    quadratic split: [`pick_seeds`] chooses the two entries that waste the
+//!    most area if grouped together (`d = area(union(e1, e2)) - area(e1) -
+//!    area(e2)`), then [`pick_next`] assigns the rest one at a time to
+//!    whichever group needs the smaller enlargement to absorb them, forcing
+//!    the emptier group to take the remainder once the other group can no
+//!    longer accept entries without dropping below [`MIN_FILL_RATIO`].
+//! 3. [`RTree::search`] answers "which candidates overlap this region"
+//!    without visiting subtrees whose bounding box doesn't overlap it, and
+//!    [`RTree::nearest_cluster`] builds on that to find the most crowded
+//!    cluster of candidate boxes for
+//!    [`super::offset::calc_overlap_center_indexed`].
+
+use super::types::Rectangle;
+
+// ============================================================
+// Constants
+// ============================================================
+
+/// Maximum entries a node may hold before [`RTree::insert`] splits it.
+const MAX_ENTRIES: usize = 4;
+
+/// Minimum fraction of [`MAX_ENTRIES`] a split group must retain; Guttman's
+/// quadratic split forces the emptier group to absorb the rest of the
+/// remaining entries once the other group has reached `MAX_ENTRIES + 1 -
+/// min_fill` members, so neither group ends up starved.
+const MIN_FILL_RATIO: f64 = 0.3;
+
+// ============================================================
+// Tree structure
+// ============================================================
+
+/// One entry in a [`Node`]: either a leaf holding a candidate's index into
+/// the caller's original slice, or an internal pointer to a child subtree.
+/// Either way it carries its own bounding box so a search or split never
+/// has to recompute it from scratch.
+#[derive(Debug, Clone)]
+enum Entry {
+    Leaf { bbox: Rectangle, candidate: usize },
+    Child { bbox: Rectangle, node: Box<Node> },
+}
+
+impl Entry {
+    fn bbox(&self) -> Rectangle {
+        match self {
+            Entry::Leaf { bbox, .. } => *bbox,
+            Entry::Child { bbox, .. } => *bbox,
+        }
+    }
+}
+
+/// A tree node: a flat list of entries, all leaves or all children.
+#[derive(Debug, Clone)]
+struct Node {
+    entries: Vec<Entry>,
+    is_leaf: bool,
+}
+
+impl Node {
+    fn new_leaf() -> Self {
+        Self {
+            entries: Vec::new(),
+            is_leaf: true,
+        }
+    }
+
+    fn new_internal() -> Self {
+        Self {
+            entries: Vec::new(),
+            is_leaf: false,
+        }
+    }
+}
+
+/// Bounding box covering every entry in `node`.
+fn node_bbox(node: &Node) -> Rectangle {
+    let mut boxes = node.entries.iter().map(Entry::bbox);
+    let first = boxes
+        .next()
+        .expect("a node is always split or created with at least one entry");
+    boxes.fold(first, |acc, b| acc.union(&b))
+}
+
+/// Minimal-in-memory R-tree over [`Rectangle`] candidate boxes, indexed by
+/// position in whatever slice the caller built it from.
+#[derive(Debug, Clone)]
+pub struct RTree {
+    root: Node,
+}
+
+impl RTree {
+    /// An empty tree, ready for [`Self::insert`].
+    pub fn new() -> Self {
+        Self {
+            root: Node::new_leaf(),
+        }
+    }
+
+    /// Build a tree over `boxes`, where each box's candidate index is its
+    /// position in the slice - the index [`Self::search`] and
+    /// [`Self::nearest_cluster`] return.
+    pub fn build(boxes: &[Rectangle]) -> Self {
+        let mut tree = Self::new();
+        for (i, bbox) in boxes.iter().enumerate() {
+            tree.insert(*bbox, i);
+        }
+        tree
+    }
+
+    /// Insert `bbox` under `candidate`'s index, splitting any node that
+    /// overflows [`MAX_ENTRIES`] along the way.
+    pub fn insert(&mut self, bbox: Rectangle, candidate: usize) {
+        let entry = Entry::Leaf { bbox, candidate };
+
+        if let Some(sibling) = Self::insert_into(&mut self.root, entry) {
+            let old_root = std::mem::replace(&mut self.root, Node::new_internal());
+            let old_bbox = node_bbox(&old_root);
+            let sibling_bbox = node_bbox(&sibling);
+            self.root.entries.push(Entry::Child {
+                bbox: old_bbox,
+                node: Box::new(old_root),
+            });
+            self.root.entries.push(Entry::Child {
+                bbox: sibling_bbox,
+                node: Box::new(sibling),
+            });
+        }
+    }
+
+    /// Insert `entry` into `node` (recursing into the best child for an
+    /// internal node), returning the sibling produced by
+    /// [`quadratic_split`] if `node` overflowed.
+    fn insert_into(node: &mut Node, entry: Entry) -> Option<Node> {
+        if node.is_leaf {
+            node.entries.push(entry);
+        } else {
+            let idx = choose_subtree(node, &entry.bbox());
+            let split = match &mut node.entries[idx] {
+                Entry::Child { node: child, .. } => Self::insert_into(child, entry),
+                Entry::Leaf { .. } => unreachable!("internal node entries are always Child"),
+            };
+
+            let refreshed_bbox = match &node.entries[idx] {
+                Entry::Child { node: child, .. } => node_bbox(child),
+                Entry::Leaf { .. } => unreachable!("internal node entries are always Child"),
+            };
+            if let Entry::Child { bbox, .. } = &mut node.entries[idx] {
+                *bbox = refreshed_bbox;
+            }
+
+            if let Some(sibling) = split {
+                let sibling_bbox = node_bbox(&sibling);
+                node.entries.push(Entry::Child {
+                    bbox: sibling_bbox,
+                    node: Box::new(sibling),
+                });
+            }
+        }
+
+        if node.entries.len() > MAX_ENTRIES {
+            Some(quadratic_split(node))
+        } else {
+            None
+        }
+    }
+
+    /// All candidate indices whose stored bounding box overlaps `region`.
+    pub fn search(&self, region: &Rectangle) -> Vec<usize> {
+        let mut results = Vec::new();
+        Self::search_node(&self.root, region, &mut results);
+        results
+    }
+
+    fn search_node(node: &Node, region: &Rectangle, results: &mut Vec<usize>) {
+        for entry in &node.entries {
+            if entry.bbox().overlaps(region) {
+                match entry {
+                    Entry::Leaf { candidate, .. } => results.push(*candidate),
+                    Entry::Child { node: child, .. } => Self::search_node(child, region, results),
+                }
+            }
+        }
+    }
+
+    /// Find the most crowded cluster of mutually overlapping candidates:
+    /// for every box in `boxes`, [`Self::search`] its own region and keep
+    /// the largest hit set found (ties keep the first, earliest-indexed
+    /// anchor). Used by
+    /// [`super::offset::calc_overlap_center_indexed`] in place of
+    /// `calc_overlap_center`'s pairwise containment scan.
+    pub fn nearest_cluster(&self, boxes: &[Rectangle]) -> Vec<usize> {
+        let mut best: Vec<usize> = Vec::new();
+
+        for bbox in boxes {
+            let hits = self.search(bbox);
+            if hits.len() > best.len() {
+                best = hits;
+            }
+        }
+
+        best
+    }
+}
+
+impl Default for RTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Child of `node` requiring the least area enlargement to absorb `bbox`,
+/// ties broken by the child with the smaller existing area.
+fn choose_subtree(node: &Node, bbox: &Rectangle) -> usize {
+    let mut best_idx = 0;
+    let mut best_enlargement = f64::INFINITY;
+    let mut best_area = f64::INFINITY;
+
+    for (idx, entry) in node.entries.iter().enumerate() {
+        let child_bbox = entry.bbox();
+        let enlarged_area = child_bbox.union(bbox).area() as f64;
+        let child_area = child_bbox.area() as f64;
+        let enlargement = enlarged_area - child_area;
+
+        if enlargement < best_enlargement
+            || (enlargement == best_enlargement && child_area < best_area)
+        {
+            best_enlargement = enlargement;
+            best_area = child_area;
+            best_idx = idx;
+        }
+    }
+
+    best_idx
+}
+
+/// Split an overflowing node in place (it keeps one resulting group) and
+/// return the other group as a new sibling node of the same kind (leaf or
+/// internal).
+fn quadratic_split(node: &mut Node) -> Node {
+    let entries = std::mem::take(&mut node.entries);
+    let is_leaf = node.is_leaf;
+
+    let (seed1, seed2) = pick_seeds(&entries);
+
+    let mut group1: Vec<Entry> = Vec::new();
+    let mut group2: Vec<Entry> = Vec::new();
+    let mut remaining: Vec<Entry> = Vec::new();
+
+    for (i, entry) in entries.into_iter().enumerate() {
+        if i == seed1 {
+            group1.push(entry);
+        } else if i == seed2 {
+            group2.push(entry);
+        } else {
+            remaining.push(entry);
+        }
+    }
+
+    let min_fill = (((MAX_ENTRIES + 1) as f64 * MIN_FILL_RATIO).ceil() as usize).max(1);
+    let mut group1_bbox = group1[0].bbox();
+    let mut group2_bbox = group2[0].bbox();
+
+    while !remaining.is_empty() {
+        // Forced fill: once a group can no longer reach `min_fill` without
+        // taking every remaining entry, it takes them all.
+        if group1.len() + remaining.len() == min_fill {
+            group1.extend(remaining.drain(..));
+            break;
+        }
+        if group2.len() + remaining.len() == min_fill {
+            group2.extend(remaining.drain(..));
+            break;
+        }
+
+        let (pick_idx, to_group1) = pick_next(&remaining, group1_bbox, group2_bbox);
+        let entry = remaining.remove(pick_idx);
+        if to_group1 {
+            group1_bbox = group1_bbox.union(&entry.bbox());
+            group1.push(entry);
+        } else {
+            group2_bbox = group2_bbox.union(&entry.bbox());
+            group2.push(entry);
+        }
+    }
+
+    node.entries = group1;
+    Node {
+        entries: group2,
+        is_leaf,
+    }
+}
+
+/// The pair of entries whose union would waste the most area if grouped
+/// together (`d = area(union(e1, e2)) - area(e1) - area(e2)`): Guttman's
+/// quadratic-split seed choice, picking the two entries that most want to
+/// be apart.
+fn pick_seeds(entries: &[Entry]) -> (usize, usize) {
+    let mut best = (0, 1.min(entries.len().saturating_sub(1)));
+    let mut best_waste = i64::MIN;
+
+    for i in 0..entries.len() {
+        for j in (i + 1)..entries.len() {
+            let bi = entries[i].bbox();
+            let bj = entries[j].bbox();
+            let union_area = bi.union(&bj).area() as i64;
+            let waste = union_area - bi.area() as i64 - bj.area() as i64;
+            if waste > best_waste {
+                best_waste = waste;
+                best = (i, j);
+            }
+        }
+    }
+
+    best
+}
+
+/// The remaining entry with the largest enlargement-cost difference between
+/// the two groups (Guttman's "pick next" preference), and which group it
+/// prefers: the one needing less enlargement to absorb it, ties broken by
+/// the group with the smaller current area.
+fn pick_next(remaining: &[Entry], group1_bbox: Rectangle, group2_bbox: Rectangle) -> (usize, bool) {
+    let mut best_idx = 0;
+    let mut best_diff = -1.0f64;
+    let mut best_to_group1 = true;
+
+    for (idx, entry) in remaining.iter().enumerate() {
+        let bbox = entry.bbox();
+        let enlargement1 = group1_bbox.union(&bbox).area() as f64 - group1_bbox.area() as f64;
+        let enlargement2 = group2_bbox.union(&bbox).area() as f64 - group2_bbox.area() as f64;
+        let diff = (enlargement1 - enlargement2).abs();
+
+        if diff > best_diff {
+            best_diff = diff;
+            best_idx = idx;
+            best_to_group1 = enlargement1 < enlargement2
+                || (enlargement1 == enlargement2 && group1_bbox.area() <= group2_bbox.area());
+        }
+    }
+
+    (best_idx, best_to_group1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_search_single_box() {
+        let mut tree = RTree::new();
+        tree.insert(Rectangle::new(0, 0, 10, 10), 0);
+
+        let hits = tree.search(&Rectangle::new(5, 5, 10, 10));
+        assert_eq!(hits, vec![0]);
+    }
+
+    #[test]
+    fn test_search_excludes_non_overlapping_boxes() {
+        let mut tree = RTree::new();
+        tree.insert(Rectangle::new(0, 0, 10, 10), 0);
+        tree.insert(Rectangle::new(1000, 1000, 10, 10), 1);
+
+        let hits = tree.search(&Rectangle::new(0, 0, 10, 10));
+        assert_eq!(hits, vec![0]);
+    }
+
+    #[test]
+    fn test_search_finds_all_overlapping_boxes() {
+        let mut tree = RTree::new();
+        tree.insert(Rectangle::new(0, 0, 10, 10), 0);
+        tree.insert(Rectangle::new(5, 5, 10, 10), 1);
+        tree.insert(Rectangle::new(1000, 1000, 10, 10), 2);
+
+        let mut hits = tree.search(&Rectangle::new(0, 0, 15, 15));
+        hits.sort();
+        assert_eq!(hits, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_insert_beyond_max_entries_triggers_split_and_stays_searchable() {
+        let mut tree = RTree::new();
+        let boxes: Vec<Rectangle> = (0..20)
+            .map(|i| Rectangle::new(i * 100, i * 100, 10, 10))
+            .collect();
+        for (i, bbox) in boxes.iter().enumerate() {
+            tree.insert(*bbox, i);
+        }
+
+        for (i, bbox) in boxes.iter().enumerate() {
+            let hits = tree.search(bbox);
+            assert!(hits.contains(&i), "candidate {} missing after splits", i);
+        }
+    }
+
+    #[test]
+    fn test_build_indexes_every_box() {
+        let boxes = vec![
+            Rectangle::new(0, 0, 10, 10),
+            Rectangle::new(20, 20, 10, 10),
+            Rectangle::new(40, 40, 10, 10),
+        ];
+        let tree = RTree::build(&boxes);
+
+        let mut all_hits: Vec<usize> = boxes.iter().flat_map(|b| tree.search(b)).collect();
+        all_hits.sort();
+        all_hits.dedup();
+        assert_eq!(all_hits, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_nearest_cluster_finds_densest_group() {
+        // Five boxes clustered tightly around (500, 500), plus two isolated
+        // outliers; the cluster must win.
+        let mut boxes: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new(500 + i, 500 + i, 50, 50))
+            .collect();
+        boxes.push(Rectangle::new(0, 0, 50, 50));
+        boxes.push(Rectangle::new(2000, 2000, 50, 50));
+
+        let tree = RTree::build(&boxes);
+        let mut cluster = tree.nearest_cluster(&boxes);
+        cluster.sort();
+
+        assert_eq!(cluster, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_nearest_cluster_empty_tree_returns_empty() {
+        let tree = RTree::new();
+        let cluster = tree.nearest_cluster(&[]);
+        assert!(cluster.is_empty());
+    }
+
+    #[test]
+    fn test_pick_seeds_chooses_most_wasteful_pair() {
+        let entries = vec![
+            Entry::Leaf {
+                bbox: Rectangle::new(0, 0, 10, 10),
+                candidate: 0,
+            },
+            Entry::Leaf {
+                bbox: Rectangle::new(5, 5, 10, 10),
+                candidate: 1,
+            },
+            Entry::Leaf {
+                bbox: Rectangle::new(1000, 1000, 10, 10),
+                candidate: 2,
+            },
+        ];
+
+        let (i, j) = pick_seeds(&entries);
+        let picked = [i, j];
+        // The far-away box must be one of the two chosen seeds, since any
+        // pairing involving it wastes far more area than pairing the two
+        // nearby boxes together.
+        assert!(picked.contains(&2));
+    }
+
+    #[test]
+    fn test_quadratic_split_keeps_minimum_fill() {
+        let mut node = Node::new_leaf();
+        for i in 0..(MAX_ENTRIES + 1) {
+            node.entries.push(Entry::Leaf {
+                bbox: Rectangle::new(i as i32 * 100, 0, 10, 10),
+                candidate: i,
+            });
+        }
+
+        let sibling = quadratic_split(&mut node);
+        let min_fill = (((MAX_ENTRIES + 1) as f64 * MIN_FILL_RATIO).ceil() as usize).max(1);
+
+        assert!(node.entries.len() >= min_fill);
+        assert!(sibling.entries.len() >= min_fill);
+        assert_eq!(node.entries.len() + sibling.entries.len(), MAX_ENTRIES + 1);
+    }
+
+    #[test]
+    fn test_choose_subtree_prefers_least_enlargement() {
+        let node = Node {
+            is_leaf: false,
+            entries: vec![
+                Entry::Child {
+                    bbox: Rectangle::new(0, 0, 10, 10),
+                    node: Box::new(Node::new_leaf()),
+                },
+                Entry::Child {
+                    bbox: Rectangle::new(1000, 1000, 10, 10),
+                    node: Box::new(Node::new_leaf()),
+                },
+            ],
+        };
+
+        // A box right next to the first child should prefer it.
+        let idx = choose_subtree(&node, &Rectangle::new(10, 10, 10, 10));
+        assert_eq!(idx, 0);
+    }
+}
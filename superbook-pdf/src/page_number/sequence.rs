@@ -0,0 +1,337 @@
+//! Page Number Sequence Modeling
+//!
+//! Detected page numbers should lie on a line `number = slope * page_index + intercept`
+//! (usually `slope = 1`, `intercept` = the label of the first page). A single OCR
+//! misread or a front-matter renumbering breaks that assumption for
+//! [`TesseractPageDetector::validate_order`](super::detect::TesseractPageDetector::validate_order),
+//! which only checks pairwise ascent. This module fits the line robustly instead:
+//!
+//! 1. Sample every pair of detections that carry a number, derive the `(slope, intercept)` that
+//!    pair implies, and count inliers (detections whose predicted number matches the
+//!    one actually detected). This is RANSAC's minimal-sample-and-score loop made
+//!    exhaustive rather than randomized, since a book's detection count is small
+//!    enough to enumerate every pair and this keeps the fit deterministic without
+//!    pulling in a `rand` dependency.
+//! 2. Keep the pair whose implied line has the most inliers, then re-fit `(slope,
+//!    intercept)` by least squares over the final inlier set (the two-point sample is
+//!    just a seed; the refit uses every inlier).
+//! 3. Detections that disagree with the fit are outliers - almost always a misread
+//!    digit - and get their number overwritten with the model's prediction.
+//! 4. A second pass repeats the process over the residual outliers, to catch a
+//!    second line segment (e.g. renumbered front matter before the body resets to 1).
+//!
+//! Models with fewer than [`MIN_INLIERS`] inliers are rejected outright, since two
+//! points can always be fit trivially and such a "model" carries no signal.
+
+use super::types::DetectedPageNumber;
+use std::collections::HashSet;
+
+// ============================================================
+// Constants
+// ============================================================
+
+/// Minimum inliers for a fitted line to be accepted; below this a fit is
+/// indistinguishable from noise and is discarded rather than used to "correct"
+/// anything.
+const MIN_INLIERS: usize = 3;
+
+/// Maximum number of line segments to fit (primary sequence + one front-matter
+/// renumbering); further residual outliers are left untouched.
+const MAX_SEGMENTS: usize = 2;
+
+// ============================================================
+// Data Structures
+// ============================================================
+
+/// A fitted `number = slope * page_index + intercept` line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SequenceModel {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl SequenceModel {
+    /// Predict the page number for a given (0-indexed) page index
+    pub fn predict(&self, page_index: usize) -> i32 {
+        (self.slope * page_index as f64 + self.intercept).round() as i32
+    }
+}
+
+/// One fitted segment and the page indices that support it
+#[derive(Debug, Clone)]
+pub struct SequenceSegment {
+    pub model: SequenceModel,
+    /// Page indices (0-indexed) whose detected number matches the model
+    pub inlier_pages: Vec<usize>,
+}
+
+/// Result of fitting and correcting a batch of detections
+#[derive(Debug, Clone, Default)]
+pub struct SequenceAnalysis {
+    /// Line segments found, in the order they were fit (primary sequence first)
+    pub segments: Vec<SequenceSegment>,
+    /// Page indices whose `number` was overwritten with a model prediction
+    pub corrected_pages: Vec<usize>,
+}
+
+// ============================================================
+// RANSAC Fitting
+// ============================================================
+
+/// Fit a single robust line to `points` via exhaustive-pair RANSAC + least-squares
+/// refit. Returns `None` if fewer than [`MIN_INLIERS`] detections support the best
+/// line found.
+fn fit_one(points: &[(usize, i32)]) -> Option<(SequenceModel, Vec<(usize, i32)>)> {
+    if points.len() < MIN_INLIERS {
+        return None;
+    }
+
+    let mut best_inliers: Vec<(usize, i32)> = Vec::new();
+
+    for i in 0..points.len() {
+        for j in (i + 1)..points.len() {
+            let (idx_a, num_a) = points[i];
+            let (idx_b, num_b) = points[j];
+            if idx_a == idx_b {
+                continue;
+            }
+
+            let slope = (num_b - num_a) as f64 / (idx_b as f64 - idx_a as f64);
+            let intercept = num_a as f64 - slope * idx_a as f64;
+            let model = SequenceModel { slope, intercept };
+
+            let inliers: Vec<(usize, i32)> = points
+                .iter()
+                .copied()
+                .filter(|&(idx, num)| model.predict(idx) == num)
+                .collect();
+
+            if inliers.len() > best_inliers.len() {
+                best_inliers = inliers;
+            }
+        }
+    }
+
+    if best_inliers.len() < MIN_INLIERS {
+        return None;
+    }
+
+    Some((least_squares(&best_inliers), best_inliers))
+}
+
+/// Ordinary least-squares fit of `number = slope * page_index + intercept`
+fn least_squares(points: &[(usize, i32)]) -> SequenceModel {
+    let n = points.len() as f64;
+    let sum_x: f64 = points.iter().map(|&(idx, _)| idx as f64).sum();
+    let sum_y: f64 = points.iter().map(|&(_, num)| num as f64).sum();
+    let sum_xy: f64 = points.iter().map(|&(idx, num)| idx as f64 * num as f64).sum();
+    let sum_xx: f64 = points.iter().map(|&(idx, _)| (idx as f64).powi(2)).sum();
+
+    let denom = n * sum_xx - sum_x * sum_x;
+    if denom.abs() < f64::EPSILON {
+        // All points share a page index (shouldn't happen in practice); fall back
+        // to a flat line through the mean rather than dividing by zero.
+        return SequenceModel {
+            slope: 0.0,
+            intercept: sum_y / n,
+        };
+    }
+
+    let slope = (n * sum_xy - sum_x * sum_y) / denom;
+    let intercept = (sum_y - slope * sum_x) / n;
+    SequenceModel { slope, intercept }
+}
+
+/// Pick the segment whose inlier page-index range is closest to `page_index`,
+/// for assigning a correction to an outlier that isn't covered by any segment.
+fn closest_segment(segments: &[SequenceSegment], page_index: usize) -> Option<&SequenceSegment> {
+    segments.iter().min_by_key(|segment| {
+        let min = *segment.inlier_pages.iter().min().unwrap();
+        let max = *segment.inlier_pages.iter().max().unwrap();
+        if page_index < min {
+            min - page_index
+        } else if page_index > max {
+            page_index - max
+        } else {
+            0
+        }
+    })
+}
+
+// ============================================================
+// Public API
+// ============================================================
+
+/// Fit up to [`MAX_SEGMENTS`] robust line segments across `detections` and correct
+/// any detection whose number disagrees with the segment it belongs to (an OCR
+/// misread). Returns the fitted segments and which pages were corrected; callers
+/// use [`SequenceAnalysis::corrected_pages`] as the "interpolated" flag for those
+/// pages.
+pub fn analyze_sequence(detections: &mut [DetectedPageNumber]) -> SequenceAnalysis {
+    let points: Vec<(usize, i32)> = detections
+        .iter()
+        .filter_map(|d| d.number.map(|n| (d.page_index, n)))
+        .collect();
+
+    let mut segments: Vec<SequenceSegment> = Vec::new();
+    let mut remaining = points.clone();
+
+    for _ in 0..MAX_SEGMENTS {
+        let Some((model, inliers)) = fit_one(&remaining) else {
+            break;
+        };
+        let inlier_pages: HashSet<usize> = inliers.iter().map(|&(idx, _)| idx).collect();
+        remaining.retain(|&(idx, _)| !inlier_pages.contains(&idx));
+        segments.push(SequenceSegment {
+            model,
+            inlier_pages: inlier_pages.into_iter().collect(),
+        });
+    }
+
+    if segments.is_empty() {
+        return SequenceAnalysis::default();
+    }
+
+    let mut corrected_pages = Vec::new();
+    for detection in detections.iter_mut() {
+        let Some(number) = detection.number else {
+            continue;
+        };
+        if segments
+            .iter()
+            .any(|segment| segment.inlier_pages.contains(&detection.page_index))
+        {
+            continue;
+        }
+
+        let Some(segment) = closest_segment(&segments, detection.page_index) else {
+            continue;
+        };
+        let predicted = segment.model.predict(detection.page_index);
+        if predicted != number {
+            detection.number = Some(predicted);
+            corrected_pages.push(detection.page_index);
+        }
+    }
+    corrected_pages.sort_unstable();
+
+    SequenceAnalysis {
+        segments,
+        corrected_pages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_number::types::PageNumberRect;
+
+    fn detection(page_index: usize, number: i32) -> DetectedPageNumber {
+        DetectedPageNumber {
+            page_index,
+            number: Some(number),
+            position: PageNumberRect {
+                x: 500,
+                y: 100,
+                width: 50,
+                height: 20,
+            },
+            confidence: 0.9,
+            raw_text: number.to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_fit_one_perfect_sequence() {
+        let points: Vec<(usize, i32)> = (0..6).map(|i| (i, i as i32 + 1)).collect();
+        let (model, inliers) = fit_one(&points).unwrap();
+        assert!((model.slope - 1.0).abs() < 1e-6);
+        assert!((model.intercept - 1.0).abs() < 1e-6);
+        assert_eq!(inliers.len(), 6);
+    }
+
+    #[test]
+    fn test_fit_one_too_few_points() {
+        let points = vec![(0, 1), (1, 2)];
+        assert!(fit_one(&points).is_none());
+    }
+
+    #[test]
+    fn test_fit_one_rejects_low_support() {
+        // Every point disagrees with every other pair's line: no 3-point consensus
+        let points = vec![(0, 1), (1, 100), (2, 3), (3, 200)];
+        assert!(fit_one(&points).is_none());
+    }
+
+    #[test]
+    fn test_analyze_sequence_fixes_single_misread() {
+        let mut detections = vec![
+            detection(0, 1),
+            detection(1, 2),
+            detection(2, 9), // misread: should be 3
+            detection(3, 4),
+            detection(4, 5),
+        ];
+
+        let analysis = analyze_sequence(&mut detections);
+
+        assert_eq!(analysis.corrected_pages, vec![2]);
+        assert_eq!(detections[2].number, Some(3));
+        // Untouched detections keep their original numbers
+        assert_eq!(detections[0].number, Some(1));
+        assert_eq!(detections[4].number, Some(5));
+    }
+
+    #[test]
+    fn test_analyze_sequence_rejects_sparse_noise() {
+        // Too few consistent points anywhere to fit a model
+        let mut detections = vec![detection(0, 5), detection(1, 77)];
+        let analysis = analyze_sequence(&mut detections);
+        assert!(analysis.segments.is_empty());
+        assert!(analysis.corrected_pages.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_sequence_front_matter_reset() {
+        // Front matter pages 0-3 are numbered i-xiv style but OCR'd as small
+        // Arabic numerals 1..4, then the body resets to page 1 at index 4.
+        let mut detections = vec![
+            detection(0, 1),
+            detection(1, 2),
+            detection(2, 3),
+            detection(3, 4),
+            detection(4, 1),
+            detection(5, 2),
+            detection(6, 3),
+            detection(7, 4),
+            detection(8, 5),
+        ];
+
+        let analysis = analyze_sequence(&mut detections);
+
+        assert_eq!(analysis.segments.len(), 2);
+        // Both segments should be well-supported
+        for segment in &analysis.segments {
+            assert!(segment.inlier_pages.len() >= MIN_INLIERS);
+        }
+    }
+
+    #[test]
+    fn test_least_squares_matches_exact_line() {
+        let points = vec![(0, 10), (1, 12), (2, 14), (3, 16)];
+        let model = least_squares(&points);
+        assert!((model.slope - 2.0).abs() < 1e-6);
+        assert!((model.intercept - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sequence_model_predict() {
+        let model = SequenceModel {
+            slope: 1.0,
+            intercept: 5.0,
+        };
+        assert_eq!(model.predict(0), 5);
+        assert_eq!(model.predict(10), 15);
+    }
+}
@@ -24,6 +24,40 @@ pub const STRICT_MIN_CONFIDENCE: f32 = 80.0;
 /// Minimum search region clamp value
 pub const MIN_SEARCH_REGION: f32 = 5.0;
 
+/// Vertical tolerance (pixels) for [`DetectedPageNumber::reading_order_cmp`]:
+/// boxes whose `y` falls within the same multiple of this are treated as sitting
+/// on the same visual line, so a few pixels of scan skew do not split them into
+/// separate bands.
+pub const READING_ORDER_BAND_HEIGHT: u32 = 10;
+
+/// Default regex templates for labeled page numbers, e.g. "Page 12", "- 12 -",
+/// "12 / 340". Each pattern's first capture group is the page number.
+pub const DEFAULT_LABEL_PATTERNS: &[&str] = &[
+    r"(?i)page\s*(\d+)",
+    r"-\s*(\d+)\s*-",
+    r"^(\d+)\s*/\s*\d+$",
+];
+
+/// A known OCR confusion: `.0`/`.1` are visually similar glyphs (checked in either
+/// direction) and `.2` is the substitution cost Stage 2's similarity matching should
+/// charge in place of the default Levenshtein cost of `1.0`.
+pub type ConfusionPair = (char, char, f64);
+
+/// Default table of visually-similar glyph pairs Tesseract commonly confuses.
+/// Exposed as [`PageNumberOptions::confusion_pairs`] so callers can add
+/// language-specific glyphs without forking the matcher.
+pub const DEFAULT_CONFUSION_PAIRS: &[ConfusionPair] = &[
+    ('0', 'O', 0.2),
+    ('0', 'Q', 0.2),
+    ('1', 'l', 0.2),
+    ('1', 'I', 0.2),
+    ('1', '|', 0.2),
+    ('5', 'S', 0.2),
+    ('8', 'B', 0.2),
+    ('6', 'G', 0.2),
+    ('2', 'Z', 0.2),
+];
+
 /// Maximum search region clamp value
 pub const MAX_SEARCH_REGION: f32 = 50.0;
 
@@ -99,6 +133,30 @@ pub struct DetectedPageNumber {
     pub confidence: f32,
     /// Raw OCR text
     pub raw_text: String,
+    /// Structured page label recognizing Roman numerals, alpha-decorated
+    /// numbers, and full (non-Arabic) numbering schemes in addition to the
+    /// plain Arabic `number` field; used by
+    /// [`crate::page_number::numbering_map::PageNumberingMap`] to segment a
+    /// book's physical-page span into its per-scheme logical ranges
+    pub label: Option<PageLabel>,
+}
+
+impl DetectedPageNumber {
+    /// Canonical reading-order comparator: top-to-bottom, then left-to-right.
+    ///
+    /// `position.y` is first snapped to a [`READING_ORDER_BAND_HEIGHT`]-pixel
+    /// band so boxes that sit on the same visual line but differ by a few
+    /// pixels of scan skew are not split into separate bands; within a band,
+    /// boxes are ordered by `position.x`. Geometry-based aggregation (e.g. the
+    /// area-based selection in [`super::offset::calc_group_reference_position`])
+    /// should sort by this first so ties resolve the same way regardless of
+    /// the order detections happened to be produced in.
+    pub fn reading_order_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let band = self.position.y / READING_ORDER_BAND_HEIGHT;
+        let other_band = other.position.y / READING_ORDER_BAND_HEIGHT;
+        band.cmp(&other_band)
+            .then_with(|| self.position.x.cmp(&other.position.x))
+    }
 }
 
 /// Page number analysis result
@@ -118,6 +176,10 @@ pub struct PageNumberAnalysis {
     pub missing_pages: Vec<usize>,
     /// Duplicate page numbers
     pub duplicate_pages: Vec<i32>,
+    /// Page indices whose detected number was overwritten by the robust
+    /// arithmetic-sequence fit (see [`super::sequence::analyze_sequence`]) rather
+    /// than read directly from OCR
+    pub interpolated_pages: Vec<usize>,
 }
 
 /// Offset correction result
@@ -216,8 +278,9 @@ impl Rectangle {
         self.width as u64 * self.height as u64
     }
 
-    /// Calculate intersection with another rectangle
-    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+    /// Calculate the intersecting region with another rectangle, or `None`
+    /// if the two do not overlap
+    pub fn intersect(&self, other: &Rectangle) -> Option<Rectangle> {
         let x1 = self.x.max(other.x);
         let y1 = self.y.max(other.y);
         let x2 = (self.x + self.width as i32).min(other.x + other.width as i32);
@@ -235,6 +298,21 @@ impl Rectangle {
         }
     }
 
+    /// Calculate the smallest rectangle containing both this one and `other`
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        let x1 = self.x.min(other.x);
+        let y1 = self.y.min(other.y);
+        let x2 = (self.x + self.width as i32).max(other.x + other.width as i32);
+        let y2 = (self.y + self.height as i32).max(other.y + other.height as i32);
+
+        Rectangle {
+            x: x1,
+            y: y1,
+            width: (x2 - x1) as u32,
+            height: (y2 - y1) as u32,
+        }
+    }
+
     /// Check if this rectangle contains another rectangle completely
     pub fn contains_rect(&self, other: &Rectangle) -> bool {
         other.x >= self.x
@@ -255,8 +333,11 @@ impl Rectangle {
 pub struct PageNumberCandidate {
     /// Detected text
     pub text: String,
-    /// Parsed number (if parseable)
+    /// Parsed number (if parseable as a plain Arabic numeral)
     pub number: Option<u32>,
+    /// Structured page label recognizing Roman numerals and alpha-decorated
+    /// numbers in addition to Arabic numerals (see [`PageLabel::parse`])
+    pub label: Option<PageLabel>,
     /// Bounding box
     pub bbox: Rectangle,
     /// OCR confidence (0.0 - 1.0)
@@ -265,14 +346,364 @@ pub struct PageNumberCandidate {
     pub ocr_success: bool,
 }
 
+/// Parse a page-number string that may use a non-Western numeral system.
+///
+/// Scanned books aren't always numbered with ASCII digits: tries, in order,
+/// plain ASCII digits, full-width/Arabic-Indic/Devanagari digit scripts (via
+/// per-codepoint translation to ASCII), and simple Kanji numerals (一二三...十).
+/// Returns `None` if nothing recognizable is found.
+fn parse_numeral(text: &str) -> Option<u32> {
+    if let Ok(n) = text.parse::<u32>() {
+        return Some(n);
+    }
+
+    if let Some(ascii) = translate_digit_script(text) {
+        if let Ok(n) = ascii.parse::<u32>() {
+            return Some(n);
+        }
+    }
+
+    parse_kanji_numeral(text)
+}
+
+/// Translate a string of non-ASCII decimal digits (full-width, Arabic-Indic,
+/// extended Arabic-Indic, or Devanagari) into ASCII digits. Returns `None` if the
+/// string contains anything outside those digit blocks (including mixed scripts).
+fn translate_digit_script(text: &str) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+
+    text.chars()
+        .map(|c| match c {
+            '0'..='9' => Some(c),
+            '\u{FF10}'..='\u{FF19}' => char::from_u32('0' as u32 + (c as u32 - 0xFF10)),
+            '\u{0660}'..='\u{0669}' => char::from_u32('0' as u32 + (c as u32 - 0x0660)),
+            '\u{06F0}'..='\u{06F9}' => char::from_u32('0' as u32 + (c as u32 - 0x06F0)),
+            '\u{0966}'..='\u{096F}' => char::from_u32('0' as u32 + (c as u32 - 0x0966)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse a simple Kanji numeral (e.g. "十二" = 12, "三十" = 30, "七" = 7).
+///
+/// Covers the range used for book page numbers (1-99): single digits 一-九,
+/// bare 十 (= 10), and 十-combinations like "X十Y" / "十Y" / "X十".
+fn parse_kanji_numeral(text: &str) -> Option<u32> {
+    let digit = |c: char| -> Option<u32> {
+        match c {
+            '〇' => Some(0),
+            '一' => Some(1),
+            '二' => Some(2),
+            '三' => Some(3),
+            '四' => Some(4),
+            '五' => Some(5),
+            '六' => Some(6),
+            '七' => Some(7),
+            '八' => Some(8),
+            '九' => Some(9),
+            _ => None,
+        }
+    };
+
+    let chars: Vec<char> = text.trim().chars().collect();
+    if chars.is_empty() {
+        return None;
+    }
+
+    if let Some(pos) = chars.iter().position(|&c| c == '十') {
+        let tens = if pos == 0 {
+            1
+        } else {
+            digit(chars[pos - 1])?
+        };
+        let ones = if pos + 1 < chars.len() {
+            digit(chars[pos + 1])?
+        } else {
+            0
+        };
+        if pos > 1 || pos + 2 < chars.len() {
+            // More than one digit on either side of 十 isn't a simple 1-99 numeral
+            return None;
+        }
+        return Some(tens * 10 + ones);
+    }
+
+    if chars.len() == 1 {
+        return digit(chars[0]);
+    }
+
+    None
+}
+
+/// The numbering scheme a [`PageLabel`] was recognized in. Front matter and body
+/// text commonly use different schemes (roman-numeral preface, Arabic body,
+/// lettered appendix), and they should never be cross-matched against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageLabelStyle {
+    /// Plain Arabic numerals, including non-Western digit scripts, e.g. "12"
+    Arabic,
+    /// Lowercase Roman numerals, e.g. "xii"
+    RomanLower,
+    /// Uppercase Roman numerals, e.g. "XII"
+    RomanUpper,
+    /// A number decorated with an alphabetic prefix or suffix, e.g. "A-12", "12b"
+    Alpha,
+    /// A bare alphabetic page label with no digits, e.g. "a", "b", ..., "z",
+    /// "aa" - the lettered-appendix convention (Appendix A, B, C, ...)
+    Letters,
+}
+
+/// Roman numeral digit values, largest first; shared by parsing and rendering so
+/// the two stay in sync.
+const ROMAN_DIGITS: &[(&str, u32)] = &[
+    ("m", 1000),
+    ("cm", 900),
+    ("d", 500),
+    ("cd", 400),
+    ("c", 100),
+    ("xc", 90),
+    ("l", 50),
+    ("xl", 40),
+    ("x", 10),
+    ("ix", 9),
+    ("v", 5),
+    ("iv", 4),
+    ("i", 1),
+];
+
+/// A structured page label: the numbering scheme plus the ordinal within it. Lets
+/// callers compare labels within the same scheme instead of treating every page
+/// number as a bare integer (e.g. roman-numeral front matter vs. Arabic body).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLabel {
+    pub style: PageLabelStyle,
+    pub ordinal: u32,
+    /// Alphabetic decoration attached to the ordinal for [`PageLabelStyle::Alpha`]
+    /// labels (e.g. "a" for both "A-12" and "12a"), lowercased; `None` otherwise.
+    pub prefix: Option<String>,
+}
+
+impl PageLabel {
+    /// Construct a plain Arabic-numeral label
+    pub fn arabic(ordinal: u32) -> Self {
+        Self {
+            style: PageLabelStyle::Arabic,
+            ordinal,
+            prefix: None,
+        }
+    }
+
+    /// Parse `text` as a page label, trying (in order) plain Arabic numerals
+    /// (including non-Western digit scripts and Kanji, via [`parse_numeral`]),
+    /// case-preserving Roman numerals, bare lettered labels like "a" or "aa",
+    /// and alpha-decorated numbers like "A-12" or "12b". Returns `None` if
+    /// nothing recognizable is found.
+    ///
+    /// A string that is a valid single Roman-numeral letter (e.g. "c", "l")
+    /// is always read as Roman rather than a lettered label, since a
+    /// misdetected Roman digit is far more common than a one-letter
+    /// appendix label; letters only wins once Roman parsing rejects it.
+    pub fn parse(text: &str) -> Option<PageLabel> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        if let Some(ordinal) = parse_numeral(trimmed) {
+            return Some(PageLabel::arabic(ordinal));
+        }
+
+        if let Some((ordinal, style)) = parse_roman_case_aware(trimmed) {
+            return Some(PageLabel {
+                style,
+                ordinal,
+                prefix: None,
+            });
+        }
+
+        if let Some(ordinal) = parse_letters(trimmed) {
+            return Some(PageLabel {
+                style: PageLabelStyle::Letters,
+                ordinal,
+                prefix: None,
+            });
+        }
+
+        parse_alpha_decorated(trimmed)
+    }
+
+    /// Render the label back to text, e.g. for Stage 2 similarity comparison
+    /// against OCR'd text in the label's own numbering scheme.
+    pub fn render(&self) -> String {
+        match self.style {
+            PageLabelStyle::Arabic => self.ordinal.to_string(),
+            PageLabelStyle::RomanLower => to_roman(self.ordinal),
+            PageLabelStyle::RomanUpper => to_roman(self.ordinal).to_uppercase(),
+            PageLabelStyle::Letters => to_letters(self.ordinal),
+            PageLabelStyle::Alpha => match &self.prefix {
+                Some(prefix) => format!("{prefix}-{}", self.ordinal),
+                None => self.ordinal.to_string(),
+            },
+        }
+    }
+}
+
+/// Convert `ordinal` to a lowercase Roman numeral (standard subtractive notation).
+fn to_roman(mut ordinal: u32) -> String {
+    let mut out = String::new();
+    for &(numeral, value) in ROMAN_DIGITS {
+        while ordinal >= value {
+            out.push_str(numeral);
+            ordinal -= value;
+        }
+    }
+    out
+}
+
+/// Parse a Roman numeral value out of `lower` (already lowercased), or `None` if
+/// it isn't a clean Roman numeral (leftover characters after greedily consuming
+/// known digits, or empty input).
+fn parse_roman_value(lower: &str) -> Option<u32> {
+    let mut result = 0u32;
+    let mut remaining = lower;
+    for &(numeral, value) in ROMAN_DIGITS {
+        while remaining.starts_with(numeral) {
+            result += value;
+            remaining = &remaining[numeral.len()..];
+        }
+    }
+    if remaining.is_empty() && result > 0 {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// Parse `text` as a Roman numeral, returning its value and whether it was upper
+/// or lowercase. Mixed case (e.g. "Xii") isn't treated as a clean Roman numeral,
+/// since that's more likely an OCR artifact than a deliberate numbering scheme.
+///
+/// Also rejects non-canonical forms like "iiii" (four consecutive "i" - real
+/// Roman numerals never repeat a symbol that many times) or "vx" (not a valid
+/// subtractive pairing): a well-formed Roman numeral always round-trips
+/// exactly back through [`to_roman`], so anything that fails to round-trip is
+/// OCR noise rather than a real page label.
+fn parse_roman_case_aware(text: &str) -> Option<(u32, PageLabelStyle)> {
+    if text.is_empty() || !text.chars().all(|c| "ivxlcdmIVXLCDM".contains(c)) {
+        return None;
+    }
+
+    let is_upper = text.chars().all(|c| c.is_uppercase());
+    let is_lower = text.chars().all(|c| c.is_lowercase());
+    if !is_upper && !is_lower {
+        return None;
+    }
+
+    let lower = text.to_lowercase();
+    let ordinal = parse_roman_value(&lower)?;
+    if to_roman(ordinal) != lower {
+        return None;
+    }
+
+    let style = if is_upper {
+        PageLabelStyle::RomanUpper
+    } else {
+        PageLabelStyle::RomanLower
+    };
+    Some((ordinal, style))
+}
+
+/// Parse a bare alphabetic page label ("a", "b", ..., "z", "aa", "ab", ...) as
+/// a 1-indexed bijective base-26 ordinal, the convention used for lettered
+/// appendices (Appendix A, B, C, ...). Case-insensitive; `None` if `text`
+/// contains anything but ASCII letters.
+fn parse_letters(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut ordinal: u32 = 0;
+    for c in text.chars() {
+        let digit = c.to_ascii_lowercase() as u32 - 'a' as u32 + 1;
+        ordinal = ordinal.checked_mul(26)?.checked_add(digit)?;
+    }
+    Some(ordinal)
+}
+
+/// Inverse of [`parse_letters`]: render a 1-indexed ordinal back to its
+/// bijective base-26 letter sequence, lowercase.
+fn to_letters(mut ordinal: u32) -> String {
+    let mut letters = Vec::new();
+    while ordinal > 0 {
+        let remainder = (ordinal - 1) % 26;
+        letters.push((b'a' + remainder as u8) as char);
+        ordinal = (ordinal - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Parse a number decorated with an alphabetic prefix ("A-12", "A12") or suffix
+/// ("12b", "12-b"). The decoration is lowercased into [`PageLabel::prefix`]
+/// regardless of which side it was on.
+fn parse_alpha_decorated(text: &str) -> Option<PageLabel> {
+    if let Ok(re) = regex::Regex::new(r"^([A-Za-z]+)[\s\-.]*([0-9]+)$") {
+        if let Some(caps) = re.captures(text) {
+            let prefix = caps.get(1)?.as_str().to_lowercase();
+            let ordinal = caps.get(2)?.as_str().parse().ok()?;
+            return Some(PageLabel {
+                style: PageLabelStyle::Alpha,
+                ordinal,
+                prefix: Some(prefix),
+            });
+        }
+    }
+
+    if let Ok(re) = regex::Regex::new(r"^([0-9]+)[\s\-.]*([A-Za-z]+)$") {
+        if let Some(caps) = re.captures(text) {
+            let ordinal = caps.get(1)?.as_str().parse().ok()?;
+            let suffix = caps.get(2)?.as_str().to_lowercase();
+            return Some(PageLabel {
+                style: PageLabelStyle::Alpha,
+                ordinal,
+                prefix: Some(suffix),
+            });
+        }
+    }
+
+    None
+}
+
+/// Try each of `patterns` (regex strings, first capture group = the number) against
+/// `text` in order, returning the first match. Invalid regex patterns are skipped
+/// rather than causing an error, since patterns are user-configurable.
+pub fn extract_label_number(text: &str, patterns: &[String]) -> Option<u32> {
+    for pattern in patterns {
+        let Ok(re) = regex::Regex::new(pattern) else {
+            continue;
+        };
+        if let Some(caps) = re.captures(text) {
+            if let Some(m) = caps.get(1) {
+                if let Some(n) = parse_numeral(m.as_str()) {
+                    return Some(n);
+                }
+            }
+        }
+    }
+    None
+}
+
 impl PageNumberCandidate {
     /// Create a new candidate
     pub fn new(text: String, bbox: Rectangle, confidence: f32) -> Self {
-        let number = text.trim().parse::<u32>().ok();
+        let number = parse_numeral(text.trim());
+        let label = PageLabel::parse(text.trim());
         let ocr_success = number.is_some() || !text.trim().is_empty();
         Self {
             text,
             number,
+            label,
             bbox,
             confidence,
             ocr_success,
@@ -331,8 +762,19 @@ pub struct PageNumberMatch {
     pub score: f64,
     /// Distance from reference point
     pub distance: f64,
-    /// Expected number that was being searched for
-    pub expected_number: u32,
+    /// Expected label that was being searched for
+    pub expected_label: PageLabel,
+    /// Whether this match only passed Stage 2 because of the length-adaptive typo
+    /// budget (see [`super::detect::allowed_edit_cost`]) rather than the stricter
+    /// legacy similarity threshold; always `false` for stages other than
+    /// [`MatchStage::SimilarityMatch`]
+    pub relaxed_budget: bool,
+    /// fzf-style composite ranking score (see [`super::detect::composite_score`]):
+    /// a weighted blend of normalized geometric distance, OCR confidence,
+    /// confusion-weighted text similarity, and a dominant-position bonus, comparable
+    /// across stages and pages unlike `score`/`distance`. Defaults to `0.0` until set
+    /// via [`PageNumberMatch::with_composite_score`].
+    pub composite_score: f64,
 }
 
 impl PageNumberMatch {
@@ -342,17 +784,35 @@ impl PageNumberMatch {
         stage: MatchStage,
         score: f64,
         distance: f64,
-        expected_number: u32,
+        expected_label: PageLabel,
     ) -> Self {
         Self {
             candidate,
             stage,
             score,
             distance,
-            expected_number,
+            expected_label,
+            relaxed_budget: false,
+            composite_score: 0.0,
         }
     }
 
+    /// Same as [`PageNumberMatch::new`], but for a Stage 2 match that should record
+    /// whether it only passed because of the relaxed length-adaptive typo budget.
+    #[must_use]
+    pub fn with_relaxed_budget(mut self, relaxed_budget: bool) -> Self {
+        self.relaxed_budget = relaxed_budget;
+        self
+    }
+
+    /// Same as [`PageNumberMatch::new`], but attaching the fzf-style composite
+    /// ranking score computed for the winning candidate.
+    #[must_use]
+    pub fn with_composite_score(mut self, composite_score: f64) -> Self {
+        self.composite_score = composite_score;
+        self
+    }
+
     /// Check if this is an exact match
     pub fn is_exact(&self) -> bool {
         self.stage == MatchStage::ExactMatch
@@ -386,6 +846,14 @@ pub struct PageNumberOptions {
     pub numbers_only: bool,
     /// Position hint
     pub position_hint: Option<PageNumberPosition>,
+    /// Auto-detect page orientation (OSD) and rotate before searching for numbers
+    pub auto_rotate: bool,
+    /// Regex templates for labeled page numbers (e.g. "Page 12", "- 12 -", "12 / 340"),
+    /// tried in order against each detected line of text; see [`DEFAULT_LABEL_PATTERNS`]
+    pub label_patterns: Vec<String>,
+    /// Confusion-weighted substitution costs used by Stage 2 similarity matching
+    /// (see [`ConfusionPair`]); see [`DEFAULT_CONFUSION_PAIRS`]
+    pub confusion_pairs: Vec<ConfusionPair>,
 }
 
 impl Default for PageNumberOptions {
@@ -396,6 +864,9 @@ impl Default for PageNumberOptions {
             min_confidence: DEFAULT_MIN_CONFIDENCE,
             numbers_only: true,
             position_hint: None,
+            auto_rotate: true,
+            label_patterns: DEFAULT_LABEL_PATTERNS.iter().map(|s| s.to_string()).collect(),
+            confusion_pairs: DEFAULT_CONFUSION_PAIRS.to_vec(),
         }
     }
 }
@@ -474,6 +945,29 @@ impl PageNumberOptionsBuilder {
         self
     }
 
+    /// Set whether to run orientation/script detection (OSD) and auto-rotate
+    /// before searching for page numbers
+    #[must_use]
+    pub fn auto_rotate(mut self, enabled: bool) -> Self {
+        self.options.auto_rotate = enabled;
+        self
+    }
+
+    /// Set the regex templates used to recognize labeled page numbers
+    #[must_use]
+    pub fn label_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.options.label_patterns = patterns;
+        self
+    }
+
+    /// Set the confusion-weighted substitution costs used by Stage 2 similarity
+    /// matching, e.g. to add language-specific glyph confusions
+    #[must_use]
+    pub fn confusion_pairs(mut self, pairs: Vec<ConfusionPair>) -> Self {
+        self.options.confusion_pairs = pairs;
+        self
+    }
+
     /// Build the options
     #[must_use]
     pub fn build(self) -> PageNumberOptions {
@@ -518,6 +1012,21 @@ mod tests {
         assert_eq!(opts.search_region_percent, 10.0);
         assert_eq!(opts.min_confidence, 60.0);
         assert!(opts.numbers_only);
+        assert!(opts.auto_rotate);
+    }
+
+    #[test]
+    fn test_page_number_options_builder_auto_rotate() {
+        let opts = PageNumberOptions::builder().auto_rotate(false).build();
+        assert!(!opts.auto_rotate);
+    }
+
+    #[test]
+    fn test_page_number_options_builder_confusion_pairs() {
+        let opts = PageNumberOptions::builder()
+            .confusion_pairs(vec![('3', 'E', 0.1)])
+            .build();
+        assert_eq!(opts.confusion_pairs, vec![('3', 'E', 0.1)]);
     }
 
     #[test]
@@ -630,6 +1139,7 @@ mod tests {
             },
             confidence: 95.5,
             raw_text: "42".to_string(),
+            label: None,
         };
 
         assert_eq!(detected.page_index, 5);
@@ -638,6 +1148,45 @@ mod tests {
         assert_eq!(detected.raw_text, "42");
     }
 
+    fn detected_at(x: u32, y: u32) -> DetectedPageNumber {
+        DetectedPageNumber {
+            page_index: 0,
+            number: Some(1),
+            position: PageNumberRect {
+                x,
+                y,
+                width: 50,
+                height: 20,
+            },
+            confidence: 0.9,
+            raw_text: "1".to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_reading_order_cmp_orders_by_vertical_band_first() {
+        let top = detected_at(900, 50);
+        let bottom = detected_at(100, 950);
+        assert_eq!(top.reading_order_cmp(&bottom), std::cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn test_reading_order_cmp_orders_left_to_right_within_a_band() {
+        let left = detected_at(100, 900);
+        let right = detected_at(900, 900);
+        assert_eq!(left.reading_order_cmp(&right), std::cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn test_reading_order_cmp_snaps_y_to_a_tolerance_band() {
+        // A few pixels of scan skew should not split boxes onto different
+        // "lines"; they should still compare by x within the same band.
+        let left = detected_at(100, 900);
+        let right = detected_at(900, 903);
+        assert_eq!(left.reading_order_cmp(&right), std::cmp::Ordering::Less);
+    }
+
     #[test]
     fn test_error_types() {
         let _err1 = PageNumberError::ImageNotFound(PathBuf::from("/test/path"));
@@ -709,6 +1258,63 @@ mod tests {
         assert_eq!(expanded.height, 120);
     }
 
+    #[test]
+    fn test_rectangle_contains_rect() {
+        let outer = Rectangle::new(0, 0, 100, 100);
+        let inner = Rectangle::new(10, 10, 50, 50);
+        let overflowing = Rectangle::new(10, 10, 200, 50);
+
+        assert!(outer.contains_rect(&inner));
+        assert!(!outer.contains_rect(&overflowing));
+        assert!(!inner.contains_rect(&outer));
+    }
+
+    #[test]
+    fn test_rectangle_intersect_overlapping() {
+        let rect1 = Rectangle::new(0, 0, 100, 100);
+        let rect2 = Rectangle::new(50, 50, 100, 100);
+
+        let intersection = rect1.intersect(&rect2).unwrap();
+        assert_eq!(intersection, Rectangle::new(50, 50, 50, 50));
+    }
+
+    #[test]
+    fn test_rectangle_intersect_disjoint_is_none() {
+        let rect1 = Rectangle::new(0, 0, 10, 10);
+        let rect2 = Rectangle::new(100, 100, 10, 10);
+
+        assert!(rect1.intersect(&rect2).is_none());
+    }
+
+    #[test]
+    fn test_rectangle_intersect_touching_edges_is_none() {
+        // Sharing only a boundary edge is not an overlap, matching the
+        // strict inequality used by `overlaps`.
+        let rect1 = Rectangle::new(0, 0, 10, 10);
+        let rect2 = Rectangle::new(10, 0, 10, 10);
+
+        assert!(rect1.intersect(&rect2).is_none());
+    }
+
+    #[test]
+    fn test_rectangle_union() {
+        let rect1 = Rectangle::new(0, 0, 50, 50);
+        let rect2 = Rectangle::new(100, 100, 50, 50);
+
+        let union = rect1.union(&rect2);
+        assert_eq!(union, Rectangle::new(0, 0, 150, 150));
+    }
+
+    #[test]
+    fn test_rectangle_union_contains_both_inputs() {
+        let rect1 = Rectangle::new(10, 20, 30, 10);
+        let rect2 = Rectangle::new(0, 0, 5, 5);
+
+        let union = rect1.union(&rect2);
+        assert!(union.contains_rect(&rect1));
+        assert!(union.contains_rect(&rect2));
+    }
+
     #[test]
     fn test_page_number_candidate_new() {
         let candidate = PageNumberCandidate::new(
@@ -744,6 +1350,207 @@ mod tests {
         assert!(!candidate.ocr_success);
     }
 
+    // ============================================================
+    // Non-Western Numeral Parsing Tests
+    // ============================================================
+
+    #[test]
+    fn test_parse_numeral_fullwidth_digits() {
+        let candidate = PageNumberCandidate::new("４２".to_string(), Rectangle::new(0, 0, 1, 1), 0.9);
+        assert_eq!(candidate.number, Some(42));
+    }
+
+    #[test]
+    fn test_parse_numeral_arabic_indic_digits() {
+        let candidate = PageNumberCandidate::new("٤٢".to_string(), Rectangle::new(0, 0, 1, 1), 0.9);
+        assert_eq!(candidate.number, Some(42));
+    }
+
+    #[test]
+    fn test_parse_numeral_devanagari_digits() {
+        let candidate = PageNumberCandidate::new("४२".to_string(), Rectangle::new(0, 0, 1, 1), 0.9);
+        assert_eq!(candidate.number, Some(42));
+    }
+
+    #[test]
+    fn test_parse_numeral_kanji_single_digit() {
+        let candidate = PageNumberCandidate::new("七".to_string(), Rectangle::new(0, 0, 1, 1), 0.9);
+        assert_eq!(candidate.number, Some(7));
+    }
+
+    #[test]
+    fn test_parse_numeral_kanji_tens() {
+        assert_eq!(parse_numeral("十"), Some(10));
+        assert_eq!(parse_numeral("十二"), Some(12));
+        assert_eq!(parse_numeral("三十"), Some(30));
+        assert_eq!(parse_numeral("三十七"), Some(37));
+    }
+
+    #[test]
+    fn test_parse_numeral_non_digit_text_rejected() {
+        assert_eq!(parse_numeral("4a"), None);
+        assert_eq!(parse_numeral("abc"), None);
+    }
+
+    // ============================================================
+    // PageLabel Parsing Tests
+    // ============================================================
+
+    #[test]
+    fn test_page_label_parse_arabic() {
+        let label = PageLabel::parse("42").unwrap();
+        assert_eq!(label.style, PageLabelStyle::Arabic);
+        assert_eq!(label.ordinal, 42);
+        assert_eq!(label.prefix, None);
+    }
+
+    #[test]
+    fn test_page_label_parse_roman_lower() {
+        let label = PageLabel::parse("xii").unwrap();
+        assert_eq!(label.style, PageLabelStyle::RomanLower);
+        assert_eq!(label.ordinal, 12);
+    }
+
+    #[test]
+    fn test_page_label_parse_roman_upper() {
+        let label = PageLabel::parse("XII").unwrap();
+        assert_eq!(label.style, PageLabelStyle::RomanUpper);
+        assert_eq!(label.ordinal, 12);
+    }
+
+    #[test]
+    fn test_page_label_parse_roman_mixed_case_rejected() {
+        // Mixed case is more likely an OCR artifact than a deliberate numeral
+        assert!(PageLabel::parse("Xii").is_none());
+    }
+
+    #[test]
+    fn test_page_label_parse_roman_rejects_non_canonical_repetition() {
+        // "iiii" is not valid Roman notation (4 is "iv"); OCR noise, not a
+        // real page label.
+        assert!(PageLabel::parse("iiii").is_none());
+    }
+
+    #[test]
+    fn test_page_label_parse_roman_rejects_invalid_subtractive_pair() {
+        // "vx" is not a valid subtractive combination in either direction.
+        assert!(PageLabel::parse("vx").is_none());
+    }
+
+    #[test]
+    fn test_page_label_parse_letters_yields_to_roman_for_valid_roman_letters() {
+        // "c" is ALSO a valid single-letter Roman numeral (100); Roman
+        // parsing is tried first and wins.
+        let label = PageLabel::parse("c").unwrap();
+        assert_eq!(label.style, PageLabelStyle::RomanLower);
+        assert_eq!(label.ordinal, 100);
+    }
+
+    #[test]
+    fn test_page_label_parse_letters_non_roman_letter() {
+        // "b" is not a valid Roman-numeral symbol at all, so it falls
+        // through to a bare lettered label: ordinal 2.
+        let label = PageLabel::parse("b").unwrap();
+        assert_eq!(label.style, PageLabelStyle::Letters);
+        assert_eq!(label.ordinal, 2);
+    }
+
+    #[test]
+    fn test_page_label_parse_letters_multi_char() {
+        let label = PageLabel::parse("aa").unwrap();
+        assert_eq!(label.style, PageLabelStyle::Letters);
+        assert_eq!(label.ordinal, 27);
+    }
+
+    #[test]
+    fn test_page_label_parse_alpha_prefix() {
+        let label = PageLabel::parse("A-12").unwrap();
+        assert_eq!(label.style, PageLabelStyle::Alpha);
+        assert_eq!(label.ordinal, 12);
+        assert_eq!(label.prefix, Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_page_label_parse_alpha_suffix() {
+        let label = PageLabel::parse("12b").unwrap();
+        assert_eq!(label.style, PageLabelStyle::Alpha);
+        assert_eq!(label.ordinal, 12);
+        assert_eq!(label.prefix, Some("b".to_string()));
+    }
+
+    #[test]
+    fn test_page_label_parse_unrecognizable() {
+        assert!(PageLabel::parse("Chapter One").is_none());
+        assert!(PageLabel::parse("").is_none());
+    }
+
+    #[test]
+    fn test_page_label_render_round_trips() {
+        assert_eq!(PageLabel::arabic(42).render(), "42");
+        assert_eq!(
+            PageLabel {
+                style: PageLabelStyle::RomanLower,
+                ordinal: 14,
+                prefix: None,
+            }
+            .render(),
+            "xiv"
+        );
+        assert_eq!(
+            PageLabel {
+                style: PageLabelStyle::RomanUpper,
+                ordinal: 14,
+                prefix: None,
+            }
+            .render(),
+            "XIV"
+        );
+        assert_eq!(
+            PageLabel {
+                style: PageLabelStyle::Alpha,
+                ordinal: 12,
+                prefix: Some("a".to_string()),
+            }
+            .render(),
+            "a-12"
+        );
+        assert_eq!(
+            PageLabel {
+                style: PageLabelStyle::Letters,
+                ordinal: 27,
+                prefix: None,
+            }
+            .render(),
+            "aa"
+        );
+    }
+
+    #[test]
+    fn test_page_label_equality_distinguishes_style() {
+        // Same ordinal, different scheme: must not compare equal
+        assert_ne!(
+            PageLabel::arabic(12),
+            PageLabel {
+                style: PageLabelStyle::RomanLower,
+                ordinal: 12,
+                prefix: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_page_number_candidate_label_populated() {
+        let candidate = PageNumberCandidate::new("xii".to_string(), Rectangle::new(0, 0, 1, 1), 0.9);
+        assert_eq!(
+            candidate.label,
+            Some(PageLabel {
+                style: PageLabelStyle::RomanLower,
+                ordinal: 12,
+                prefix: None,
+            })
+        );
+    }
+
     #[test]
     fn test_match_stage_number() {
         assert_eq!(MatchStage::ExactMatch.stage_number(), 1);
@@ -772,10 +1579,10 @@ mod tests {
             MatchStage::ExactMatch,
             1.0,
             10.0,
-            42,
+            PageLabel::arabic(42),
         );
         assert!(match_result.is_exact());
-        assert_eq!(match_result.expected_number, 42);
+        assert_eq!(match_result.expected_label, PageLabel::arabic(42));
         assert_eq!(match_result.distance, 10.0);
     }
 
@@ -792,14 +1599,14 @@ mod tests {
             MatchStage::ExactMatch,
             1.0,
             10.0,
-            42,
+            PageLabel::arabic(42),
         );
         let fallback_match = PageNumberMatch::new(
             candidate,
             MatchStage::FallbackMatch,
             0.5,
             10.0,
-            42,
+            PageLabel::arabic(42),
         );
 
         // Exact match should have higher quality
@@ -0,0 +1,205 @@
+//! JSONPath queries over structured page output
+//!
+//! Backs the `superbook-pdf query <doc.json> '<jsonpath>'` subcommand:
+//! evaluates a JSONPath expression (e.g. `$[?(@.confidence < 0.5)].page_index`)
+//! against the page records written by [`crate::ndjson_output`] (NDJSON, a
+//! pretty-JSON combined document, or a bare JSON array all work) and returns
+//! the matching nodes, either as raw JSON or flattened one-match-per-line
+//! text for shell pipelines.
+
+use std::path::Path;
+use thiserror::Error;
+
+use serde_json::Value;
+
+/// Error type for JSONPath queries over structured output
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("invalid JSONPath expression {0:?}: {1}")]
+    InvalidJsonPath(String, String),
+
+    #[error("{0:?} contains neither a JSON array, a {{\"pages\": [...]}} document, nor NDJSON lines")]
+    UnrecognizedDocument(String),
+}
+
+/// How [`QueryResult::format`] renders query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOutputFormat {
+    /// A pretty-printed JSON array of the matched nodes
+    Json,
+    /// One match per line: strings are printed raw, everything else as
+    /// compact JSON, so the output composes with shell pipelines
+    Text,
+}
+
+/// Result of evaluating a JSONPath query
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub matches: Vec<Value>,
+}
+
+impl QueryResult {
+    /// `false` means the caller should exit non-zero
+    pub fn has_matches(&self) -> bool {
+        !self.matches.is_empty()
+    }
+
+    pub fn format(&self, format: QueryOutputFormat) -> String {
+        match format {
+            QueryOutputFormat::Json => {
+                serde_json::to_string_pretty(&self.matches).unwrap_or_else(|_| "[]".to_string())
+            }
+            QueryOutputFormat::Text => self
+                .matches
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Load page records from `path`, accepting NDJSON (one object per line), a
+/// pretty-JSON `{"title": ..., "pages": [...]}` document, or a bare JSON
+/// array of page records
+fn load_page_records(path: &Path) -> Result<Vec<Value>, QueryError> {
+    let content = std::fs::read_to_string(path)?;
+    let trimmed = content.trim();
+
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed) {
+        match value {
+            Value::Array(items) => return Ok(items),
+            Value::Object(ref map) => {
+                if let Some(Value::Array(pages)) = map.get("pages") {
+                    return Ok(pages.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let lines: Result<Vec<Value>, _> = trimmed
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<Value>)
+        .collect();
+    match lines {
+        Ok(records) if !records.is_empty() => Ok(records),
+        _ => Err(QueryError::UnrecognizedDocument(path.display().to_string())),
+    }
+}
+
+/// Evaluate `jsonpath_expr` against the page records in `doc_path`
+pub fn run_query(doc_path: &Path, jsonpath_expr: &str) -> Result<QueryResult, QueryError> {
+    let records = load_page_records(doc_path)?;
+    let document = Value::Array(records);
+
+    let matches = jsonpath_lib::select(&document, jsonpath_expr)
+        .map_err(|e| QueryError::InvalidJsonPath(jsonpath_expr.to_string(), e.to_string()))?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    Ok(QueryResult { matches })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_page(index: usize, confidence: f64) -> Value {
+        serde_json::json!({
+            "page_index": index,
+            "confidence": confidence,
+            "text_blocks": [{"text": "hello", "direction": "Horizontal"}],
+        })
+    }
+
+    #[test]
+    fn test_load_page_records_from_bare_array() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.json");
+        std::fs::write(&path, serde_json::to_string(&vec![sample_page(0, 0.9)]).unwrap()).unwrap();
+
+        let records = load_page_records(&path).unwrap();
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn test_load_page_records_from_pretty_json_document() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.json");
+        let doc = serde_json::json!({"title": "t", "pages": [sample_page(0, 0.9), sample_page(1, 0.2)]});
+        std::fs::write(&path, serde_json::to_string(&doc).unwrap()).unwrap();
+
+        let records = load_page_records(&path).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_load_page_records_from_ndjson() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.ndjson");
+        let lines = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&sample_page(0, 0.9)).unwrap(),
+            serde_json::to_string(&sample_page(1, 0.2)).unwrap()
+        );
+        std::fs::write(&path, lines).unwrap();
+
+        let records = load_page_records(&path).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_run_query_filters_low_confidence_pages() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.json");
+        let doc = vec![sample_page(0, 0.9), sample_page(1, 0.2), sample_page(2, 0.4)];
+        std::fs::write(&path, serde_json::to_string(&doc).unwrap()).unwrap();
+
+        let result = run_query(&path, "$[?(@.confidence < 0.5)].page_index").unwrap();
+        assert!(result.has_matches());
+        assert_eq!(result.matches, vec![Value::from(1), Value::from(2)]);
+    }
+
+    #[test]
+    fn test_run_query_no_matches_reports_empty() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("pages.json");
+        let doc = vec![sample_page(0, 0.9)];
+        std::fs::write(&path, serde_json::to_string(&doc).unwrap()).unwrap();
+
+        let result = run_query(&path, "$[?(@.confidence < 0.1)].page_index").unwrap();
+        assert!(!result.has_matches());
+    }
+
+    #[test]
+    fn test_query_result_format_text_joins_with_newlines() {
+        let result = QueryResult {
+            matches: vec![Value::from(1), Value::from(2)],
+        };
+        assert_eq!(result.format(QueryOutputFormat::Text), "1\n2");
+    }
+
+    #[test]
+    fn test_load_page_records_unrecognized_document_errors() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("not_json.txt");
+        std::fs::write(&path, "this is not json at all").unwrap();
+
+        assert!(matches!(
+            load_page_records(&path),
+            Err(QueryError::UnrecognizedDocument(_))
+        ));
+    }
+}
@@ -0,0 +1,483 @@
+//! RAG-oriented chunked output
+//!
+//! `generate_page_markdown` produces one flat Markdown string per page,
+//! sized for a human reader rather than the context window of an
+//! embedding model. `RagChunker` walks the same `ContentElement` stream
+//! [`MarkdownGenerator::build_page_content`] already assembles and
+//! re-groups it into token-budgeted chunks suited to a Retrieval-Augmented
+//! Generation index: each chunk carries the heading breadcrumb it falls
+//! under and the source page(s) it was drawn from, and text is never split
+//! across a figure or full-page image.
+//!
+//! The heading-stack walk mirrors that of [`DocumentTree`](crate::document_tree::DocumentTree)
+//! (a `##`/`###` heading closes any open heading at the same or a deeper
+//! level before it is pushed), but threads page indices through instead
+//! of building a tree, since a flat chunk sequence - not a hierarchy - is
+//! what a retrieval index wants.
+//!
+//! [`MarkdownGenerator::build_page_content`]: crate::markdown_gen::MarkdownGenerator::build_page_content
+
+use crate::markdown_gen::{ContentElement, MarkdownGenError, PageContent};
+
+/// Default token budget per chunk, used unless overridden via
+/// [`RagChunker::with_token_budget`]
+pub const DEFAULT_TOKEN_BUDGET: usize = 512;
+
+/// Default number of trailing tokens repeated at the start of the next
+/// chunk when a budget split occurs, used unless overridden via
+/// [`RagChunker::with_overlap_tokens`]
+pub const DEFAULT_OVERLAP_TOKENS: usize = 64;
+
+/// Kind of element that contributed text to a chunk, carried as metadata so a
+/// retrieval pipeline can weight or filter by element type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RagElementKind {
+    Heading,
+    Paragraph,
+    Figure,
+    FullPageImage,
+}
+
+/// One retrieval-ready chunk of a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct RagChunk {
+    /// Chunk text, with its heading breadcrumb already prepended
+    pub text: String,
+    /// 0-indexed source pages the text in this chunk was drawn from, in
+    /// ascending document order
+    pub pages: Vec<usize>,
+    /// Open heading titles above this chunk, shallowest first (the `##`/`###`
+    /// analogue of an H1 > H2 > H3 breadcrumb)
+    pub heading_path: Vec<String>,
+    /// Element kinds of the elements accumulated into this chunk, in the
+    /// order first encountered; the prepended heading breadcrumb does not
+    /// add [`RagElementKind::Heading`]
+    pub element_kinds: Vec<RagElementKind>,
+    /// Bounding box this chunk's content was detected at when the upstream
+    /// pipeline carries one. `ContentElement` does not retain per-element
+    /// geometry today, so this is always `None`; the field exists so a
+    /// future `ContentElement` that does carry a bbox does not need a
+    /// breaking change here.
+    pub bbox: Option<(i32, i32, i32, i32)>,
+}
+
+/// Walks the pages of a document in reading order and re-groups them into
+/// token-budgeted [`RagChunk`]s. See the [module docs](self) for the
+/// splitting rules.
+pub struct RagChunker {
+    token_budget: usize,
+    overlap_tokens: usize,
+    count_tokens: Box<dyn Fn(&str) -> usize>,
+}
+
+impl Default for RagChunker {
+    fn default() -> Self {
+        Self {
+            token_budget: DEFAULT_TOKEN_BUDGET,
+            overlap_tokens: DEFAULT_OVERLAP_TOKENS,
+            count_tokens: Box::new(approximate_tokens),
+        }
+    }
+}
+
+impl RagChunker {
+    /// Create a chunker with the default token budget, overlap, and the
+    /// `chars/4` token-count approximation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum tokens (per [`Self::count_tokens`]) the body of a chunk may
+    /// hold before it is flushed. Defaults to [`DEFAULT_TOKEN_BUDGET`].
+    pub fn with_token_budget(mut self, tokens: usize) -> Self {
+        self.token_budget = tokens;
+        self
+    }
+
+    /// Trailing tokens repeated at the start of the next chunk across a
+    /// budget split, to preserve context over the boundary. Defaults to
+    /// [`DEFAULT_OVERLAP_TOKENS`].
+    pub fn with_overlap_tokens(mut self, tokens: usize) -> Self {
+        self.overlap_tokens = tokens;
+        self
+    }
+
+    /// Override the `chars/4` token-count heuristic with a real tokenizer
+    pub fn with_token_counter(mut self, counter: impl Fn(&str) -> usize + 'static) -> Self {
+        self.count_tokens = Box::new(counter);
+        self
+    }
+
+    /// Chunk `pages` in document order, carrying the heading stack across
+    /// page boundaries the same way [`DocumentTree::push_page`](crate::document_tree::DocumentTree::push_page) does
+    pub fn chunk_pages(&self, pages: &[PageContent]) -> Vec<RagChunk> {
+        let mut state = ChunkerState::new();
+        for page in pages {
+            for element in &page.elements {
+                match element {
+                    ContentElement::Text { content, .. } => {
+                        self.push_text(&mut state, page.page_index, content);
+                    }
+                    ContentElement::Figure {
+                        image_path,
+                        caption,
+                    } => {
+                        self.flush(&mut state, false);
+                        let text = format!(
+                            "{}![{}]({})",
+                            breadcrumb(&state.heading_path),
+                            caption.as_deref().unwrap_or("図"),
+                            image_path.display()
+                        );
+                        state.chunks.push(RagChunk {
+                            text,
+                            pages: vec![page.page_index],
+                            heading_path: titles(&state.heading_path),
+                            element_kinds: vec![RagElementKind::Figure],
+                            bbox: None,
+                        });
+                    }
+                    ContentElement::FullPageImage { image_path } => {
+                        self.flush(&mut state, false);
+                        let text = format!(
+                            "{}![]({})",
+                            breadcrumb(&state.heading_path),
+                            image_path.display()
+                        );
+                        state.chunks.push(RagChunk {
+                            text,
+                            pages: vec![page.page_index],
+                            heading_path: titles(&state.heading_path),
+                            element_kinds: vec![RagElementKind::FullPageImage],
+                            bbox: None,
+                        });
+                    }
+                    ContentElement::PageBreak => {}
+                }
+            }
+        }
+        self.flush(&mut state, false);
+        state.chunks
+    }
+
+    /// Split `content` into paragraphs the same way [`DocumentTree::push_text`](crate::document_tree::DocumentTree::push_text)
+    /// does, updating the heading stack on `"## "`/`"### "` paragraphs and
+    /// accumulating everything else into the current chunk
+    fn push_text(&self, state: &mut ChunkerState, page_index: usize, content: &str) {
+        for paragraph in content.split("\n\n") {
+            let trimmed = paragraph.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let mut lines = trimmed.splitn(2, '\n');
+            let first = lines.next().unwrap_or("");
+            let rest = lines.next();
+
+            if let Some(title) = first.strip_prefix("### ") {
+                self.push_heading(state, 3, title.trim());
+            } else if let Some(title) = first.strip_prefix("## ") {
+                self.push_heading(state, 2, title.trim());
+            } else {
+                self.push_body(state, page_index, trimmed);
+                continue;
+            }
+
+            if let Some(rest) = rest {
+                let rest = rest.trim();
+                if !rest.is_empty() {
+                    self.push_body(state, page_index, rest);
+                }
+            }
+        }
+    }
+
+    /// Pop any open heading at the same or a deeper level, flush whatever chunk
+    /// was accumulating under the old heading path (a new heading is a
+    /// semantic break, so no overlap carries across it), then push the new
+    /// heading onto the stack
+    fn push_heading(&self, state: &mut ChunkerState, level: u8, title: &str) {
+        self.flush(state, false);
+        while matches!(state.heading_path.last(), Some((top, _)) if *top >= level) {
+            state.heading_path.pop();
+        }
+        state.heading_path.push((level, title.to_string()));
+    }
+
+    /// Append `text` to the current chunk, flushing first (carrying
+    /// overlap) if appending it would push the chunk over the token budget
+    fn push_body(&self, state: &mut ChunkerState, page_index: usize, text: &str) {
+        if !state.body.is_empty()
+            && (self.count_tokens)(&format!("{}\n\n{}", state.body, text)) > self.token_budget
+        {
+            self.flush(state, true);
+        }
+        if !state.body.is_empty() {
+            state.body.push_str("\n\n");
+        }
+        state.body.push_str(text);
+        if state.pages.last() != Some(&page_index) {
+            state.pages.push(page_index);
+        }
+        if state.element_kinds.last() != Some(&RagElementKind::Paragraph) {
+            state.element_kinds.push(RagElementKind::Paragraph);
+        }
+    }
+
+    /// Emit the current chunk (if it has any body text) and reset the
+    /// accumulator. When `carry_overlap` is set, the trailing
+    /// `self.overlap_tokens` worth of the flushed body seeds the next
+    /// chunk, so context survives a budget split.
+    fn flush(&self, state: &mut ChunkerState, carry_overlap: bool) {
+        if state.body.is_empty() {
+            return;
+        }
+
+        let heading_path = state.heading_path.clone();
+        let text = format!("{}{}", breadcrumb(&heading_path), state.body);
+        state.chunks.push(RagChunk {
+            text,
+            pages: std::mem::take(&mut state.pages),
+            heading_path: titles(&heading_path),
+            element_kinds: std::mem::take(&mut state.element_kinds),
+            bbox: None,
+        });
+
+        let overlap = if carry_overlap {
+            self.trailing_overlap(&state.body)
+        } else {
+            String::new()
+        };
+        state.body = overlap;
+    }
+
+    /// Largest trailing run of whole words from `body` whose token count
+    /// (per [`Self::count_tokens`]) does not exceed `self.overlap_tokens`
+    fn trailing_overlap(&self, body: &str) -> String {
+        let words: Vec<&str> = body.split_whitespace().collect();
+        let mut best = String::new();
+        for start in (0..words.len()).rev() {
+            let candidate = words[start..].join(" ");
+            if (self.count_tokens)(&candidate) > self.overlap_tokens {
+                break;
+            }
+            best = candidate;
+        }
+        best
+    }
+}
+
+/// Mutable accumulator threaded through [`RagChunker::chunk_pages`]
+struct ChunkerState {
+    heading_path: Vec<(u8, String)>,
+    body: String,
+    pages: Vec<usize>,
+    element_kinds: Vec<RagElementKind>,
+    chunks: Vec<RagChunk>,
+}
+
+impl ChunkerState {
+    fn new() -> Self {
+        Self {
+            heading_path: Vec::new(),
+            body: String::new(),
+            pages: Vec::new(),
+            element_kinds: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+}
+
+/// Render `heading_path` as `"## "`/`"### "` lines, matching the markup
+/// produced by [`MarkdownRenderer::emit_heading`](crate::markdown_gen::MarkdownRenderer)
+fn breadcrumb(heading_path: &[(u8, String)]) -> String {
+    let mut out = String::new();
+    for (level, title) in heading_path {
+        out.push_str(&"#".repeat(*level as usize));
+        out.push(' ');
+        out.push_str(title);
+        out.push_str("\n\n");
+    }
+    out
+}
+
+fn titles(heading_path: &[(u8, String)]) -> Vec<String> {
+    heading_path.iter().map(|(_, title)| title.clone()).collect()
+}
+
+/// `chars/4` token-count heuristic, the default when no real tokenizer is
+/// supplied via [`RagChunker::with_token_counter`]
+fn approximate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Serialize `chunks` as JSONL (one [`RagChunk`] per line), the shape a
+/// retrieval pipeline ingests. Chunks do not derive `serde::Serialize`
+/// directly since `RagElementKind` and `(i32, i32, i32, i32)` would need
+/// their own wire format decisions; this keeps that choice in one place.
+pub fn to_jsonl(chunks: &[RagChunk]) -> Result<String, MarkdownGenError> {
+    let mut out = String::new();
+    for chunk in chunks {
+        let line = serde_json::json!({
+            "text": chunk.text,
+            "pages": chunk.pages,
+            "heading_path": chunk.heading_path,
+            "element_kinds": chunk.element_kinds.iter().map(element_kind_name).collect::<Vec<_>>(),
+            "bbox": chunk.bbox,
+        });
+        out.push_str(
+            &serde_json::to_string(&line)
+                .map_err(|e| MarkdownGenError::GenerationError(e.to_string()))?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn element_kind_name(kind: &RagElementKind) -> &'static str {
+    match kind {
+        RagElementKind::Heading => "heading",
+        RagElementKind::Paragraph => "paragraph",
+        RagElementKind::Figure => "figure",
+        RagElementKind::FullPageImage => "full_page_image",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yomitoku::TextDirection;
+    use std::path::PathBuf;
+
+    fn text_page(page_index: usize, content: &str) -> PageContent {
+        PageContent {
+            page_index,
+            elements: vec![ContentElement::Text {
+                content: content.to_string(),
+                direction: TextDirection::Horizontal,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_single_paragraph_becomes_one_chunk() {
+        let pages = vec![text_page(0, "Hello world.")];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "Hello world.");
+        assert_eq!(chunks[0].pages, vec![0]);
+        assert!(chunks[0].heading_path.is_empty());
+    }
+
+    #[test]
+    fn test_heading_is_prepended_to_its_body_chunk() {
+        let pages = vec![text_page(0, "## Chapter One\nThe story begins.")];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "## Chapter One\n\nThe story begins.");
+        assert_eq!(chunks[0].heading_path, vec!["Chapter One".to_string()]);
+    }
+
+    #[test]
+    fn test_nested_heading_breadcrumb() {
+        let pages = vec![text_page(
+            0,
+            "## Chapter One\n\n### Section A\n\nBody text.",
+        )];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].heading_path,
+            vec!["Chapter One".to_string(), "Section A".to_string()]
+        );
+        assert!(chunks[0].text.starts_with("## Chapter One\n\n### Section A\n\n"));
+    }
+
+    #[test]
+    fn test_sibling_heading_closes_prior_section() {
+        let pages = vec![text_page(
+            0,
+            "## Chapter One\n\nFirst body.\n\n## Chapter Two\n\nSecond body.",
+        )];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].heading_path, vec!["Chapter One".to_string()]);
+        assert_eq!(chunks[1].heading_path, vec!["Chapter Two".to_string()]);
+        assert!(chunks[1].text.contains("Second body."));
+        assert!(!chunks[1].text.contains("First body."));
+    }
+
+    #[test]
+    fn test_budget_split_carries_overlap() {
+        let pages = vec![text_page(0, "one two three\n\nfour five six")];
+        let chunks = RagChunker::new()
+            .with_token_counter(|text| text.split_whitespace().count())
+            .with_token_budget(3)
+            .with_overlap_tokens(1)
+            .chunk_pages(&pages);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].text, "one two three");
+        // The second paragraph starts a new chunk seeded with the last word
+        // of the first, so context survives the split
+        assert_eq!(chunks[1].text, "three\n\nfour five six");
+    }
+
+    #[test]
+    fn test_figure_is_never_merged_into_a_text_chunk() {
+        let pages = vec![PageContent {
+            page_index: 0,
+            elements: vec![
+                ContentElement::Text {
+                    content: "Before the figure.".to_string(),
+                    direction: TextDirection::Horizontal,
+                },
+                ContentElement::Figure {
+                    image_path: PathBuf::from("fig1.png"),
+                    caption: Some("A diagram".to_string()),
+                },
+                ContentElement::Text {
+                    content: "After the figure.".to_string(),
+                    direction: TextDirection::Horizontal,
+                },
+            ],
+        }];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].text, "Before the figure.");
+        assert_eq!(chunks[1].element_kinds, vec![RagElementKind::Figure]);
+        assert!(chunks[1].text.contains("A diagram"));
+        assert!(chunks[1].text.contains("fig1.png"));
+        assert_eq!(chunks[2].text, "After the figure.");
+    }
+
+    #[test]
+    fn test_heading_stays_open_across_page_boundary() {
+        let pages = vec![
+            text_page(0, "## Chapter One\n\nFirst page body."),
+            text_page(1, "Second page body."),
+        ];
+        let chunks = RagChunker::new().chunk_pages(&pages);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].pages, vec![0, 1]);
+        assert_eq!(chunks[0].heading_path, vec!["Chapter One".to_string()]);
+    }
+
+    #[test]
+    fn test_to_jsonl_emits_one_line_per_chunk() {
+        let pages = vec![text_page(0, "First."), text_page(1, "Second.")];
+        let chunks = RagChunker::new()
+            .with_token_counter(|t| t.split_whitespace().count())
+            .with_token_budget(1)
+            .with_overlap_tokens(0)
+            .chunk_pages(&pages);
+        let jsonl = to_jsonl(&chunks).unwrap();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), chunks.len());
+        for line in &lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value.get("text").is_some());
+            assert!(value.get("pages").is_some());
+        }
+    }
+}
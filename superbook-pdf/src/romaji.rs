@@ -0,0 +1,283 @@
+//! Kanji/kana to romaji transliteration for ASCII-safe filenames and slugs
+//!
+//! [`sanitize_filename`](crate::markdown_gen::sanitize_filename) only strips
+//! path-hostile punctuation, so merged output filenames stay non-ASCII and
+//! can break on filesystems or URLs that don't handle Unicode well. This
+//! module implements a small kakasi-style transliterator: NFKC-normalize the
+//! input, walk its characters left to right, greedily match the longest
+//! kanji run against a bundled kanji-compound dictionary (reusing
+//! [`crate::furigana::BuiltinReadingDict`], the same stand-in for a
+//! `phf`-generated table used for furigana) to get its kana reading, then
+//! convert every kana mora to romaji via a fixed lookup table, handling the
+//! small っ/ッ as gemination of the following consonant and the long vowel
+//! mark ー by repeating the preceding vowel.
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::furigana::ReadingDictionary;
+
+/// Which style [`crate::markdown_gen::MarkdownGenerator::merge_pages`] uses
+/// to derive an output filename from the document title
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameStyle {
+    /// Keep the title's Unicode characters, only stripping path-hostile
+    /// punctuation (today's default)
+    #[default]
+    Preserve,
+    /// Transliterate kanji/kana to a lowercase, underscore-separated ASCII
+    /// slug, e.g. "日本語のタイトル" -> `nihongo_no_taitoru`
+    RomajiSlug,
+}
+
+/// Mora table, longest keys first so two-character yoon moras (きゃ, しゅ, ...)
+/// are tried before their single-character components
+const MORA_TABLE: &[(&str, &str)] = &[
+    ("きゃ", "kya"), ("きゅ", "kyu"), ("きょ", "kyo"),
+    ("しゃ", "sha"), ("しゅ", "shu"), ("しょ", "sho"),
+    ("ちゃ", "cha"), ("ちゅ", "chu"), ("ちょ", "cho"),
+    ("にゃ", "nya"), ("にゅ", "nyu"), ("にょ", "nyo"),
+    ("ひゃ", "hya"), ("ひゅ", "hyu"), ("ひょ", "hyo"),
+    ("みゃ", "mya"), ("みゅ", "myu"), ("みょ", "myo"),
+    ("りゃ", "rya"), ("りゅ", "ryu"), ("りょ", "ryo"),
+    ("ぎゃ", "gya"), ("ぎゅ", "gyu"), ("ぎょ", "gyo"),
+    ("じゃ", "ja"), ("じゅ", "ju"), ("じょ", "jo"),
+    ("びゃ", "bya"), ("びゅ", "byu"), ("びょ", "byo"),
+    ("ぴゃ", "pya"), ("ぴゅ", "pyu"), ("ぴょ", "pyo"),
+    ("あ", "a"), ("い", "i"), ("う", "u"), ("え", "e"), ("お", "o"),
+    ("か", "ka"), ("き", "ki"), ("く", "ku"), ("け", "ke"), ("こ", "ko"),
+    ("さ", "sa"), ("し", "shi"), ("す", "su"), ("せ", "se"), ("そ", "so"),
+    ("た", "ta"), ("ち", "chi"), ("つ", "tsu"), ("て", "te"), ("と", "to"),
+    ("な", "na"), ("に", "ni"), ("ぬ", "nu"), ("ね", "ne"), ("の", "no"),
+    ("は", "ha"), ("ひ", "hi"), ("ふ", "fu"), ("へ", "he"), ("ほ", "ho"),
+    ("ま", "ma"), ("み", "mi"), ("む", "mu"), ("め", "me"), ("も", "mo"),
+    ("や", "ya"), ("ゆ", "yu"), ("よ", "yo"),
+    ("ら", "ra"), ("り", "ri"), ("る", "ru"), ("れ", "re"), ("ろ", "ro"),
+    ("わ", "wa"), ("を", "wo"), ("ん", "n"),
+    ("が", "ga"), ("ぎ", "gi"), ("ぐ", "gu"), ("げ", "ge"), ("ご", "go"),
+    ("ざ", "za"), ("じ", "ji"), ("ず", "zu"), ("ぜ", "ze"), ("ぞ", "zo"),
+    ("だ", "da"), ("ぢ", "ji"), ("づ", "zu"), ("で", "de"), ("ど", "do"),
+    ("ば", "ba"), ("び", "bi"), ("ぶ", "bu"), ("べ", "be"), ("ぼ", "bo"),
+    ("ぱ", "pa"), ("ぴ", "pi"), ("ぷ", "pu"), ("ぺ", "pe"), ("ぽ", "po"),
+];
+
+/// Prolonged sound mark used by katakana (e.g. コーヒー); has no hiragana
+/// equivalent, so it's handled separately from [`MORA_TABLE`]
+const LONG_VOWEL_MARK: char = 'ー';
+
+/// Small tsu, hiragana and katakana forms; triggers gemination of the
+/// following consonant rather than producing romaji of its own
+const SMALL_TSU: &[char] = &['っ', 'ッ'];
+
+fn is_kanji(c: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&c)
+}
+
+/// Length of the contiguous run of kanji characters starting at `start`
+fn kanji_run_len(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|&&c| is_kanji(c)).count()
+}
+
+/// Map a katakana character to its hiragana equivalent. Hiragana and
+/// katakana share layout in Unicode, offset by a fixed 0x60, for every
+/// character that has a hiragana counterpart; the prolonged sound mark and
+/// punctuation fall outside that range and pass through unchanged.
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' => {
+            char::from_u32(c as u32 - 0x60).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+/// Convert a run of kana (hiragana or katakana) to romaji, mora by mora.
+fn kana_to_romaji(kana: &str) -> String {
+    let chars: Vec<char> = kana.chars().map(katakana_to_hiragana).collect();
+    let mut out = String::with_capacity(kana.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if SMALL_TSU.contains(&chars[i]) {
+            // Gemination: double the first (consonant) letter of the next mora.
+            if let Some(next_romaji) = next_mora_romaji(&chars, i + 1) {
+                if let Some(first) = next_romaji.chars().next() {
+                    if first != 'a' && first != 'i' && first != 'u' && first != 'e' && first != 'o' {
+                        out.push(first);
+                    }
+                }
+            }
+            i += 1;
+            continue;
+        }
+
+        if chars[i] == LONG_VOWEL_MARK {
+            if let Some(prev_vowel) = out.chars().last() {
+                out.push(prev_vowel);
+            }
+            i += 1;
+            continue;
+        }
+
+        // Two-character yoon moras are tried before single-character ones
+        // because MORA_TABLE lists them first.
+        let mut matched = false;
+        for &(mora, romaji) in MORA_TABLE {
+            let mora_len = mora.chars().count();
+            if i + mora_len <= chars.len() && chars[i..i + mora_len].iter().copied().eq(mora.chars()) {
+                out.push_str(romaji);
+                i += mora_len;
+                matched = true;
+                break;
+            }
+        }
+
+        if !matched {
+            // Not kana at all (already-ASCII, punctuation, etc.): pass through.
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
+
+/// Look up the romaji for the mora starting at `start`, without consuming
+/// input, so [`kana_to_romaji`] can peek past a small tsu for gemination.
+fn next_mora_romaji(chars: &[char], start: usize) -> Option<&'static str> {
+    if start >= chars.len() {
+        return None;
+    }
+    for &(mora, romaji) in MORA_TABLE {
+        let mora_len = mora.chars().count();
+        if start + mora_len <= chars.len() && chars[start..start + mora_len].iter().copied().eq(mora.chars()) {
+            return Some(romaji);
+        }
+    }
+    None
+}
+
+/// Transliterate `text` to a lowercase, underscore-separated ASCII slug.
+/// Kanji runs are looked up in `dict` (longest-match, same strategy as
+/// [`crate::furigana::annotate_with_furigana`]) to get a kana reading before
+/// romanization; kana already present in `text` is romanized directly.
+/// Characters with no reading (unknown kanji, symbols) pass through as-is
+/// and are swept up by the final non-alphanumeric collapse.
+pub fn romaji_slug(text: &str, dict: &dyn ReadingDictionary) -> String {
+    let normalized: String = text.nfkc().collect();
+    let chars: Vec<char> = normalized.chars().collect();
+    let mut out = String::with_capacity(normalized.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_kanji(chars[i]) {
+            let run_len = kanji_run_len(&chars, i);
+            let max_len = dict.max_key_len().min(run_len);
+            let mut matched = None;
+
+            for len in (1..=max_len).rev() {
+                let candidate: String = chars[i..i + len].iter().collect();
+                if let Some(reading) = dict.lookup(&candidate) {
+                    matched = Some((reading, len));
+                    break;
+                }
+            }
+
+            if let Some((reading, len)) = matched {
+                out.push_str(&kana_to_romaji(reading));
+                i += len;
+                continue;
+            }
+        }
+
+        out.push_str(&kana_to_romaji(&chars[i].to_string()));
+        i += 1;
+    }
+
+    let mut slug = String::with_capacity(out.len());
+    let mut last_was_sep = false;
+    for c in out.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+            last_was_sep = false;
+        } else if !last_was_sep {
+            slug.push('_');
+            last_was_sep = true;
+        }
+    }
+
+    slug.trim_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::furigana::BuiltinReadingDict;
+
+    #[test]
+    fn test_kana_to_romaji_plain_hiragana() {
+        assert_eq!(kana_to_romaji("にほん"), "nihon");
+    }
+
+    #[test]
+    fn test_kana_to_romaji_yoon() {
+        assert_eq!(kana_to_romaji("きょう"), "kyou");
+    }
+
+    #[test]
+    fn test_kana_to_romaji_gemination() {
+        // マッチ -> "macchi" (small tsu doubles the following consonant)
+        assert_eq!(kana_to_romaji("まっち"), "macchi");
+    }
+
+    #[test]
+    fn test_kana_to_romaji_long_vowel_mark() {
+        // コーヒー -> koohii (prolonged mark repeats the preceding vowel)
+        assert_eq!(kana_to_romaji("こーひー"), "koohii");
+    }
+
+    #[test]
+    fn test_kana_to_romaji_katakana_same_as_hiragana() {
+        assert_eq!(kana_to_romaji("コーヒー"), kana_to_romaji("こーひー"));
+    }
+
+    #[test]
+    fn test_romaji_slug_kanji_compound() {
+        let dict = BuiltinReadingDict::new();
+        // 日本 -> にほん -> "nihon"
+        assert_eq!(romaji_slug("日本", &dict), "nihon");
+    }
+
+    #[test]
+    fn test_romaji_slug_mixed_kanji_and_kana() {
+        let dict = BuiltinReadingDict::new();
+        assert_eq!(romaji_slug("日本語のタイトル", &dict), "nihongo_no_taitoru");
+    }
+
+    #[test]
+    fn test_romaji_slug_lowercases_ascii() {
+        let dict = BuiltinReadingDict::new();
+        assert_eq!(romaji_slug("Report 2024", &dict), "report_2024");
+    }
+
+    #[test]
+    fn test_romaji_slug_collapses_punctuation_and_trims() {
+        let dict = BuiltinReadingDict::new();
+        assert_eq!(romaji_slug("  !!Hello, World!!  ", &dict), "hello_world");
+    }
+
+    #[test]
+    fn test_romaji_slug_unknown_kanji_passes_through_untranslated() {
+        let dict = BuiltinReadingDict::new();
+        // No dictionary entry: the raw kanji characters pass through the
+        // pipeline and get swept into a single separator by the collapse,
+        // which the final trim then strips, leaving an empty slug.
+        let result = romaji_slug("未知語", &dict);
+        assert_eq!(result, "");
+    }
+
+    #[test]
+    fn test_katakana_to_hiragana_mapping() {
+        assert_eq!(katakana_to_hiragana('ア'), 'あ');
+        assert_eq!(katakana_to_hiragana('ー'), 'ー');
+    }
+}
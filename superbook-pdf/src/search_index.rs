@@ -0,0 +1,96 @@
+//! Search-index sidecar generation
+//!
+//! The Markdown pipeline discards each page's structured OCR output
+//! (`text_blocks` with confidence and geometry) once it's been rendered to
+//! Markdown. `SearchIndexDocument` captures that per-page data in the flat
+//! `id` + single-`text`-field shape common full-text indexers (Elasticsearch,
+//! Meilisearch, Typesense) expect for bulk loading, so a converted book is
+//! immediately searchable rather than a flat Markdown blob.
+
+use std::path::Path;
+use thiserror::Error;
+
+use serde::{Deserialize, Serialize};
+
+/// Error type for search-index generation
+#[derive(Debug, Error)]
+pub enum SearchIndexError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// One OCR text block, as it appears in a [`SearchIndexDocument`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndexBlock {
+    pub text: String,
+    /// `(x, y, width, height)` in page pixel coordinates
+    pub bbox: (u32, u32, u32, u32),
+    pub confidence: f32,
+}
+
+/// One page's indexable document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndexDocument {
+    /// Primary key: the page number (1-based)
+    pub id: usize,
+    pub title: String,
+    /// Concatenated text of all blocks, the single searchable field
+    pub text: String,
+    pub blocks: Vec<SearchIndexBlock>,
+    pub has_figures: bool,
+    /// Page classification as a lowercase label (`"cover"`, `"full_page_image"`,
+    /// `"mixed"`, `"text_only"`)
+    pub classification: String,
+}
+
+/// Write `documents` to `path` as a JSON array
+pub fn write_search_index(
+    path: &Path,
+    documents: &[SearchIndexDocument],
+) -> Result<(), SearchIndexError> {
+    let json = serde_json::to_string_pretty(documents)?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_search_index_roundtrips() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("search_index.json");
+
+        let docs = vec![SearchIndexDocument {
+            id: 1,
+            title: "test".to_string(),
+            text: "テストページ".to_string(),
+            blocks: vec![SearchIndexBlock {
+                text: "テストページ".to_string(),
+                bbox: (0, 0, 100, 20),
+                confidence: 0.9,
+            }],
+            has_figures: false,
+            classification: "text_only".to_string(),
+        }];
+
+        write_search_index(&path, &docs).unwrap();
+        let loaded: Vec<SearchIndexDocument> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, 1);
+        assert_eq!(loaded[0].text, "テストページ");
+    }
+
+    #[test]
+    fn test_write_search_index_empty_list() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("search_index.json");
+        write_search_index(&path, &[]).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]");
+    }
+}
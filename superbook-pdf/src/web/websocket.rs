@@ -5,19 +5,74 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        Path, Query, State,
     },
     response::IntoResponse,
 };
-use serde::Serialize;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tracing::{debug, info_span, warn};
 use uuid::Uuid;
 
 use super::job::JobStatus;
 
+/// Inbound command sent by a client over a job's WebSocket, turning the
+/// connection into a control plane rather than a notification-only firehose
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum WsClientCommand {
+    #[serde(rename = "cancel")]
+    Cancel,
+    #[serde(rename = "pause")]
+    Pause,
+    #[serde(rename = "resume")]
+    Resume,
+    #[serde(rename = "request_preview")]
+    RequestPreview { page_number: usize, stage: String },
+    #[serde(rename = "set_preview_interval")]
+    SetPreviewInterval { ms: u64 },
+    /// Start forwarding a job's (or batch's) messages over the multiplexed
+    /// `/api/ws` connection; see [`handle_multiplexed_socket`]
+    #[serde(rename = "subscribe")]
+    Subscribe { job_id: Uuid },
+    /// Stop forwarding a previously-subscribed job/batch
+    #[serde(rename = "unsubscribe")]
+    Unsubscribe { job_id: Uuid },
+    /// Acknowledge that the client has consumed everything up to `seq`, so
+    /// the server can trim its per-job replay buffer once every live
+    /// session for that job has acked past an entry (Phase 4.8 session
+    /// subsystem); see [`WsBroadcaster::ack_session`]
+    #[serde(rename = "ack")]
+    Ack { seq: u64 },
+    /// Register an additional set of preview resolution/stage preferences
+    /// for `job_id`; preferences registered earlier for the job are kept, not
+    /// replaced. Job-scoped since the multiplexed `/api/ws`
+    /// connection can be watching several jobs at once; see
+    /// [`WsBroadcaster::subscribe_with_prefs`]. The single-job endpoint
+    /// negotiates this once at connect time via `?preview_width=`/
+    /// `?preview_stages=` instead of this command.
+    #[serde(rename = "set_preview_prefs")]
+    SetPreviewPrefs {
+        job_id: Uuid,
+        width: u32,
+        stages: Vec<String>,
+    },
+}
+
 /// WebSocket message types sent to clients
+///
+/// Every variant carries a `seq`. Broadcast messages get a per-job,
+/// monotonically increasing sequence number from [`WsBroadcaster::broadcast`]
+/// ([`WsMessage::ServerShutdown`] gets one from a server-wide counter).
+/// Messages sent straight to one connection are not numbered that way:
+/// [`WsMessage::SessionInit`], [`WsMessage::Resync`] and error replies to a
+/// client command carry `seq` 0, and [`WsMessage::Gap`] carries its
+/// `resume_seq`. Late-joining
+/// clients and reconnects use it via `?from_seq=` to replay exactly what
+/// they missed, see [`WsBroadcaster::subscribe_with_replay`].
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", content = "data")]
 pub enum WsMessage {
@@ -29,6 +84,7 @@ pub enum WsMessage {
         total_steps: u32,
         step_name: String,
         percent: u8,
+        seq: u64,
     },
     /// Status change notification
     #[serde(rename = "status_change")]
@@ -36,6 +92,7 @@ pub enum WsMessage {
         job_id: Uuid,
         old_status: JobStatus,
         new_status: JobStatus,
+        seq: u64,
     },
     /// Job completed notification
     #[serde(rename = "completed")]
@@ -44,10 +101,15 @@ pub enum WsMessage {
         download_url: String,
         elapsed_seconds: f64,
         page_count: usize,
+        seq: u64,
     },
     /// Error notification
     #[serde(rename = "error")]
-    Error { job_id: Uuid, message: String },
+    Error {
+        job_id: Uuid,
+        message: String,
+        seq: u64,
+    },
     /// Batch progress update
     #[serde(rename = "batch_progress")]
     BatchProgress {
@@ -57,6 +119,7 @@ pub enum WsMessage {
         pending: usize,
         failed: usize,
         total: usize,
+        seq: u64,
     },
     /// Batch completed notification
     #[serde(rename = "batch_completed")]
@@ -64,6 +127,7 @@ pub enum WsMessage {
         batch_id: Uuid,
         success_count: usize,
         failed_count: usize,
+        seq: u64,
     },
     /// Server shutdown notification
     #[serde(rename = "server_shutdown")]
@@ -72,81 +136,558 @@ pub enum WsMessage {
         reason: String,
         /// Time until server shuts down (in seconds)
         countdown_secs: u64,
+        seq: u64,
     },
     /// Page preview for real-time visualization (Phase 4.1)
     #[serde(rename = "page_preview")]
     PagePreview {
         job_id: Uuid,
         page_number: usize,
-        /// Base64-encoded preview image (JPEG, thumbnail size)
+        /// Base64-encoded preview image (JPEG, thumbnail size). Empty when
+        /// `unchanged_from` or `too_large` is set, since there's no new
+        /// payload to ship.
         preview_base64: String,
         /// Processing stage: "original", "deskewed", "upscaled", "normalized", "final"
         stage: String,
         /// Image dimensions
         width: u32,
         height: u32,
+        /// Set instead of shipping a duplicate blob when this stage's output
+        /// is content-identical to an earlier stage already sent for this
+        /// page (see [`WsBroadcaster::broadcast_page_preview`]); names the
+        /// stage the client already has the bytes for.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unchanged_from: Option<String>,
+        /// `true` if the encoded preview exceeded `max_preview_bytes` and was
+        /// dropped rather than flooding the channel; `preview_base64` is
+        /// empty in that case.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        too_large: bool,
+        seq: u64,
     },
+    /// Sent when a client's broadcast receiver lagged and dropped messages
+    /// (ring buffer overrun), so it knows to reconnect with `?from_seq=`
+    /// rather than silently missing progress (Phase 4.2)
+    #[serde(rename = "gap")]
+    Gap {
+        job_id: Uuid,
+        /// Number of messages the server dropped before the client caught up
+        missed: u64,
+        /// `from_seq` the client should reconnect with to resume cleanly
+        resume_seq: u64,
+        seq: u64,
+    },
+    /// Sent at the start of a connection (fresh or reconnect) to summarize
+    /// catch-up state: where replay is resuming from, and how many of this
+    /// job's messages have fallen out of the replay buffer so far (see
+    /// [`JobChannel::evicted`])
+    #[serde(rename = "resync")]
+    Resync {
+        job_id: Uuid,
+        /// From this point the client receives only messages with a `seq`
+        /// above this (buffered messages at or below it are not replayed)
+        from_seq: u64,
+        /// Total messages evicted from the replay buffer for this job so
+        /// far, across all connections (a permanently growing counter, not
+        /// scoped to this one client)
+        dropped: u64,
+        seq: u64,
+    },
+    /// Sent once, first, at the start of every connection (fresh or
+    /// reconnect): hands the client a `session_id` to present on a future
+    /// reconnect, and the sequence it can consider itself caught up through
+    /// (either `?from_seq=`, or the session's last acked sequence if
+    /// `?session_id=` resumed one). The client should periodically reply
+    /// with `WsClientCommand::Ack { seq }` as it consumes messages.
+    #[serde(rename = "session_init")]
+    SessionInit {
+        session_id: Uuid,
+        last_seq: u64,
+        seq: u64,
+    },
+}
+
+impl WsMessage {
+    /// This message's sequence number
+    pub fn seq(&self) -> u64 {
+        match self {
+            WsMessage::Progress { seq, .. }
+            | WsMessage::StatusChange { seq, .. }
+            | WsMessage::Completed { seq, .. }
+            | WsMessage::Error { seq, .. }
+            | WsMessage::BatchProgress { seq, .. }
+            | WsMessage::BatchCompleted { seq, .. }
+            | WsMessage::ServerShutdown { seq, .. }
+            | WsMessage::PagePreview { seq, .. }
+            | WsMessage::Gap { seq, .. }
+            | WsMessage::Resync { seq, .. }
+            | WsMessage::SessionInit { seq, .. } => *seq,
+        }
+    }
+
+    fn set_seq(&mut self, new_seq: u64) {
+        let seq = match self {
+            WsMessage::Progress { seq, .. }
+            | WsMessage::StatusChange { seq, .. }
+            | WsMessage::Completed { seq, .. }
+            | WsMessage::Error { seq, .. }
+            | WsMessage::BatchProgress { seq, .. }
+            | WsMessage::BatchCompleted { seq, .. }
+            | WsMessage::ServerShutdown { seq, .. }
+            | WsMessage::PagePreview { seq, .. }
+            | WsMessage::Gap { seq, .. }
+            | WsMessage::Resync { seq, .. }
+            | WsMessage::SessionInit { seq, .. } => seq,
+        };
+        *seq = new_seq;
+    }
+}
+
+/// Number of recent messages retained per job for late-join replay
+const REPLAY_BUFFER_CAPACITY: usize = 50;
+
+/// A job's (or batch's) broadcast channel plus its replay buffer and
+/// sequence counter
+struct JobChannel {
+    sender: broadcast::Sender<WsMessage>,
+    buffer: Mutex<VecDeque<(u64, WsMessage)>>,
+    next_seq: AtomicU64,
+    /// Messages evicted from `buffer` to make room for newer ones, before a
+    /// slow/reconnecting client ever had a chance to replay them
+    evicted: AtomicU64,
+    /// Messages broadcast on this channel so far, for [`JobMetricsSnapshot`]
+    messages_sent: AtomicU64,
+    /// Total JSON-serialized size of messages broadcast on this channel so
+    /// far, for [`JobMetricsSnapshot`]
+    bytes_sent: AtomicU64,
+}
+
+impl JobChannel {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            buffer: Mutex::new(VecDeque::with_capacity(REPLAY_BUFFER_CAPACITY)),
+            next_seq: AtomicU64::new(1),
+            evicted: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A snapshot of one job's broadcast throughput, for an HTTP status endpoint
+/// to report live preview/progress activity (see
+/// [`WsBroadcaster::job_metrics`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct JobMetricsSnapshot {
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    /// Replay-buffer evictions plus rate-limiter drops — every way a
+    /// message failed to reach a client through normal delivery
+    pub drops: u64,
+    pub active_subscribers: usize,
 }
 
+/// Default minimum interval between `PagePreview` sends for the same job
+/// (coalesces bursts instead of filling a slow client's buffer)
+const DEFAULT_PREVIEW_RATE_LIMIT: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Default ceiling on a single preview's base64 payload size, above which
+/// [`WsBroadcaster::broadcast_page_preview`] emits a `too_large` marker
+/// instead of the blob itself
+const DEFAULT_MAX_PREVIEW_BYTES: usize = 512 * 1024;
+
 /// Broadcaster for sending messages to connected WebSocket clients
 pub struct WsBroadcaster {
-    /// Map of job_id to broadcast sender
-    channels: RwLock<HashMap<Uuid, broadcast::Sender<WsMessage>>>,
+    /// Map of job_id to its channel, replay buffer, and sequence counter
+    channels: RwLock<HashMap<Uuid, JobChannel>>,
     /// Global channel for server-wide messages (shutdown, etc.)
     global_sender: broadcast::Sender<WsMessage>,
+    /// Sequence counter for messages sent over `global_sender`
+    global_seq: AtomicU64,
     /// Channel capacity
     capacity: usize,
+    /// Sender half of the inbound client-command channel; cloned into each
+    /// `handle_socket` task so every connection can forward commands
+    command_tx: mpsc::Sender<(Uuid, WsClientCommand)>,
+    /// Receiver half, handed out once via [`Self::take_command_receiver`] to
+    /// whatever job subsystem acts on client commands
+    command_rx: Mutex<Option<mpsc::Receiver<(Uuid, WsClientCommand)>>>,
+    /// Minimum interval between `PagePreview` sends for a given
+    /// `(job_id, page_number, stage, width)` key; see [`Self::with_preview_rate_limit`]
+    preview_rate_limit: std::time::Duration,
+    /// Last time a preview was actually sent, per `(job_id, page_number, stage, width)`
+    preview_last_sent: Mutex<HashMap<(Uuid, usize, String, u32), std::time::Instant>>,
+    /// Previews dropped by the rate limiter, per job (observability only —
+    /// does not affect delivery of critical messages, which never throttle)
+    preview_dropped: Mutex<HashMap<Uuid, u64>>,
+    /// Content hash (blake3 of the base64 payload) of the last stage sent
+    /// for each `(job_id, page_number)`, so a pixel-identical later stage
+    /// can be deduplicated into a lightweight `unchanged_from` reference
+    /// instead of shipping a duplicate blob
+    preview_content_cache: Mutex<HashMap<(Uuid, usize), (String, blake3::Hash)>>,
+    /// Ceiling on a single preview's base64 payload size; see
+    /// [`Self::with_max_preview_bytes`]
+    max_preview_bytes: usize,
+    /// Preview preferences registered by each job's subscribers via
+    /// [`Self::subscribe_with_prefs`]; [`Self::broadcast_page_preview_for_path`]
+    /// renders only the widths/stages someone actually asked for
+    preview_prefs: Mutex<HashMap<Uuid, Vec<PreviewPrefs>>>,
+    /// Reconnect sessions registered via [`Self::register_session`], keyed by
+    /// `session_id`; see [`Self::resume_session`] / [`Self::ack_session`]
+    sessions: RwLock<HashMap<Uuid, SessionState>>,
+}
+
+/// A reconnect session's state: which job it's watching, and the highest
+/// sequence number it has acknowledged consuming (see
+/// [`WsBroadcaster::ack_session`])
+struct SessionState {
+    job_id: Uuid,
+    acked_seq: AtomicU64,
 }
 
 impl WsBroadcaster {
     /// Create a new broadcaster
     pub fn new() -> Self {
-        let (global_sender, _) = broadcast::channel(100);
-        Self {
-            channels: RwLock::new(HashMap::new()),
-            global_sender,
-            capacity: 100,
-        }
+        Self::with_capacity(100)
     }
 
     /// Create a new broadcaster with custom capacity
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::build(capacity, DEFAULT_PREVIEW_RATE_LIMIT)
+    }
+
+    /// Create a new broadcaster with a custom minimum interval between
+    /// `PagePreview` sends for the same job (default capacity). Bursts of
+    /// previews for the same `(page_number, stage)` within the interval
+    /// collapse to a single send; `StatusChange`/`Completed`/`Error` always
+    /// bypass this throttle.
+    pub fn with_preview_rate_limit(interval: std::time::Duration) -> Self {
+        Self::build(100, interval)
+    }
+
+    /// Create a new broadcaster with a custom ceiling on a single preview's
+    /// base64 payload size (default capacity and rate limit). Previews
+    /// exceeding `max_bytes` are replaced with a `too_large` marker rather
+    /// than broadcast in full.
+    pub fn with_max_preview_bytes(max_bytes: usize) -> Self {
+        let mut broadcaster = Self::build(100, DEFAULT_PREVIEW_RATE_LIMIT);
+        broadcaster.max_preview_bytes = max_bytes;
+        broadcaster
+    }
+
+    fn build(capacity: usize, preview_rate_limit: std::time::Duration) -> Self {
         let (global_sender, _) = broadcast::channel(capacity);
+        let (command_tx, command_rx) = mpsc::channel(capacity);
         Self {
             channels: RwLock::new(HashMap::new()),
             global_sender,
+            global_seq: AtomicU64::new(1),
             capacity,
+            command_tx,
+            command_rx: Mutex::new(Some(command_rx)),
+            preview_rate_limit,
+            preview_last_sent: Mutex::new(HashMap::new()),
+            preview_dropped: Mutex::new(HashMap::new()),
+            preview_content_cache: Mutex::new(HashMap::new()),
+            max_preview_bytes: DEFAULT_MAX_PREVIEW_BYTES,
+            preview_prefs: Mutex::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Take ownership of the inbound client-command receiver. Returns `None`
+    /// if already taken (only one consumer can drain commands).
+    pub fn take_command_receiver(&self) -> Option<mpsc::Receiver<(Uuid, WsClientCommand)>> {
+        self.command_rx.lock().unwrap().take()
+    }
+
+    /// Clone a sender for forwarding client commands; used internally by
+    /// `handle_socket` for each connection
+    fn command_sender(&self) -> mpsc::Sender<(Uuid, WsClientCommand)> {
+        self.command_tx.clone()
+    }
+
+    /// `true` if a `PagePreview` for this `(job_id, page_number, stage, width)`
+    /// may be sent now, i.e. the rate limit window has elapsed since the last
+    /// one that was actually sent. Updates the last-sent timestamp as a
+    /// side effect when it returns `true`. Keyed by `width` too, so a job
+    /// rendering several requested resolutions for the same stage doesn't
+    /// throttle the second size just because the first one just went out.
+    fn should_send_preview(&self, job_id: Uuid, page_number: usize, stage: &str, width: u32) -> bool {
+        let key = (job_id, page_number, stage.to_string(), width);
+        let now = std::time::Instant::now();
+        let mut last_sent = self.preview_last_sent.lock().unwrap();
+        match last_sent.get(&key) {
+            Some(previous) if now.duration_since(*previous) < self.preview_rate_limit => false,
+            _ => {
+                last_sent.insert(key, now);
+                true
+            }
+        }
+    }
+
+    fn record_dropped_preview(&self, job_id: Uuid) {
+        let mut dropped = self.preview_dropped.lock().unwrap();
+        *dropped.entry(job_id).or_insert(0) += 1;
+    }
+
+    /// Number of `PagePreview` broadcasts dropped by the rate limiter for
+    /// this job so far
+    pub fn dropped_preview_count(&self, job_id: Uuid) -> u64 {
+        self.preview_dropped
+            .lock()
+            .unwrap()
+            .get(&job_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Total messages evicted from `job_id`'s replay buffer so far (0 if the
+    /// job has no channel yet), for [`WsMessage::Resync`]
+    pub async fn evicted_count(&self, job_id: Uuid) -> u64 {
+        self.channels
+            .read()
+            .await
+            .get(&job_id)
+            .map(|channel| channel.evicted.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
+    /// Register a new reconnect session watching `job_id`, starting from
+    /// `initial_acked_seq`, and return its `session_id` for the client to
+    /// present on a future reconnect
+    pub async fn register_session(&self, job_id: Uuid, initial_acked_seq: u64) -> Uuid {
+        let session_id = Uuid::new_v4();
+        self.sessions.write().await.insert(
+            session_id,
+            SessionState {
+                job_id,
+                acked_seq: AtomicU64::new(initial_acked_seq),
+            },
+        );
+        session_id
+    }
+
+    /// Resume a previously registered session for `job_id`, returning its
+    /// last acked sequence number. `None` if the session doesn't exist or
+    /// was registered for a different job (the client should fall back to
+    /// registering a fresh session in that case).
+    pub async fn resume_session(&self, session_id: Uuid, job_id: Uuid) -> Option<u64> {
+        self.sessions
+            .read()
+            .await
+            .get(&session_id)
+            .filter(|state| state.job_id == job_id)
+            .map(|state| state.acked_seq.load(Ordering::SeqCst))
+    }
+
+    /// Record that a session has consumed everything up to `seq`. Ignored if
+    /// no session with this id is registered; a session stays registered
+    /// after its connection closes.
+    pub async fn ack_session(&self, session_id: Uuid, seq: u64) {
+        if let Some(state) = self.sessions.read().await.get(&session_id) {
+            state.acked_seq.fetch_max(seq, Ordering::SeqCst);
         }
     }
 
+    /// This session's last acked sequence number, for tests/observability
+    pub async fn session_acked_seq(&self, session_id: Uuid) -> Option<u64> {
+        self.sessions
+            .read()
+            .await
+            .get(&session_id)
+            .map(|state| state.acked_seq.load(Ordering::SeqCst))
+    }
+
+    /// Lowest acked sequence number across every live session watching
+    /// `job_id`, or `None` if it has no sessions (in which case the replay
+    /// buffer is trimmed by capacity alone, see [`Self::broadcast`])
+    async fn min_acked_seq(&self, job_id: Uuid) -> Option<u64> {
+        self.sessions
+            .read()
+            .await
+            .values()
+            .filter(|state| state.job_id == job_id)
+            .map(|state| state.acked_seq.load(Ordering::SeqCst))
+            .min()
+    }
+
     /// Subscribe to global server-wide messages
     pub fn subscribe_global(&self) -> broadcast::Receiver<WsMessage> {
-        self.global_sender.subscribe()
+        let receiver = self.global_sender.subscribe();
+        debug!(subscribers = self.global_sender.receiver_count(), "subscribed to global channel");
+        receiver
     }
 
-    /// Subscribe to updates for a specific job
+    /// Subscribe to updates for a specific job, without replay
     pub async fn subscribe(&self, job_id: Uuid) -> broadcast::Receiver<WsMessage> {
+        self.subscribe_with_replay(job_id, u64::MAX).await.1
+    }
+
+    /// Subscribe to updates for a specific job, also returning any buffered
+    /// messages with `seq > from_seq` (oldest first) so a late-joining or
+    /// reconnecting client can catch up before switching to live delivery
+    pub async fn subscribe_with_replay(
+        &self,
+        job_id: Uuid,
+        from_seq: u64,
+    ) -> (Vec<WsMessage>, broadcast::Receiver<WsMessage>) {
+        let _span = info_span!("ws_subscribe", %job_id, from_seq).entered();
+
         let mut channels = self.channels.write().await;
+        let channel = channels
+            .entry(job_id)
+            .or_insert_with(|| JobChannel::new(self.capacity));
+
+        let receiver = channel.sender.subscribe();
+        let buffered: Vec<WsMessage> = {
+            let buffer = channel.buffer.lock().unwrap();
+            buffer
+                .iter()
+                .filter(|(seq, _)| *seq > from_seq)
+                .map(|(_, msg)| msg.clone())
+                .collect()
+        };
+        debug!(
+            %job_id,
+            replayed = buffered.len(),
+            subscribers = channel.sender.receiver_count(),
+            "subscribed with replay"
+        );
+        (buffered, receiver)
+    }
 
-        if let Some(sender) = channels.get(&job_id) {
-            sender.subscribe()
-        } else {
-            let (sender, receiver) = broadcast::channel(self.capacity);
-            channels.insert(job_id, sender);
-            receiver
+    /// Subscribe to a job while registering `prefs` as one of its subscribers'
+    /// preview preferences, replaying buffered messages with `seq > from_seq`
+    /// exactly like [`Self::subscribe_with_replay`]. A job's active
+    /// widths/stages (see [`Self::active_preview_widths`] /
+    /// [`Self::stage_is_wanted`]) are the union across every
+    /// registered `PreviewPrefs`, so multiple viewers (e.g. a
+    /// thumbnail strip and a detail inspector) can each get what they asked
+    /// for out of the same render pass.
+    pub async fn subscribe_with_prefs(
+        &self,
+        job_id: Uuid,
+        from_seq: u64,
+        prefs: PreviewPrefs,
+    ) -> (Vec<WsMessage>, broadcast::Receiver<WsMessage>) {
+        self.register_preview_prefs(job_id, prefs);
+        self.subscribe_with_replay(job_id, from_seq).await
+    }
+
+    /// Register `prefs` as one of `job_id`'s subscribers' preview
+    /// preferences without (re)subscribing, for a connection (e.g. a
+    /// multiplexed `/api/ws` client already forwarding that job) that wants
+    /// to add preferences mid-stream; see
+    /// [`WsClientCommand::SetPreviewPrefs`]. The new entry is appended to the
+    /// job's list; no earlier entry is replaced or removed.
+    fn register_preview_prefs(&self, job_id: Uuid, prefs: PreviewPrefs) {
+        self.preview_prefs
+            .lock()
+            .unwrap()
+            .entry(job_id)
+            .or_default()
+            .push(prefs);
+    }
+
+    /// Distinct preview widths requested by `job_id`'s subscribers, largest
+    /// first. Falls back to `[PREVIEW_WIDTH]` if nobody registered prefs
+    /// (i.e. every subscriber used plain [`Self::subscribe`]), preserving the
+    /// pre-chunk12-3 single-resolution behavior by default.
+    fn active_preview_widths(&self, job_id: Uuid) -> Vec<u32> {
+        let prefs = self.preview_prefs.lock().unwrap();
+        match prefs.get(&job_id) {
+            Some(registered) if !registered.is_empty() => {
+                let mut widths: Vec<u32> = registered.iter().map(|p| p.width).collect();
+                widths.sort_unstable();
+                widths.dedup();
+                widths.reverse();
+                widths
+            }
+            _ => vec![PREVIEW_WIDTH],
+        }
+    }
+
+    /// `true` if at least one of `job_id`'s registered subscribers wants
+    /// `stage`. With no registered prefs (the default), every stage passes.
+    fn stage_is_wanted(&self, job_id: Uuid, stage: &str) -> bool {
+        let prefs = self.preview_prefs.lock().unwrap();
+        match prefs.get(&job_id) {
+            Some(registered) if !registered.is_empty() => {
+                registered.iter().any(|p| p.stages.contains(stage))
+            }
+            _ => true,
         }
     }
 
-    /// Broadcast a message to all subscribers of a job
-    pub async fn broadcast(&self, job_id: Uuid, message: WsMessage) {
+    /// Broadcast a message to all subscribers of a job, assigning it the
+    /// next sequence number and retaining it in the job's replay buffer
+    pub async fn broadcast(&self, job_id: Uuid, mut message: WsMessage) {
+        let span = info_span!("ws_broadcast", %job_id, seq = tracing::field::Empty, bytes = tracing::field::Empty, subscribers = tracing::field::Empty);
+        let _enter = span.enter();
+
         let channels = self.channels.read().await;
 
-        if let Some(sender) = channels.get(&job_id) {
+        if let Some(channel) = channels.get(&job_id) {
+            let seq = channel.next_seq.fetch_add(1, Ordering::SeqCst);
+            message.set_seq(seq);
+            span.record("seq", seq);
+
+            let payload_size = serde_json::to_vec(&message).map(|v| v.len()).unwrap_or(0);
+            span.record("bytes", payload_size);
+            channel.messages_sent.fetch_add(1, Ordering::SeqCst);
+            channel.bytes_sent.fetch_add(payload_size as u64, Ordering::SeqCst);
+
+            // Every live session already acked past this point, so trimming
+            // it isn't a loss (unlike the capacity-based eviction below) and
+            // doesn't count against `evicted`.
+            let min_acked = self.min_acked_seq(job_id).await;
+
+            {
+                let mut buffer = channel.buffer.lock().unwrap();
+                buffer.push_back((seq, message.clone()));
+                if let Some(min_acked) = min_acked {
+                    while buffer.front().map(|(s, _)| *s <= min_acked).unwrap_or(false) {
+                        buffer.pop_front();
+                    }
+                }
+                if buffer.len() > REPLAY_BUFFER_CAPACITY {
+                    buffer.pop_front();
+                    channel.evicted.fetch_add(1, Ordering::SeqCst);
+                    warn!(%job_id, seq, "replay buffer evicted an undelivered message");
+                }
+            }
+
+            let subscriber_count = channel.sender.receiver_count();
+            span.record("subscribers", subscriber_count);
+            if subscriber_count == 0 {
+                warn!(%job_id, seq, "broadcast with zero subscribers");
+            }
+
             // Ignore send errors (no receivers)
-            let _ = sender.send(message);
+            let _ = channel.sender.send(message);
+            debug!(%job_id, seq, bytes = payload_size, subscribers = subscriber_count, "broadcast sent");
         }
     }
 
+    /// A live snapshot of `job_id`'s broadcast throughput for an HTTP status
+    /// endpoint: messages/bytes sent so far, total drops (replay-buffer
+    /// evictions plus rate-limited previews), and how many subscribers are
+    /// currently attached. `None` if the job has no channel (never
+    /// subscribed to, or already [`Self::remove_job`]'d).
+    pub async fn job_metrics(&self, job_id: Uuid) -> Option<JobMetricsSnapshot> {
+        let channels = self.channels.read().await;
+        let channel = channels.get(&job_id)?;
+        Some(JobMetricsSnapshot {
+            messages_sent: channel.messages_sent.load(Ordering::SeqCst),
+            bytes_sent: channel.bytes_sent.load(Ordering::SeqCst),
+            drops: channel.evicted.load(Ordering::SeqCst) + self.dropped_preview_count(job_id),
+            active_subscribers: channel.sender.receiver_count(),
+        })
+    }
+
     /// Broadcast progress update
     pub async fn broadcast_progress(
         &self,
@@ -169,6 +710,7 @@ impl WsBroadcaster {
                 total_steps,
                 step_name: step_name.to_string(),
                 percent,
+                seq: 0,
             },
         )
         .await;
@@ -187,6 +729,7 @@ impl WsBroadcaster {
                 job_id,
                 old_status,
                 new_status,
+                seq: 0,
             },
         )
         .await;
@@ -206,6 +749,7 @@ impl WsBroadcaster {
                 download_url: format!("/api/jobs/{}/download", job_id),
                 elapsed_seconds,
                 page_count,
+                seq: 0,
             },
         )
         .await;
@@ -218,6 +762,7 @@ impl WsBroadcaster {
             WsMessage::Error {
                 job_id,
                 message: message.to_string(),
+                seq: 0,
             },
         )
         .await;
@@ -225,6 +770,14 @@ impl WsBroadcaster {
 
     /// Broadcast page preview (Phase 4.1)
     ///
+    /// Deduplicates by content: if this stage's bytes hash the same as the
+    /// last stage sent for this page, a lightweight `unchanged_from` marker
+    /// goes out instead of a duplicate blob. If the payload exceeds
+    /// [`Self::max_preview_bytes`], a `too_large` marker goes out instead —
+    /// this function only receives an already-encoded thumbnail, so it has
+    /// no image to re-render smaller; callers wanting a guaranteed send
+    /// should encode at a smaller `max_width` up front.
+    ///
     /// # Arguments
     /// * `job_id` - Job UUID
     /// * `page_number` - Page number (1-indexed)
@@ -232,6 +785,7 @@ impl WsBroadcaster {
     /// * `stage` - Processing stage name
     /// * `width` - Preview image width
     /// * `height` - Preview image height
+    #[tracing::instrument(skip(self, preview_base64), fields(payload_bytes = preview_base64.len()))]
     pub async fn broadcast_page_preview(
         &self,
         job_id: Uuid,
@@ -241,6 +795,65 @@ impl WsBroadcaster {
         width: u32,
         height: u32,
     ) {
+        if !self.should_send_preview(job_id, page_number, stage, width) {
+            self.record_dropped_preview(job_id);
+            return;
+        }
+
+        let hash = blake3::hash(preview_base64.as_bytes());
+        let cache_key = (job_id, page_number);
+        let too_large = preview_base64.len() > self.max_preview_bytes;
+        let previous = {
+            let mut cache = self.preview_content_cache.lock().unwrap();
+            // An oversized preview goes out without its bytes, so it is not
+            // recorded as a stage the client has content for
+            if too_large {
+                cache.get(&cache_key).cloned()
+            } else {
+                cache.insert(cache_key, (stage.to_string(), hash))
+            }
+        };
+
+        if let Some((previous_stage, previous_hash)) = previous {
+            if previous_hash == hash {
+                self.broadcast(
+                    job_id,
+                    WsMessage::PagePreview {
+                        job_id,
+                        page_number,
+                        preview_base64: String::new(),
+                        stage: stage.to_string(),
+                        width,
+                        height,
+                        unchanged_from: Some(previous_stage),
+                        too_large: false,
+                        seq: 0,
+                    },
+                )
+                .await;
+                return;
+            }
+        }
+
+        if too_large {
+            self.broadcast(
+                job_id,
+                WsMessage::PagePreview {
+                    job_id,
+                    page_number,
+                    preview_base64: String::new(),
+                    stage: stage.to_string(),
+                    width,
+                    height,
+                    unchanged_from: None,
+                    too_large: true,
+                    seq: 0,
+                },
+            )
+            .await;
+            return;
+        }
+
         self.broadcast(
             job_id,
             WsMessage::PagePreview {
@@ -250,11 +863,40 @@ impl WsBroadcaster {
                 stage: stage.to_string(),
                 width,
                 height,
+                unchanged_from: None,
+                too_large: false,
+                seq: 0,
             },
         )
         .await;
     }
 
+    /// Render `image_path` for `stage` at every width a `job_id` subscriber
+    /// has asked for via [`Self::subscribe_with_prefs`] (or just
+    /// [`PREVIEW_WIDTH`] if nobody registered prefs) and broadcast each as a
+    /// separate [`WsMessage::PagePreview`] through [`Self::broadcast_page_preview`],
+    /// so a thumbnail strip and a detail inspector can share one job without
+    /// re-running the rendering pipeline per viewer. Stages nobody
+    /// subscribed to are skipped entirely.
+    pub async fn broadcast_page_preview_for_path(
+        &self,
+        job_id: Uuid,
+        page_number: usize,
+        image_path: &std::path::Path,
+        stage: &str,
+    ) {
+        if !self.stage_is_wanted(job_id, stage) {
+            return;
+        }
+
+        for width in self.active_preview_widths(job_id) {
+            if let Some((preview_base64, w, h)) = generate_preview_base64(image_path, width) {
+                self.broadcast_page_preview(job_id, page_number, preview_base64, stage, w, h)
+                    .await;
+            }
+        }
+    }
+
     /// Broadcast batch progress update
     pub async fn broadcast_batch_progress(
         &self,
@@ -274,6 +916,7 @@ impl WsBroadcaster {
                 pending,
                 failed,
                 total,
+                seq: 0,
             },
         )
         .await;
@@ -292,6 +935,7 @@ impl WsBroadcaster {
                 batch_id,
                 success_count,
                 failed_count,
+                seq: 0,
             },
         )
         .await;
@@ -299,12 +943,19 @@ impl WsBroadcaster {
 
     /// Broadcast server shutdown notification to all connected clients
     pub fn broadcast_shutdown(&self, reason: &str, countdown_secs: u64) {
+        let seq = self.global_seq.fetch_add(1, Ordering::SeqCst);
         let message = WsMessage::ServerShutdown {
             reason: reason.to_string(),
             countdown_secs,
+            seq,
         };
+        let subscriber_count = self.global_sender.receiver_count();
+        if subscriber_count == 0 {
+            warn!(reason, seq, "shutdown broadcast with zero subscribers");
+        }
         // Ignore send errors (no receivers)
         let _ = self.global_sender.send(message);
+        debug!(reason, seq, subscribers = subscriber_count, "shutdown broadcast sent");
     }
 
     /// Remove channel for a job (cleanup)
@@ -325,18 +976,129 @@ impl Default for WsBroadcaster {
     }
 }
 
+/// Query parameters accepted on the job WebSocket upgrade
+#[derive(Debug, Deserialize)]
+pub struct WsQueryParams {
+    /// Replay buffered messages with `seq > from_seq` before switching to
+    /// live delivery; defaults to 0 (replay everything still buffered)
+    #[serde(default)]
+    from_seq: u64,
+    /// `?binary=1` opts this connection into the binary preview transport
+    /// ([`encode_binary_preview_frame`]) instead of base64-in-JSON; any
+    /// other value (including absent) keeps the existing JSON path, so this
+    /// is purely additive per-connection negotiation
+    #[serde(default)]
+    binary: Option<String>,
+    /// A `session_id` from an earlier [`WsMessage::SessionInit`], presented
+    /// to resume that session's replay point instead of `?from_seq=`. Absent
+    /// or unrecognized (e.g. the server restarted) falls back to `from_seq`
+    /// and registers a fresh session.
+    #[serde(default)]
+    session_id: Option<Uuid>,
+    /// Thumbnail width to render previews at for this connection; absent
+    /// keeps the pre-chunk12-3 default of [`PREVIEW_WIDTH`], see
+    /// [`PreviewPrefs`]
+    #[serde(default)]
+    preview_width: Option<u32>,
+    /// Comma-separated subset of [`preview_stage::ALL`] this connection
+    /// wants previews for; absent wants every stage, see [`PreviewPrefs`]
+    #[serde(default)]
+    preview_stages: Option<String>,
+}
+
+impl WsQueryParams {
+    /// `None` when the client didn't negotiate anything, so the connection
+    /// falls back to plain [`WsBroadcaster::subscribe_with_replay`] and the
+    /// pre-chunk12-3 single-resolution behavior
+    fn preview_prefs(&self) -> Option<PreviewPrefs> {
+        if self.preview_width.is_none() && self.preview_stages.is_none() {
+            return None;
+        }
+        let mut prefs = PreviewPrefs::default();
+        if let Some(width) = self.preview_width {
+            prefs.width = width;
+        }
+        if let Some(stages) = &self.preview_stages {
+            prefs.stages = stages.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        Some(prefs)
+    }
+}
+
 /// WebSocket handler for job progress updates
 pub async fn ws_job_handler(
     ws: WebSocketUpgrade,
     Path(job_id): Path<Uuid>,
+    Query(params): Query<WsQueryParams>,
     State(broadcaster): State<Arc<WsBroadcaster>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, job_id, broadcaster))
+    let binary_preview = params.binary.as_deref() == Some("1");
+    let preview_prefs = params.preview_prefs();
+    ws.on_upgrade(move |socket| {
+        handle_socket(
+            socket,
+            job_id,
+            params.from_seq,
+            params.session_id,
+            binary_preview,
+            preview_prefs,
+            broadcaster,
+        )
+    })
 }
 
 /// Handle a WebSocket connection
-async fn handle_socket(mut socket: WebSocket, job_id: Uuid, broadcaster: Arc<WsBroadcaster>) {
-    let mut receiver = broadcaster.subscribe(job_id).await;
+async fn handle_socket(
+    mut socket: WebSocket,
+    job_id: Uuid,
+    from_seq: u64,
+    requested_session_id: Option<Uuid>,
+    binary_preview: bool,
+    preview_prefs: Option<PreviewPrefs>,
+    broadcaster: Arc<WsBroadcaster>,
+) {
+    let (session_id, resume_from_seq) = match requested_session_id {
+        Some(requested) => match broadcaster.resume_session(requested, job_id).await {
+            Some(acked_seq) => (requested, acked_seq),
+            None => (broadcaster.register_session(job_id, from_seq).await, from_seq),
+        },
+        None => (broadcaster.register_session(job_id, from_seq).await, from_seq),
+    };
+
+    let session_init = WsMessage::SessionInit {
+        session_id,
+        last_seq: resume_from_seq,
+        seq: 0,
+    };
+    if send_ws_message(&mut socket, &session_init, binary_preview).await.is_err() {
+        return;
+    }
+
+    let (buffered, mut receiver) = match preview_prefs {
+        Some(prefs) => broadcaster.subscribe_with_prefs(job_id, resume_from_seq, prefs).await,
+        None => broadcaster.subscribe_with_replay(job_id, resume_from_seq).await,
+    };
+    let mut last_seq = resume_from_seq;
+
+    let dropped = broadcaster.evicted_count(job_id).await;
+    let resync = WsMessage::Resync {
+        job_id,
+        from_seq: resume_from_seq,
+        dropped,
+        seq: 0,
+    };
+    if send_ws_message(&mut socket, &resync, binary_preview).await.is_err() {
+        return;
+    }
+
+    for msg in buffered {
+        last_seq = last_seq.max(msg.seq());
+        if send_ws_message(&mut socket, &msg, binary_preview).await.is_err() {
+            return;
+        }
+    }
+
+    let command_tx = broadcaster.command_sender();
 
     loop {
         tokio::select! {
@@ -344,24 +1106,36 @@ async fn handle_socket(mut socket: WebSocket, job_id: Uuid, broadcaster: Arc<WsB
             result = receiver.recv() => {
                 match result {
                     Ok(msg) => {
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            if socket.send(Message::Text(json.into())).await.is_err() {
-                                // Client disconnected
-                                break;
-                            }
+                        last_seq = msg.seq();
+                        if send_ws_message(&mut socket, &msg, binary_preview).await.is_err() {
+                            // Client disconnected
+                            break;
                         }
                     }
                     Err(broadcast::error::RecvError::Closed) => {
                         // Channel closed
                         break;
                     }
-                    Err(broadcast::error::RecvError::Lagged(_)) => {
-                        // Receiver lagged, continue
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        // Receiver lagged and dropped messages; tell the client
+                        // where to resume from rather than silently losing progress
+                        let resume_seq = last_seq;
+                        let gap = WsMessage::Gap {
+                            job_id,
+                            missed,
+                            resume_seq,
+                            seq: resume_seq,
+                        };
+                        if let Ok(json) = serde_json::to_string(&gap) {
+                            if socket.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
                         continue;
                     }
                 }
             }
-            // Handle incoming messages from client (ping/pong, close)
+            // Handle incoming messages from client (commands, ping/pong, close)
             result = socket.recv() => {
                 match result {
                     Some(Ok(Message::Close(_))) | None => {
@@ -373,54 +1147,285 @@ async fn handle_socket(mut socket: WebSocket, job_id: Uuid, broadcaster: Arc<WsB
                             break;
                         }
                     }
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(WsClientCommand::Ack { seq }) = serde_json::from_str::<WsClientCommand>(text.as_str()) {
+                            broadcaster.ack_session(session_id, seq).await;
+                        } else if let Err(err_message) = forward_client_command(job_id, text.as_str(), &command_tx).await {
+                            if socket
+                                .send(Message::Text(
+                                    serde_json::to_string(&WsMessage::Error { job_id, message: err_message, seq: 0 })
+                                        .unwrap_or_default()
+                                        .into(),
+                                ))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Binary(data))) => {
+                        let text = String::from_utf8_lossy(&data).into_owned();
+                        if let Ok(WsClientCommand::Ack { seq }) = serde_json::from_str::<WsClientCommand>(&text) {
+                            broadcaster.ack_session(session_id, seq).await;
+                        } else if let Err(err_message) = forward_client_command(job_id, &text, &command_tx).await {
+                            if socket
+                                .send(Message::Text(
+                                    serde_json::to_string(&WsMessage::Error { job_id, message: err_message, seq: 0 })
+                                        .unwrap_or_default()
+                                        .into(),
+                                ))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {
+                        // Ignore other messages (e.g. Pong)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// WebSocket handler for the multiplexed `/api/ws` endpoint: one connection,
+/// many dynamically subscribed jobs/batches, rather than one socket per job
+pub async fn ws_multiplex_handler(
+    ws: WebSocketUpgrade,
+    State(broadcaster): State<Arc<WsBroadcaster>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_multiplexed_socket(socket, broadcaster))
+}
+
+/// Sentinel used as the `job_id` on protocol-level [`WsMessage::Error`]
+/// replies over the multiplexed socket, which isn't scoped to one job
+const MULTIPLEX_ERROR_JOB_ID: Uuid = Uuid::nil();
+
+/// Handle a multiplexed WebSocket connection: fan in every subscribed job's
+/// broadcast messages (plus the global channel) into one outbound stream,
+/// and let the client grow/shrink the subscription set at runtime via
+/// `{"type":"subscribe","job_id":...}` / `{"type":"unsubscribe","job_id":...}`.
+/// Outbound frames already carry `job_id`/`batch_id`, so the client
+/// demultiplexes on its side; this function only fans in.
+async fn handle_multiplexed_socket(mut socket: WebSocket, broadcaster: Arc<WsBroadcaster>) {
+    // Each subscribed job gets its own forwarder task relaying into this
+    // single channel, which is what the select loop actually waits on.
+    let (fanin_tx, mut fanin_rx) = mpsc::channel::<WsMessage>(256);
+    let mut subscriptions: HashMap<Uuid, tokio::task::JoinHandle<()>> = HashMap::new();
+    let mut global_receiver = broadcaster.subscribe_global();
+
+    loop {
+        tokio::select! {
+            Some(msg) = fanin_rx.recv() => {
+                if let Ok(json) = serde_json::to_string(&msg) {
+                    if socket.send(Message::Text(json.into())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            result = global_receiver.recv() => {
+                match result {
+                    Ok(msg) => {
+                        if let Ok(json) = serde_json::to_string(&msg) {
+                            if socket.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                }
+            }
+            result = socket.recv() => {
+                match result {
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(Message::Ping(data))) => {
+                        if socket.send(Message::Pong(data)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(message) = apply_multiplex_command(text.as_str(), &broadcaster, &fanin_tx, &mut subscriptions).await {
+                            let reply = WsMessage::Error { job_id: MULTIPLEX_ERROR_JOB_ID, message, seq: 0 };
+                            if let Ok(json) = serde_json::to_string(&reply) {
+                                if socket.send(Message::Text(json.into())).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Binary(data))) => {
+                        let text = String::from_utf8_lossy(&data).into_owned();
+                        if let Err(message) = apply_multiplex_command(&text, &broadcaster, &fanin_tx, &mut subscriptions).await {
+                            let reply = WsMessage::Error { job_id: MULTIPLEX_ERROR_JOB_ID, message, seq: 0 };
+                            if let Ok(json) = serde_json::to_string(&reply) {
+                                if socket.send(Message::Text(json.into())).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
                     _ => {
-                        // Ignore other messages
+                        // Ignore other messages (e.g. Pong)
+                    }
+                }
+            }
+        }
+    }
+
+    for (_, handle) in subscriptions {
+        handle.abort();
+    }
+}
+
+/// Apply a `subscribe`/`unsubscribe`/`set_preview_prefs` command to the
+/// multiplexed connection's receiver set (the latter just registers
+/// preferences for the named job, whether or not it is subscribed, see
+/// [`WsBroadcaster::register_preview_prefs`]). Every other
+/// [`WsClientCommand`] variant is rejected — those are job-scoped controls
+/// meant for the single-job endpoint, not `/api/ws`.
+async fn apply_multiplex_command(
+    payload: &str,
+    broadcaster: &Arc<WsBroadcaster>,
+    fanin_tx: &mpsc::Sender<WsMessage>,
+    subscriptions: &mut HashMap<Uuid, tokio::task::JoinHandle<()>>,
+) -> Result<(), String> {
+    let command: WsClientCommand =
+        serde_json::from_str(payload).map_err(|e| format!("unrecognized client command: {e}"))?;
+
+    match command {
+        WsClientCommand::Subscribe { job_id } => {
+            subscriptions
+                .entry(job_id)
+                .or_insert_with(|| spawn_job_forwarder(broadcaster.clone(), job_id, fanin_tx.clone()));
+            Ok(())
+        }
+        WsClientCommand::Unsubscribe { job_id } => {
+            if let Some(handle) = subscriptions.remove(&job_id) {
+                handle.abort();
+            }
+            Ok(())
+        }
+        WsClientCommand::SetPreviewPrefs { job_id, width, stages } => {
+            broadcaster.register_preview_prefs(job_id, PreviewPrefs {
+                width,
+                stages: stages.into_iter().collect(),
+            });
+            Ok(())
+        }
+        _ => Err("only subscribe/unsubscribe/set_preview_prefs commands are supported on /api/ws".to_string()),
+    }
+}
+
+/// Spawn a task relaying one job's broadcast messages into the multiplexed
+/// connection's fan-in channel until the client unsubscribes or disconnects
+fn spawn_job_forwarder(
+    broadcaster: Arc<WsBroadcaster>,
+    job_id: Uuid,
+    fanin_tx: mpsc::Sender<WsMessage>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut receiver = broadcaster.subscribe(job_id).await;
+        loop {
+            match receiver.recv().await {
+                Ok(msg) => {
+                    if fanin_tx.send(msg).await.is_err() {
+                        break;
                     }
                 }
+                Err(broadcast::error::RecvError::Closed) => break,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            }
+        }
+    })
+}
+
+/// Parse an inbound client payload as a [`WsClientCommand`] and forward it to
+/// the job subsystem. Returns `Err` with a human-readable message (suitable
+/// for a [`WsMessage::Error`] reply) if the payload isn't a recognized
+/// command or the command channel has no consumer.
+async fn forward_client_command(
+    job_id: Uuid,
+    payload: &str,
+    command_tx: &mpsc::Sender<(Uuid, WsClientCommand)>,
+) -> Result<(), String> {
+    let command: WsClientCommand = serde_json::from_str(payload)
+        .map_err(|e| format!("unrecognized client command: {e}"))?;
+    command_tx
+        .send((job_id, command))
+        .await
+        .map_err(|_| "command channel has no consumer".to_string())
+}
+
+/// Send one [`WsMessage`] down `socket`, choosing transport per connection:
+/// a negotiated binary connection (`binary_preview`) gets `PagePreview`
+/// frames packed via [`encode_binary_preview_frame`] over `Message::Binary`;
+/// everything else (and every other variant) goes out as JSON text, exactly
+/// as before this connection opted in.
+async fn send_ws_message(socket: &mut WebSocket, msg: &WsMessage, binary_preview: bool) -> Result<(), ()> {
+    if binary_preview {
+        if let WsMessage::PagePreview {
+            job_id,
+            page_number,
+            preview_base64,
+            stage,
+            width,
+            height,
+            ..
+        } = msg
+        {
+            // Markers (unchanged_from / too_large) carry no payload to pack
+            // into a binary frame; fall through to the JSON path so the
+            // client still sees the flag.
+            if preview_base64.is_empty() {
+                return match serde_json::to_string(msg) {
+                    Ok(json) => socket.send(Message::Text(json.into())).await.map_err(|_| ()),
+                    Err(_) => Ok(()),
+                };
+            }
+            if let Some(frame) =
+                encode_binary_preview_frame(*job_id, *page_number as u32, stage, *width as u16, *height as u16, preview_base64)
+            {
+                return socket.send(Message::Binary(frame)).await.map_err(|_| ());
             }
         }
     }
+
+    match serde_json::to_string(msg) {
+        Ok(json) => socket.send(Message::Text(json.into())).await.map_err(|_| ()),
+        Err(_) => Ok(()),
+    }
 }
 
 // ============================================================
-// Preview Generation Utilities (Phase 4.1)
+// Preview Generation Utilities (Phase 4.1 / 4.2)
 // ============================================================
 
 /// Default thumbnail width for preview images
 pub const PREVIEW_WIDTH: u32 = 200;
 
-/// Generate a base64-encoded JPEG thumbnail from an image file
-///
-/// # Arguments
-/// * `image_path` - Path to the source image
-/// * `max_width` - Maximum width for the thumbnail (height scaled proportionally)
-///
-/// # Returns
-/// Tuple of (base64_string, width, height) or None if generation fails
-pub fn generate_preview_base64(
-    image_path: &std::path::Path,
-    max_width: u32,
-) -> Option<(String, u32, u32)> {
-    use base64::{engine::general_purpose::STANDARD, Engine};
+/// Resize `image_path` to a JPEG thumbnail no wider than `max_width`
+/// (height scaled proportionally), returning the encoded JPEG bytes plus
+/// its dimensions. Shared by [`generate_preview_base64`] (JSON transport)
+/// and [`generate_preview_bytes`] (binary transport) so the two only
+/// differ in how they package the same bytes.
+fn render_preview_jpeg(image_path: &std::path::Path, max_width: u32) -> Option<(Vec<u8>, u32, u32)> {
     use image::{imageops::FilterType, GenericImageView};
     use std::io::Cursor;
 
-    // Load image
     let img = image::open(image_path).ok()?;
 
-    // Calculate thumbnail dimensions maintaining aspect ratio
     let (orig_width, orig_height) = img.dimensions();
     let scale = max_width as f32 / orig_width as f32;
     let thumb_width = max_width;
     let thumb_height = (orig_height as f32 * scale) as u32;
 
-    // Create thumbnail
     let thumbnail = img.resize(thumb_width, thumb_height, FilterType::Triangle);
-
-    // Convert to RGB for JPEG encoding
     let rgb_img = thumbnail.to_rgb8();
 
-    // Encode as JPEG
     let mut buffer = Cursor::new(Vec::new());
     let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, 75);
     encoder
@@ -432,21 +1437,153 @@ pub fn generate_preview_base64(
         )
         .ok()?;
 
-    // Convert to base64
-    let base64_str = STANDARD.encode(buffer.into_inner());
-
-    Some((base64_str, thumb_width, thumb_height))
+    Some((buffer.into_inner(), thumb_width, thumb_height))
 }
 
-/// Processing stage names for preview
-pub mod preview_stage {
-    pub const ORIGINAL: &str = "original";
-    pub const DESKEWED: &str = "deskewed";
-    pub const TRIMMED: &str = "trimmed";
+/// Generate a base64-encoded JPEG thumbnail from an image file
+///
+/// # Arguments
+/// * `image_path` - Path to the source image
+/// * `max_width` - Maximum width for the thumbnail (height scaled proportionally)
+///
+/// # Returns
+/// Tuple of (base64_string, width, height) or None if generation fails
+pub fn generate_preview_base64(
+    image_path: &std::path::Path,
+    max_width: u32,
+) -> Option<(String, u32, u32)> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let (jpeg_bytes, width, height) = render_preview_jpeg(image_path, max_width)?;
+    Some((STANDARD.encode(jpeg_bytes), width, height))
+}
+
+/// Generate a raw JPEG thumbnail from an image file, for the binary preview
+/// transport (no base64 overhead). See [`generate_preview_base64`] for the
+/// JSON-transport sibling.
+///
+/// # Returns
+/// Tuple of (jpeg_bytes, width, height) or None if generation fails
+pub fn generate_preview_bytes(image_path: &std::path::Path, max_width: u32) -> Option<(Vec<u8>, u32, u32)> {
+    render_preview_jpeg(image_path, max_width)
+}
+
+/// Processing stage names for preview
+pub mod preview_stage {
+    pub const ORIGINAL: &str = "original";
+    pub const DESKEWED: &str = "deskewed";
+    pub const TRIMMED: &str = "trimmed";
     pub const UPSCALED: &str = "upscaled";
     pub const NORMALIZED: &str = "normalized";
     pub const COLOR_CORRECTED: &str = "color_corrected";
     pub const FINAL: &str = "final";
+
+    /// Every known stage name, for [`super::PreviewPrefs::default`]
+    pub const ALL: [&str; 7] = [
+        ORIGINAL,
+        DESKEWED,
+        TRIMMED,
+        UPSCALED,
+        NORMALIZED,
+        COLOR_CORRECTED,
+        FINAL,
+    ];
+}
+
+/// A client's negotiated preview resolution and stage filter, registered via
+/// [`WsBroadcaster::subscribe_with_prefs`]. The default matches the
+/// pre-chunk12-3 behavior: one render at [`PREVIEW_WIDTH`], every stage.
+#[derive(Debug, Clone)]
+pub struct PreviewPrefs {
+    /// Thumbnail width to render at (e.g. 200 for a strip, 1024 for an
+    /// inspector)
+    pub width: u32,
+    /// Stage names this client wants; others are filtered out of its render
+    /// pass
+    pub stages: std::collections::HashSet<String>,
+}
+
+impl Default for PreviewPrefs {
+    fn default() -> Self {
+        Self {
+            width: PREVIEW_WIDTH,
+            stages: preview_stage::ALL.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// First byte of every binary preview frame, so a client can tell it apart
+/// from any other binary payload the protocol might carry in the future
+const PREVIEW_BINARY_MAGIC: u8 = 0xB1;
+
+/// Single-byte encoding of [`preview_stage`], used in the binary preview
+/// frame header in place of the variable-length stage string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum PreviewStageCode {
+    Original = 0,
+    Deskewed = 1,
+    Trimmed = 2,
+    Upscaled = 3,
+    Normalized = 4,
+    ColorCorrected = 5,
+    Final = 6,
+}
+
+impl PreviewStageCode {
+    fn from_stage_name(stage: &str) -> Option<Self> {
+        match stage {
+            preview_stage::ORIGINAL => Some(Self::Original),
+            preview_stage::DESKEWED => Some(Self::Deskewed),
+            preview_stage::TRIMMED => Some(Self::Trimmed),
+            preview_stage::UPSCALED => Some(Self::Upscaled),
+            preview_stage::NORMALIZED => Some(Self::Normalized),
+            preview_stage::COLOR_CORRECTED => Some(Self::ColorCorrected),
+            preview_stage::FINAL => Some(Self::Final),
+            _ => None,
+        }
+    }
+}
+
+/// Pack a page preview into the binary frame format: 1-byte magic, 16-byte
+/// `job_id`, 4-byte big-endian `page_number`, 1-byte stage code, 2-byte
+/// big-endian `width`/`height`, followed by the raw JPEG bytes.
+fn encode_binary_preview(
+    job_id: Uuid,
+    page_number: u32,
+    stage: PreviewStageCode,
+    width: u16,
+    height: u16,
+    jpeg_bytes: &[u8],
+) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(1 + 16 + 4 + 1 + 2 + 2 + jpeg_bytes.len());
+    frame.push(PREVIEW_BINARY_MAGIC);
+    frame.extend_from_slice(job_id.as_bytes());
+    frame.extend_from_slice(&page_number.to_be_bytes());
+    frame.push(stage as u8);
+    frame.extend_from_slice(&width.to_be_bytes());
+    frame.extend_from_slice(&height.to_be_bytes());
+    frame.extend_from_slice(jpeg_bytes);
+    frame
+}
+
+/// Decode `preview_base64` back to JPEG bytes and pack it into a binary
+/// preview frame. Returns `None` if the stage name isn't recognized or the
+/// base64 payload doesn't decode, in which case the caller should fall back
+/// to the JSON transport rather than drop the preview.
+fn encode_binary_preview_frame(
+    job_id: Uuid,
+    page_number: u32,
+    stage: &str,
+    width: u16,
+    height: u16,
+    preview_base64: &str,
+) -> Option<Vec<u8>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let stage_code = PreviewStageCode::from_stage_name(stage)?;
+    let jpeg_bytes = STANDARD.decode(preview_base64).ok()?;
+    Some(encode_binary_preview(job_id, page_number, stage_code, width, height, &jpeg_bytes))
 }
 
 #[cfg(test)]
@@ -618,6 +1755,7 @@ mod tests {
             total_steps: 10,
             step_name: "Deskew".to_string(),
             percent: 30,
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -772,6 +1910,7 @@ mod tests {
             pending: 3,
             failed: 0,
             total: 10,
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -783,6 +1922,7 @@ mod tests {
             batch_id,
             success_count: 8,
             failed_count: 2,
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -804,6 +1944,7 @@ mod tests {
             WsMessage::ServerShutdown {
                 reason,
                 countdown_secs,
+                ..
             } => {
                 assert_eq!(reason, "graceful");
                 assert_eq!(countdown_secs, 30);
@@ -818,6 +1959,7 @@ mod tests {
         let msg = WsMessage::ServerShutdown {
             reason: "maintenance".to_string(),
             countdown_secs: 60,
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -886,6 +2028,9 @@ mod tests {
             stage: "deskewed".to_string(),
             width: 200,
             height: 280,
+            unchanged_from: None,
+            too_large: false,
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -912,4 +2057,794 @@ mod tests {
     fn test_preview_width_constant() {
         assert_eq!(PREVIEW_WIDTH, 200);
     }
+
+    // ============ Client Command Tests (Phase 4.2) ============
+
+    // TC-WS-025: Unit variant commands deserialize from their JSON tag
+    #[test]
+    fn test_client_command_deserializes_unit_variants() {
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(r#"{"type":"cancel"}"#).unwrap(),
+            WsClientCommand::Cancel
+        );
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(r#"{"type":"pause"}"#).unwrap(),
+            WsClientCommand::Pause
+        );
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(r#"{"type":"resume"}"#).unwrap(),
+            WsClientCommand::Resume
+        );
+    }
+
+    // TC-WS-026: RequestPreview command deserializes its fields
+    #[test]
+    fn test_client_command_deserializes_request_preview() {
+        let command: WsClientCommand =
+            serde_json::from_str(r#"{"type":"request_preview","page_number":3,"stage":"deskewed"}"#)
+                .unwrap();
+        assert_eq!(
+            command,
+            WsClientCommand::RequestPreview {
+                page_number: 3,
+                stage: "deskewed".to_string()
+            }
+        );
+    }
+
+    // TC-WS-027: SetPreviewInterval command deserializes its field
+    #[test]
+    fn test_client_command_deserializes_set_preview_interval() {
+        let command: WsClientCommand =
+            serde_json::from_str(r#"{"type":"set_preview_interval","ms":500}"#).unwrap();
+        assert_eq!(command, WsClientCommand::SetPreviewInterval { ms: 500 });
+    }
+
+    // TC-WS-028: Unknown command type fails to deserialize
+    #[test]
+    fn test_client_command_unknown_type_errors() {
+        assert!(serde_json::from_str::<WsClientCommand>(r#"{"type":"not_a_command"}"#).is_err());
+    }
+
+    // TC-WS-029: take_command_receiver hands out the receiver exactly once
+    #[test]
+    fn test_take_command_receiver_only_once() {
+        let broadcaster = WsBroadcaster::new();
+        assert!(broadcaster.take_command_receiver().is_some());
+        assert!(broadcaster.take_command_receiver().is_none());
+    }
+
+    // TC-WS-030: forward_client_command sends a valid command to the channel
+    #[tokio::test]
+    async fn test_forward_client_command_sends_valid_command() {
+        let broadcaster = WsBroadcaster::new();
+        let mut command_rx = broadcaster.take_command_receiver().unwrap();
+        let job_id = Uuid::new_v4();
+
+        forward_client_command(job_id, r#"{"type":"cancel"}"#, &broadcaster.command_sender())
+            .await
+            .unwrap();
+
+        let (received_job_id, command) = command_rx.recv().await.unwrap();
+        assert_eq!(received_job_id, job_id);
+        assert_eq!(command, WsClientCommand::Cancel);
+    }
+
+    // TC-WS-031: forward_client_command reports an error for unparseable payloads
+    #[tokio::test]
+    async fn test_forward_client_command_rejects_unparseable_payload() {
+        let broadcaster = WsBroadcaster::new();
+        let _command_rx = broadcaster.take_command_receiver().unwrap();
+        let job_id = Uuid::new_v4();
+
+        let result =
+            forward_client_command(job_id, "not json at all", &broadcaster.command_sender()).await;
+        assert!(result.is_err());
+    }
+
+    // ============ Replay Buffer Tests (Phase 4.2) ============
+
+    // TC-WS-032: Broadcast assigns monotonically increasing sequence numbers
+    #[tokio::test]
+    async fn test_broadcast_assigns_increasing_seq() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster.broadcast_progress(job_id, 1, 10, "A").await;
+        broadcaster.broadcast_progress(job_id, 2, 10, "B").await;
+
+        let first = receiver.recv().await.unwrap();
+        let second = receiver.recv().await.unwrap();
+        assert_eq!(first.seq(), 1);
+        assert_eq!(second.seq(), 2);
+    }
+
+    // TC-WS-033: subscribe_with_replay hands back buffered messages with seq > from_seq
+    #[tokio::test]
+    async fn test_subscribe_with_replay_returns_buffered_messages_after_from_seq() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+
+        // Establish the channel and emit some history before the late joiner connects
+        let _early_subscriber = broadcaster.subscribe(job_id).await;
+        broadcaster.broadcast_progress(job_id, 1, 10, "A").await;
+        broadcaster.broadcast_progress(job_id, 2, 10, "B").await;
+        broadcaster.broadcast_progress(job_id, 3, 10, "C").await;
+
+        let (buffered, _receiver) = broadcaster.subscribe_with_replay(job_id, 1).await;
+
+        assert_eq!(buffered.len(), 2);
+        assert_eq!(buffered[0].seq(), 2);
+        assert_eq!(buffered[1].seq(), 3);
+    }
+
+    // TC-WS-034: Replay buffer is capped at REPLAY_BUFFER_CAPACITY
+    #[tokio::test]
+    async fn test_replay_buffer_is_capped() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let _subscriber = broadcaster.subscribe(job_id).await;
+
+        for i in 0..(REPLAY_BUFFER_CAPACITY + 10) {
+            broadcaster
+                .broadcast_progress(job_id, i as u32, 1000, "tick")
+                .await;
+        }
+
+        let (buffered, _receiver) = broadcaster.subscribe_with_replay(job_id, 0).await;
+        assert_eq!(buffered.len(), REPLAY_BUFFER_CAPACITY);
+        // Oldest retained message is the one whose seq survived eviction
+        assert_eq!(buffered[0].seq(), 11);
+    }
+
+    // TC-WS-035: Gap message carries the missed count and the seq to resume from
+    #[test]
+    fn test_gap_message_serialization() {
+        let job_id = Uuid::new_v4();
+        let msg = WsMessage::Gap {
+            job_id,
+            missed: 4,
+            resume_seq: 9,
+            seq: 9,
+        };
+
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"gap\""));
+        assert!(json.contains("\"missed\":4"));
+        assert!(json.contains("\"resume_seq\":9"));
+    }
+
+    // TC-WS-036: seq() reads the sequence number out of every variant
+    #[test]
+    fn test_seq_accessor_reads_every_variant() {
+        let job_id = Uuid::new_v4();
+        let msg = WsMessage::Error {
+            job_id,
+            message: "boom".to_string(),
+            seq: 42,
+        };
+        assert_eq!(msg.seq(), 42);
+    }
+
+    // ============ Binary Preview Transport Tests (Phase 4.2) ============
+
+    // TC-WS-037: Binary frame header round-trips job_id, page_number, stage, and dimensions
+    #[test]
+    fn test_encode_binary_preview_frame_header_layout() {
+        let job_id = Uuid::new_v4();
+        let frame = encode_binary_preview(job_id, 7, PreviewStageCode::Deskewed, 200, 280, b"fakejpeg");
+
+        assert_eq!(frame[0], PREVIEW_BINARY_MAGIC);
+        assert_eq!(&frame[1..17], job_id.as_bytes());
+        assert_eq!(u32::from_be_bytes(frame[17..21].try_into().unwrap()), 7);
+        assert_eq!(frame[21], PreviewStageCode::Deskewed as u8);
+        assert_eq!(u16::from_be_bytes(frame[22..24].try_into().unwrap()), 200);
+        assert_eq!(u16::from_be_bytes(frame[24..26].try_into().unwrap()), 280);
+        assert_eq!(&frame[26..], b"fakejpeg");
+    }
+
+    // TC-WS-038: encode_binary_preview_frame decodes base64 back to raw JPEG bytes
+    #[test]
+    fn test_encode_binary_preview_frame_decodes_base64_payload() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let job_id = Uuid::new_v4();
+        let preview_base64 = STANDARD.encode(b"rawjpegbytes");
+
+        let frame = encode_binary_preview_frame(job_id, 1, preview_stage::FINAL, 100, 140, &preview_base64)
+            .unwrap();
+
+        assert_eq!(&frame[26..], b"rawjpegbytes");
+        assert_eq!(frame[21], PreviewStageCode::Final as u8);
+    }
+
+    // TC-WS-039: unrecognized stage names fall back to None (caller uses JSON instead)
+    #[test]
+    fn test_encode_binary_preview_frame_rejects_unknown_stage() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        let preview_base64 = STANDARD.encode(b"data");
+        assert!(encode_binary_preview_frame(Uuid::new_v4(), 1, "not_a_stage", 10, 10, &preview_base64).is_none());
+    }
+
+    // TC-WS-040: the frame that send_ws_message would emit for a negotiated
+    // binary connection carries the same JPEG bytes as the JSON path; the
+    // binary header has no seq field, so seq is read from the message
+    #[test]
+    fn test_page_preview_frame_matches_message_fields() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let job_id = Uuid::new_v4();
+        let preview_base64 = STANDARD.encode(b"jpegdata");
+        let msg = WsMessage::PagePreview {
+            job_id,
+            page_number: 2,
+            preview_base64: preview_base64.clone(),
+            stage: preview_stage::ORIGINAL.to_string(),
+            width: 150,
+            height: 200,
+            unchanged_from: None,
+            too_large: false,
+            seq: 1,
+        };
+
+        let frame =
+            encode_binary_preview_frame(job_id, 2, preview_stage::ORIGINAL, 150, 200, &preview_base64).unwrap();
+        assert_eq!(&frame[26..], b"jpegdata");
+        assert_eq!(msg.seq(), 1);
+    }
+
+    // TC-WS-041: PreviewStageCode round-trips every preview_stage constant
+    #[test]
+    fn test_preview_stage_code_covers_every_stage_constant() {
+        for stage in [
+            preview_stage::ORIGINAL,
+            preview_stage::DESKEWED,
+            preview_stage::TRIMMED,
+            preview_stage::UPSCALED,
+            preview_stage::NORMALIZED,
+            preview_stage::COLOR_CORRECTED,
+            preview_stage::FINAL,
+        ] {
+            assert!(PreviewStageCode::from_stage_name(stage).is_some());
+        }
+    }
+
+    // ============ Preview Rate Limiting Tests (Phase 4.3) ============
+
+    // TC-WS-042: A burst of previews for the same (page_number, stage) collapses to one send
+    #[tokio::test]
+    async fn test_preview_burst_collapses_within_rate_limit_window() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::from_millis(200));
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        for i in 0..5 {
+            broadcaster
+                .broadcast_page_preview(job_id, 1, format!("frame{i}"), preview_stage::ORIGINAL, 10, 10)
+                .await;
+        }
+
+        let msg = receiver.recv().await.unwrap();
+        assert!(matches!(msg, WsMessage::PagePreview { .. }));
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(broadcaster.dropped_preview_count(job_id), 4);
+    }
+
+    // TC-WS-043: Different (page_number, stage) keys are throttled independently
+    #[tokio::test]
+    async fn test_preview_throttle_is_per_key() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::from_millis(200));
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "a".to_string(), preview_stage::ORIGINAL, 10, 10)
+            .await;
+        broadcaster
+            .broadcast_page_preview(job_id, 2, "b".to_string(), preview_stage::ORIGINAL, 10, 10)
+            .await;
+
+        assert!(receiver.recv().await.is_ok());
+        assert!(receiver.recv().await.is_ok());
+        assert_eq!(broadcaster.dropped_preview_count(job_id), 0);
+    }
+
+    // TC-WS-044: Once the rate limit window elapses, the next preview is sent
+    #[tokio::test]
+    async fn test_preview_sent_again_after_rate_limit_elapses() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::from_millis(20));
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "a".to_string(), preview_stage::ORIGINAL, 10, 10)
+            .await;
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "b".to_string(), preview_stage::ORIGINAL, 10, 10)
+            .await;
+
+        assert!(receiver.recv().await.is_ok());
+        assert!(receiver.recv().await.is_ok());
+        assert_eq!(broadcaster.dropped_preview_count(job_id), 0);
+    }
+
+    // TC-WS-045: Critical messages always bypass the preview throttle
+    #[tokio::test]
+    async fn test_critical_messages_bypass_preview_throttle() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::from_secs(60));
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "a".to_string(), preview_stage::ORIGINAL, 10, 10)
+            .await;
+        broadcaster
+            .broadcast_status_change(job_id, JobStatus::Queued, JobStatus::Processing)
+            .await;
+        broadcaster.broadcast_completed(job_id, 1.0, 1).await;
+        broadcaster.broadcast_error(job_id, "boom").await;
+
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            WsMessage::PagePreview { .. }
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            WsMessage::StatusChange { .. }
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            WsMessage::Completed { .. }
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            WsMessage::Error { .. }
+        ));
+    }
+
+    // TC-WS-046: dropped_preview_count starts at zero for an unseen job
+    #[test]
+    fn test_dropped_preview_count_defaults_to_zero() {
+        let broadcaster = WsBroadcaster::new();
+        assert_eq!(broadcaster.dropped_preview_count(Uuid::new_v4()), 0);
+    }
+
+    // ============ Multiplexed Connection Tests (Phase 4.4) ============
+
+    // TC-WS-047: Subscribe/Unsubscribe commands deserialize with their job_id
+    #[test]
+    fn test_client_command_deserializes_subscribe_and_unsubscribe() {
+        let job_id = Uuid::new_v4();
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(&format!(r#"{{"type":"subscribe","job_id":"{job_id}"}}"#))
+                .unwrap(),
+            WsClientCommand::Subscribe { job_id }
+        );
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(&format!(r#"{{"type":"unsubscribe","job_id":"{job_id}"}}"#))
+                .unwrap(),
+            WsClientCommand::Unsubscribe { job_id }
+        );
+    }
+
+    // TC-WS-048: Subscribing fans a job's broadcast messages into the shared channel
+    #[tokio::test]
+    async fn test_apply_multiplex_command_subscribe_forwards_job_messages() {
+        let broadcaster = Arc::new(WsBroadcaster::new());
+        let job_id = Uuid::new_v4();
+        let (fanin_tx, mut fanin_rx) = mpsc::channel::<WsMessage>(16);
+        let mut subscriptions = HashMap::new();
+
+        apply_multiplex_command(
+            &format!(r#"{{"type":"subscribe","job_id":"{job_id}"}}"#),
+            &broadcaster,
+            &fanin_tx,
+            &mut subscriptions,
+        )
+        .await
+        .unwrap();
+        assert_eq!(subscriptions.len(), 1);
+
+        // Give the spawned forwarder a chance to subscribe before broadcasting
+        tokio::task::yield_now().await;
+        broadcaster.broadcast_progress(job_id, 1, 10, "Step").await;
+
+        let msg = fanin_rx.recv().await.unwrap();
+        match msg {
+            WsMessage::Progress { job_id: received, .. } => assert_eq!(received, job_id),
+            _ => panic!("expected Progress message"),
+        }
+    }
+
+    // TC-WS-049: Unsubscribing removes and aborts the forwarder task
+    #[tokio::test]
+    async fn test_apply_multiplex_command_unsubscribe_removes_forwarder() {
+        let broadcaster = Arc::new(WsBroadcaster::new());
+        let job_id = Uuid::new_v4();
+        let (fanin_tx, _fanin_rx) = mpsc::channel::<WsMessage>(16);
+        let mut subscriptions = HashMap::new();
+
+        apply_multiplex_command(
+            &format!(r#"{{"type":"subscribe","job_id":"{job_id}"}}"#),
+            &broadcaster,
+            &fanin_tx,
+            &mut subscriptions,
+        )
+        .await
+        .unwrap();
+        assert_eq!(subscriptions.len(), 1);
+
+        apply_multiplex_command(
+            &format!(r#"{{"type":"unsubscribe","job_id":"{job_id}"}}"#),
+            &broadcaster,
+            &fanin_tx,
+            &mut subscriptions,
+        )
+        .await
+        .unwrap();
+        assert!(subscriptions.is_empty());
+    }
+
+    // TC-WS-050: Job-scoped commands (e.g. cancel) are rejected on the multiplexed endpoint
+    #[tokio::test]
+    async fn test_apply_multiplex_command_rejects_job_scoped_commands() {
+        let broadcaster = Arc::new(WsBroadcaster::new());
+        let (fanin_tx, _fanin_rx) = mpsc::channel::<WsMessage>(16);
+        let mut subscriptions = HashMap::new();
+
+        let result =
+            apply_multiplex_command(r#"{"type":"cancel"}"#, &broadcaster, &fanin_tx, &mut subscriptions).await;
+        assert!(result.is_err());
+    }
+
+    // ============ Replay Eviction / Resync Tests (Phase 4.5) ============
+
+    // TC-WS-051: evicted_count starts at zero and increments once the replay
+    // buffer overflows and starts dropping its oldest entries
+    #[tokio::test]
+    async fn test_evicted_count_tracks_buffer_overflow() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+
+        let _receiver = broadcaster.subscribe(job_id).await;
+        assert_eq!(broadcaster.evicted_count(job_id).await, 0);
+
+        for step in 0..(REPLAY_BUFFER_CAPACITY + 5) {
+            broadcaster
+                .broadcast_progress(job_id, step as u32, 1000, "Step")
+                .await;
+        }
+
+        assert_eq!(broadcaster.evicted_count(job_id).await, 5);
+    }
+
+    // TC-WS-052: Resync message carries from_seq/dropped and serializes
+    #[test]
+    fn test_resync_message_serialization() {
+        let job_id = Uuid::new_v4();
+
+        let msg = WsMessage::Resync {
+            job_id,
+            from_seq: 42,
+            dropped: 3,
+            seq: 43,
+        };
+
+        assert_eq!(msg.seq(), 43);
+
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"resync\""));
+        assert!(json.contains("\"from_seq\":42"));
+        assert!(json.contains("\"dropped\":3"));
+    }
+
+    // TC-WS-053: evicted_count is zero for a job that has no channel yet
+    #[tokio::test]
+    async fn test_evicted_count_defaults_to_zero_for_unknown_job() {
+        let broadcaster = WsBroadcaster::new();
+        assert_eq!(broadcaster.evicted_count(Uuid::new_v4()).await, 0);
+    }
+
+    // ============ Preview Dedup / Size-Guard Tests (Phase 4.6) ============
+
+    // TC-WS-054: A stage whose content hash matches the previous stage sent
+    // for the same page is collapsed into an unchanged_from marker
+    #[tokio::test]
+    async fn test_broadcast_page_preview_dedups_identical_content() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::ZERO);
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "samebytes".to_string(), preview_stage::ORIGINAL, 200, 300)
+            .await;
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "samebytes".to_string(), preview_stage::DESKEWED, 200, 300)
+            .await;
+
+        let _first = receiver.recv().await.unwrap();
+        let second = receiver.recv().await.unwrap();
+        match second {
+            WsMessage::PagePreview {
+                preview_base64,
+                unchanged_from,
+                too_large,
+                ..
+            } => {
+                assert_eq!(preview_base64, "");
+                assert_eq!(unchanged_from, Some(preview_stage::ORIGINAL.to_string()));
+                assert!(!too_large);
+            }
+            _ => panic!("Expected PagePreview message"),
+        }
+    }
+
+    // TC-WS-055: Different content for the same page is sent in full, not deduped
+    #[tokio::test]
+    async fn test_broadcast_page_preview_does_not_dedup_different_content() {
+        let broadcaster = WsBroadcaster::with_preview_rate_limit(std::time::Duration::ZERO);
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "bytes-a".to_string(), preview_stage::ORIGINAL, 200, 300)
+            .await;
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "bytes-b".to_string(), preview_stage::DESKEWED, 200, 300)
+            .await;
+
+        let _first = receiver.recv().await.unwrap();
+        let second = receiver.recv().await.unwrap();
+        match second {
+            WsMessage::PagePreview {
+                preview_base64,
+                unchanged_from,
+                ..
+            } => {
+                assert_eq!(preview_base64, "bytes-b");
+                assert_eq!(unchanged_from, None);
+            }
+            _ => panic!("Expected PagePreview message"),
+        }
+    }
+
+    // TC-WS-056: A payload over max_preview_bytes is replaced with a too_large marker
+    #[tokio::test]
+    async fn test_broadcast_page_preview_marks_oversized_payload_too_large() {
+        let broadcaster = WsBroadcaster::with_max_preview_bytes(4);
+        let job_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe(job_id).await;
+
+        broadcaster
+            .broadcast_page_preview(job_id, 1, "way-too-big-for-the-limit".to_string(), preview_stage::ORIGINAL, 200, 300)
+            .await;
+
+        let msg = receiver.recv().await.unwrap();
+        match msg {
+            WsMessage::PagePreview {
+                preview_base64,
+                too_large,
+                ..
+            } => {
+                assert_eq!(preview_base64, "");
+                assert!(too_large);
+            }
+            _ => panic!("Expected PagePreview message"),
+        }
+    }
+
+    // ============ Preview Prefs Tests (Phase 4.7) ============
+
+    // TC-WS-057: Default prefs request PREVIEW_WIDTH and every known stage
+    #[test]
+    fn test_preview_prefs_default_matches_pre_chunk12_3_behavior() {
+        let prefs = PreviewPrefs::default();
+        assert_eq!(prefs.width, PREVIEW_WIDTH);
+        assert!(prefs.stages.contains(preview_stage::FINAL));
+        assert_eq!(prefs.stages.len(), preview_stage::ALL.len());
+    }
+
+    // TC-WS-058: With no registered prefs, active widths fall back to PREVIEW_WIDTH
+    // and every stage is wanted
+    #[tokio::test]
+    async fn test_active_preview_widths_defaults_without_registered_prefs() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+
+        assert_eq!(broadcaster.active_preview_widths(job_id), vec![PREVIEW_WIDTH]);
+        assert!(broadcaster.stage_is_wanted(job_id, preview_stage::FINAL));
+    }
+
+    // TC-WS-059: Registering prefs for two viewers unions their widths (largest first)
+    // and their stage sets
+    #[tokio::test]
+    async fn test_subscribe_with_prefs_unions_widths_and_stages() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+
+        let mut thumbnail_stages = std::collections::HashSet::new();
+        thumbnail_stages.insert(preview_stage::FINAL.to_string());
+        let _thumb_rx = broadcaster
+            .subscribe_with_prefs(
+                job_id,
+                u64::MAX,
+                PreviewPrefs {
+                    width: 200,
+                    stages: thumbnail_stages,
+                },
+            )
+            .await;
+
+        let mut inspector_stages = std::collections::HashSet::new();
+        inspector_stages.insert(preview_stage::DESKEWED.to_string());
+        let _inspector_rx = broadcaster
+            .subscribe_with_prefs(
+                job_id,
+                u64::MAX,
+                PreviewPrefs {
+                    width: 1024,
+                    stages: inspector_stages,
+                },
+            )
+            .await;
+
+        assert_eq!(broadcaster.active_preview_widths(job_id), vec![1024, 200]);
+        assert!(broadcaster.stage_is_wanted(job_id, preview_stage::FINAL));
+        assert!(broadcaster.stage_is_wanted(job_id, preview_stage::DESKEWED));
+        assert!(!broadcaster.stage_is_wanted(job_id, preview_stage::ORIGINAL));
+    }
+
+    // TC-WS-059b: set_preview_prefs deserializes its job-scoped fields
+    #[test]
+    fn test_set_preview_prefs_command_deserializes() {
+        let job_id = Uuid::new_v4();
+        let command: WsClientCommand = serde_json::from_str(&format!(
+            r#"{{"type":"set_preview_prefs","job_id":"{job_id}","width":512,"stages":["final","deskewed"]}}"#
+        ))
+        .unwrap();
+        assert_eq!(
+            command,
+            WsClientCommand::SetPreviewPrefs {
+                job_id,
+                width: 512,
+                stages: vec!["final".to_string(), "deskewed".to_string()],
+            }
+        );
+    }
+
+    // TC-WS-059c: ?preview_width=/?preview_stages= negotiate PreviewPrefs;
+    // with neither present the connection falls back to plain replay
+    #[test]
+    fn test_ws_query_params_preview_prefs() {
+        let none = WsQueryParams {
+            from_seq: 0,
+            binary: None,
+            session_id: None,
+            preview_width: None,
+            preview_stages: None,
+        };
+        assert!(none.preview_prefs().is_none());
+
+        let negotiated = WsQueryParams {
+            from_seq: 0,
+            binary: None,
+            session_id: None,
+            preview_width: Some(512),
+            preview_stages: Some("final, deskewed".to_string()),
+        };
+        let prefs = negotiated.preview_prefs().unwrap();
+        assert_eq!(prefs.width, 512);
+        assert!(prefs.stages.contains(preview_stage::FINAL));
+        assert!(prefs.stages.contains(preview_stage::DESKEWED));
+        assert_eq!(prefs.stages.len(), 2);
+    }
+
+    // ============ Session Subsystem Tests (Phase 4.8) ============
+
+    // TC-WS-060: Ack command deserializes its field
+    #[test]
+    fn test_client_command_deserializes_ack() {
+        assert_eq!(
+            serde_json::from_str::<WsClientCommand>(r#"{"type":"ack","seq":7}"#).unwrap(),
+            WsClientCommand::Ack { seq: 7 }
+        );
+    }
+
+    // TC-WS-061: A fresh session starts at its registered acked seq, and
+    // resume_session returns None for a job_id mismatch
+    #[tokio::test]
+    async fn test_register_and_resume_session() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let other_job = Uuid::new_v4();
+
+        let session_id = broadcaster.register_session(job_id, 5).await;
+        assert_eq!(broadcaster.session_acked_seq(session_id).await, Some(5));
+        assert_eq!(broadcaster.resume_session(session_id, job_id).await, Some(5));
+        assert_eq!(broadcaster.resume_session(session_id, other_job).await, None);
+    }
+
+    // TC-WS-062: ack_session only moves acked_seq forward, never backward
+    #[tokio::test]
+    async fn test_ack_session_only_advances() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let session_id = broadcaster.register_session(job_id, 0).await;
+
+        broadcaster.ack_session(session_id, 10).await;
+        assert_eq!(broadcaster.session_acked_seq(session_id).await, Some(10));
+
+        broadcaster.ack_session(session_id, 3).await;
+        assert_eq!(broadcaster.session_acked_seq(session_id).await, Some(10));
+    }
+
+    // TC-WS-063: Once every live session for a job has acked past an entry,
+    // it's trimmed from the replay buffer without counting as an eviction
+    #[tokio::test]
+    async fn test_broadcast_trims_buffer_once_all_sessions_ack() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let _receiver = broadcaster.subscribe(job_id).await;
+        let session_id = broadcaster.register_session(job_id, 0).await;
+
+        for step in 0..5 {
+            broadcaster
+                .broadcast_progress(job_id, step, 10, "Step")
+                .await;
+        }
+
+        broadcaster.ack_session(session_id, 3).await;
+        broadcaster
+            .broadcast_progress(job_id, 5, 10, "Step")
+            .await;
+
+        let (buffered, _rx) = broadcaster.subscribe_with_replay(job_id, 0).await;
+        assert!(buffered.iter().all(|msg| msg.seq() > 3));
+        assert_eq!(broadcaster.evicted_count(job_id).await, 0);
+    }
+
+    // TC-WS-064: SessionInit message serializes its fields
+    #[test]
+    fn test_session_init_message_serialization() {
+        let session_id = Uuid::new_v4();
+        let msg = WsMessage::SessionInit {
+            session_id,
+            last_seq: 12,
+            seq: 1,
+        };
+
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"session_init\""));
+        assert!(json.contains("\"last_seq\":12"));
+    }
+
+    // ============ Observability Tests (Phase 4.9) ============
+
+    // TC-WS-065: job_metrics is None before a job has ever been subscribed to
+    #[tokio::test]
+    async fn test_job_metrics_none_for_unknown_job() {
+        let broadcaster = WsBroadcaster::new();
+        assert!(broadcaster.job_metrics(Uuid::new_v4()).await.is_none());
+    }
+
+    // TC-WS-066: job_metrics tallies messages/bytes sent, drops, and
+    // active subscriber count
+    #[tokio::test]
+    async fn test_job_metrics_tracks_throughput_and_drops() {
+        let broadcaster = WsBroadcaster::new();
+        let job_id = Uuid::new_v4();
+        let _rx1 = broadcaster.subscribe(job_id).await;
+        let _rx2 = broadcaster.subscribe(job_id).await;
+
+        broadcaster.broadcast_progress(job_id, 1, 10, "Step").await;
+        broadcaster.broadcast_progress(job_id, 2, 10, "Step").await;
+
+        let metrics = broadcaster.job_metrics(job_id).await.unwrap();
+        assert_eq!(metrics.messages_sent, 2);
+        assert!(metrics.bytes_sent > 0);
+        assert_eq!(metrics.drops, 0);
+        assert_eq!(metrics.active_subscribers, 2);
+    }
 }